@@ -1,10 +1,16 @@
-mod parser;
-mod tui;
-
-use clap::{Parser as ClapParser, Subcommand};
-use parser::{Addr2LineResolver, ParseErrorInfo, StraceOutput, StraceParser, SummaryStats};
-use std::collections::HashSet;
-use std::process::Command;
+use strace_tui::{parser, tui};
+
+use clap::{ArgGroup, Parser as ClapParser, Subcommand, ValueEnum};
+use parser::{
+    Addr2LineResolver, ChromeTrace, InputFormat, ParseErrorInfo, ProgramOutputLine,
+    ResolveProgress, ResolverOptions, StraceOutput, StraceParser, SummaryStats, fd_leaks,
+    folded_stacks, format_fd_leaks, format_folded,
+};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::{BufReader, IsTerminal, PipeReader};
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
 use tempfile::NamedTempFile;
 
 #[derive(ClapParser)]
@@ -18,33 +24,86 @@ struct Cli {
 #[derive(Subcommand)]
 enum Commands {
     /// Parse an existing strace output file
+    #[command(group(ArgGroup::new("export").args(["json", "format"])))]
     Parse {
-        /// Input strace output file
-        #[arg(value_name = "FILE")]
-        input: String,
+        /// Input strace output files, or `-` for stdin (the TUI can switch between several)
+        #[arg(value_name = "FILE", required = true, num_args = 1..)]
+        inputs: Vec<String>,
 
         /// Output JSON instead of opening TUI
         #[arg(long)]
         json: bool,
 
-        /// Output file (only with --json)
-        #[arg(short, long, value_name = "FILE", requires = "json")]
+        /// Output in another format instead of opening TUI
+        #[arg(long, value_enum, value_name = "FORMAT")]
+        format: Option<ExportFormat>,
+
+        /// Output file (only with --json or --format)
+        #[arg(short, long, value_name = "FILE", requires = "export")]
         output: Option<String>,
 
-        /// Resolve backtraces using addr2line (only with --json)
-        #[arg(short, long, requires = "json")]
+        /// Resolve backtraces using addr2line (only with --json or --format)
+        #[arg(short, long, requires = "export")]
         resolve: bool,
 
-        /// Pretty print JSON output (only with --json)
-        #[arg(short, long, requires = "json")]
+        /// Pretty print JSON output (only with --json or --format)
+        #[arg(short, long, requires = "export")]
         pretty: bool,
 
+        /// Include the processes with their parents and children in the JSON output
+        #[arg(long, requires = "json")]
+        process_tree: bool,
+
         /// Merge resumed syscalls into unfinished syscalls
         #[arg(long)]
         merge_resumed: bool,
+
+        /// Stop parsing after N entries, to quickly peek at a huge trace
+        #[arg(long, value_name = "N")]
+        max_entries: Option<usize>,
+
+        /// Fetch missing debug files from the servers in DEBUGINFOD_URLS when resolving
+        #[arg(long)]
+        debuginfod: bool,
+
+        /// Don't read or save the resolved frames cached on disk
+        #[arg(long)]
+        no_resolve_cache: bool,
+
+        /// Look for the traced binaries under DIR when resolving, for a trace captured on
+        /// another machine (e.g. /lib/libc.so.6 is read from DIR/lib/libc.so.6)
+        #[arg(long, value_name = "DIR")]
+        sysroot: Option<PathBuf>,
+
+        /// TOML file of path prefixes to rewrite when resolving, for binaries that are elsewhere
+        /// on this machine, like `"/app/bin" = "/home/me/build"`
+        #[arg(long, value_name = "FILE")]
+        path_map: Option<String>,
+
+        /// Don't capture the mouse, keeping the terminal's native text selection
+        #[arg(long)]
+        no_mouse: bool,
+
+        /// Colors for a dark or light terminal background (default: guessed from COLORFGBG)
+        #[arg(long, value_enum, value_name = "THEME")]
+        theme: Option<tui::ThemeName>,
+
+        /// Keep reading the files as they grow, like `tail -f`, showing new syscalls as they come
+        #[arg(short, long, conflicts_with = "export")]
+        follow: bool,
+
+        /// Tool that wrote the input, strace or ltrace (library calls)
+        #[arg(long, value_enum, value_name = "TOOL", default_value = "strace")]
+        input_format: InputFormat,
+
+        /// Drop the bracketed tags and timestamps that a logger put before each line, like a
+        /// `[timestamp] `, up to where the strace output starts
+        #[arg(long)]
+        strip_log_prefix: bool,
     },
 
     /// Run strace on a command and parse the output
+    #[command(group(ArgGroup::new("export").args(["json", "format"])))]
     Trace {
         /// Command to trace
         #[arg(required = true, num_args = 1.., value_name = "CMD", trailing_var_arg = true)]
@@ -54,26 +113,59 @@ enum Commands {
         #[arg(long)]
         json: bool,
 
-        /// Output file (only with --json)
-        #[arg(short, long, value_name = "FILE", requires = "json")]
+        /// Output in another format instead of opening TUI
+        #[arg(long, value_enum, value_name = "FORMAT")]
+        format: Option<ExportFormat>,
+
+        /// Output file (only with --json or --format)
+        #[arg(short, long, value_name = "FILE", requires = "export")]
         output: Option<String>,
 
-        /// Resolve backtraces using addr2line (only with --json)
-        #[arg(short, long, requires = "json")]
+        /// Resolve backtraces using addr2line (only with --json or --format)
+        #[arg(short, long, requires = "export")]
         resolve: bool,
 
-        /// Pretty print JSON output (only with --json)
-        #[arg(short, long, requires = "json")]
+        /// Pretty print JSON output (only with --json or --format)
+        #[arg(short, long, requires = "export")]
         pretty: bool,
 
+        /// Include the processes with their parents and children in the JSON output
+        #[arg(long, requires = "json")]
+        process_tree: bool,
+
         /// Path for strace output (default: temp file, deleted after parsing)
         #[arg(long, value_name = "FILE")]
         trace_file: Option<String>,
 
+        /// Read the strace output through a pipe as the command runs, showing syscalls live
+        /// instead of once it ends. The command's own output is captured along with it.
+        #[arg(long, conflicts_with = "trace_file")]
+        stream: bool,
+
         /// Merge resumed syscalls into unfinished syscalls
         #[arg(long)]
         merge_resumed: bool,
 
+        /// Stop parsing after N entries, to quickly peek at a huge trace
+        #[arg(long, value_name = "N")]
+        max_entries: Option<usize>,
+
+        /// Fetch missing debug files from the servers in DEBUGINFOD_URLS when resolving
+        #[arg(long)]
+        debuginfod: bool,
+
+        /// Don't read or save the resolved frames cached on disk
+        #[arg(long)]
+        no_resolve_cache: bool,
+
+        /// Don't capture the mouse, keeping the terminal's native text selection
+        #[arg(long)]
+        no_mouse: bool,
+
+        /// Colors for a dark or light terminal background (default: guessed from COLORFGBG)
+        #[arg(long, value_enum, value_name = "THEME")]
+        theme: Option<tui::ThemeName>,
+
         /// Flags to pass to strace.
         #[arg(
             long,
@@ -81,44 +173,193 @@ enum Commands {
             default_value = "-tt -T -k -f -s 1024"
         )]
         strace_flags: String,
+
+        /// Extra argument for strace, after the flags (repeatable, e.g.
+        /// `--strace-arg=-e --strace-arg=trace=network`)
+        #[arg(long, value_name = "ARG", allow_hyphen_values = true)]
+        strace_arg: Vec<String>,
+
+        /// strace executable to run
+        #[arg(
+            long,
+            value_name = "PATH",
+            env = "STRACE_TUI_STRACE",
+            default_value = "strace"
+        )]
+        strace_bin: String,
     },
 }
 
+#[derive(Clone, Copy, ValueEnum)]
+enum ExportFormat {
+    /// Parsed entries, same as --json
+    Json,
+    /// Chrome Trace Event JSON, to load in Perfetto or chrome://tracing
+    Chrome,
+    /// Backtraces as folded stacks for flamegraph.pl, resolving them first
+    Folded,
+    /// File descriptors never closed before the end of the trace, and what opened them
+    FdLeaks,
+    /// Time, calls and errors per syscall, in the table `strace -c` prints
+    SummaryTable,
+}
+
 fn main() {
     let cli = Cli::parse();
 
     match cli.command {
         Commands::Parse {
-            input,
+            inputs,
             json,
+            format,
             output,
             resolve,
             pretty,
+            process_tree,
             merge_resumed,
+            max_entries,
+            debuginfod,
+            no_resolve_cache,
+            sysroot,
+            path_map,
+            no_mouse,
+            theme,
+            follow,
+            input_format,
+            strip_log_prefix,
         } => {
-            if json {
-                parse_file_json(&input, output, resolve, pretty, merge_resumed);
+            let input_options = InputOptions {
+                format: input_format,
+                strip_log_prefix,
+            };
+            let resolver_options = ResolverOptions {
+                debuginfod,
+                disk_cache: !no_resolve_cache,
+                sysroot,
+                path_prefixes: path_map
+                    .map(|path| load_path_map(&path))
+                    .unwrap_or_default(),
+            };
+            if let Some(format) = format.or(json.then_some(ExportFormat::Json)) {
+                if inputs.len() > 1 {
+                    eprintln!("Error: --json and --format accept a single input file");
+                    std::process::exit(1);
+                }
+                parse_file_export(
+                    &inputs[0],
+                    Export {
+                        format,
+                        output,
+                        pretty,
+                        process_tree,
+                    },
+                    resolve,
+                    merge_resumed,
+                    max_entries,
+                    resolver_options,
+                    input_options,
+                );
             } else {
-                parse_file_tui(&input, merge_resumed);
+                if follow && inputs.iter().any(|input| input == "-") {
+                    eprintln!("Error: --follow needs files, not stdin");
+                    std::process::exit(1);
+                }
+                parse_files_tui(
+                    &inputs,
+                    merge_resumed,
+                    max_entries,
+                    follow,
+                    input_options,
+                    tui_config(no_mouse, &resolver_options, theme),
+                );
             }
         }
         Commands::Trace {
             command,
             json,
+            format,
             output,
             resolve,
             pretty,
+            process_tree,
             trace_file,
             merge_resumed,
+            max_entries,
+            debuginfod,
+            no_resolve_cache,
+            no_mouse,
+            theme,
             strace_flags,
+            stream,
+            strace_arg,
+            strace_bin,
         } => {
-            let is_temp = trace_file.is_none();
-            let trace_path = run_strace(command, trace_file, strace_flags);
+            let strace_args = strace_args(&strace_flags, strace_arg);
+            let resolver_options = ResolverOptions {
+                debuginfod,
+                disk_cache: !no_resolve_cache,
+                sysroot: None,
+                path_prefixes: Vec::new(),
+            };
+            if stream {
+                let (child, reader) = spawn_strace(&strace_bin, &command, &strace_args);
+                if let Some(format) = format.or(json.then_some(ExportFormat::Json)) {
+                    stream_export(
+                        child,
+                        reader,
+                        Export {
+                            format,
+                            output,
+                            pretty,
+                            process_tree,
+                        },
+                        resolve,
+                        merge_resumed,
+                        max_entries,
+                        resolver_options,
+                    );
+                } else {
+                    stream_tui(
+                        child,
+                        reader,
+                        merge_resumed,
+                        max_entries,
+                        tui_config(no_mouse, &resolver_options, theme),
+                    );
+                }
+                return;
+            }
 
-            if json {
-                parse_file_json(&trace_path, output, resolve, pretty, merge_resumed);
+            let is_temp = trace_file.is_none();
+            let trace_path = run_strace(&strace_bin, command, trace_file, &strace_args);
+
+            if let Some(format) = format.or(json.then_some(ExportFormat::Json)) {
+                parse_file_export(
+                    &trace_path,
+                    Export {
+                        format,
+                        output,
+                        pretty,
+                        process_tree,
+                    },
+                    resolve,
+                    merge_resumed,
+                    max_entries,
+                    resolver_options,
+                    InputOptions::default(),
+                );
             } else {
-                parse_file_tui(&trace_path, merge_resumed);
+                // A temporary trace is never opened again
+                let mut config = tui_config(no_mouse, &resolver_options, theme);
+                config.sessions &= !is_temp;
+                parse_files_tui(
+                    std::slice::from_ref(&trace_path),
+                    merge_resumed,
+                    max_entries,
+                    false,
+                    InputOptions::default(),
+                    config,
+                );
             }
 
             if is_temp {
@@ -129,42 +370,125 @@ fn main() {
     }
 }
 
-fn parse_file_tui(input: &str, merge_resumed: bool) {
-    // Parse the strace output
-    let mut parser = StraceParser::new();
-    let entries = match parser.parse_file(input, merge_resumed) {
-        Ok(e) => e,
-        Err(err) => {
-            eprintln!("Error parsing file: {}", err);
+/// Load the TUI config, with command line flags taking precedence
+fn tui_config(
+    no_mouse: bool,
+    resolver_options: &ResolverOptions,
+    theme: Option<tui::ThemeName>,
+) -> tui::Config {
+    let mut config = tui::Config::load();
+    config.theme = theme.or(config.theme).or_else(tui::ThemeName::detect);
+    if no_mouse {
+        config.mouse = false;
+    }
+    config.resolver = ResolverOptions {
+        debuginfod: resolver_options.debuginfod || config.debuginfod,
+        disk_cache: resolver_options.disk_cache && config.resolve_cache,
+        ..resolver_options.clone()
+    };
+    warn_resolver_options(&config.resolver);
+    config
+}
+
+fn parse_files_tui(
+    inputs: &[String],
+    merge_resumed: bool,
+    max_entries: Option<usize>,
+    follow: bool,
+    input_options: InputOptions,
+    config: tui::Config,
+) {
+    let mut traces = Vec::new();
+
+    for input in inputs {
+        // Parse the strace output
+        let mut parser = input_options.parser(max_entries);
+        let (entries, tail) = if follow {
+            match parse_followed(&mut parser, input, merge_resumed) {
+                Ok((entries, tail)) => (entries, Some(tail)),
+                Err(err) => {
+                    eprintln!("Error reading file {}: {}", input, err);
+                    std::process::exit(1);
+                }
+            }
+        } else {
+            match parse_input(&mut parser, input, merge_resumed) {
+                Ok(e) => (e, None),
+                Err(err) => {
+                    eprintln!("Error parsing file {}: {}", input, err);
+                    std::process::exit(1);
+                }
+            }
+        };
+
+        // A followed file may just not have any yet
+        if entries.is_empty() && !follow {
+            eprintln!("No syscalls found in trace file {}", input);
             std::process::exit(1);
         }
-    };
 
-    if entries.is_empty() {
-        eprintln!("No syscalls found in trace file");
-        std::process::exit(1);
+        // Generate summary
+        let summary = SummaryStats::from_entries(&entries);
+
+        traces.push(tui::TraceFile {
+            entries,
+            summary,
+            file_path: Some(display_path(input).to_string()),
+            program_output: std::mem::take(&mut parser.program_output),
+            errors: parser.take_errors(),
+            truncated_at: max_entries.filter(|_| parser.truncated),
+            follow: tail.map(|tail| tui::Follow {
+                source: tui::LineSource::File(tail),
+                parser,
+                merge_resumed,
+            }),
+        });
     }
 
-    // Generate summary
-    let summary = generate_summary(&entries);
-
     // Run TUI
-    if let Err(e) = tui::run_tui(entries, summary, Some(input.to_string())) {
+    if let Err(e) = tui::run_tui(traces, config) {
         eprintln!("TUI error: {}", e);
         std::process::exit(1);
     }
 }
 
-fn parse_file_json(
+/// How to read the lines of the input files
+#[derive(Clone, Copy, Default)]
+struct InputOptions {
+    format: InputFormat,
+    strip_log_prefix: bool,
+}
+
+impl InputOptions {
+    fn parser(self, max_entries: Option<usize>) -> StraceParser {
+        let mut parser = StraceParser::new();
+        parser.max_entries = max_entries;
+        parser.input_format = self.format;
+        parser.strip_log_prefix = self.strip_log_prefix;
+        parser
+    }
+}
+
+/// How to write a parsed trace, instead of opening the TUI
+struct Export {
+    format: ExportFormat,
+    output: Option<String>, // Stdout if None
+    pretty: bool,
+    process_tree: bool, // Add the process tree to the JSON output
+}
+
+fn parse_file_export(
     input: &str,
-    output: Option<String>,
+    export: Export,
     resolve: bool,
-    pretty: bool,
     merge_resumed: bool,
+    max_entries: Option<usize>,
+    resolver_options: ResolverOptions,
+    input_options: InputOptions,
 ) {
     // Parse the strace output
-    let mut parser = StraceParser::new();
-    let mut entries = match parser.parse_file(input, merge_resumed) {
+    let mut parser = input_options.parser(max_entries);
+    let entries = match parse_input(&mut parser, input, merge_resumed) {
         Ok(e) => e,
         Err(err) => {
             eprintln!("Error parsing file: {}", err);
@@ -172,25 +496,309 @@ fn parse_file_json(
         }
     };
 
-    // Resolve backtraces if requested
-    if resolve {
+    // Notes written in the TUI are kept with the rest of the file's session, by line number
+    let session_notes = tui::Session::path_for(input)
+        .and_then(|path| tui::Session::load(&path))
+        .map(|session| session.notes)
+        .unwrap_or_default();
+    let notes = entries
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, entry)| Some((idx, session_notes.get(&entry.line_number)?.clone())))
+        .collect();
+
+    export_entries(
+        parser,
+        entries,
+        notes,
+        export,
+        resolve,
+        max_entries,
+        resolver_options,
+    );
+}
+
+/// Write parsed entries in the export format, resolving their backtraces first if asked to
+fn export_entries(
+    mut parser: StraceParser,
+    mut entries: Vec<parser::SyscallEntry>,
+    notes: BTreeMap<usize, String>,
+    export: Export,
+    resolve: bool,
+    max_entries: Option<usize>,
+    resolver_options: ResolverOptions,
+) {
+    // Resolve backtraces if requested, or needed for the stacks
+    if resolve || matches!(export.format, ExportFormat::Folded) {
         eprintln!("Resolving backtraces with addr2line...");
-        let mut resolver = Addr2LineResolver::new();
+        warn_resolver_options(&resolver_options);
+        let mut resolver = Addr2LineResolver::from_options(&resolver_options);
+        resolve_with_progress(&mut resolver, &mut entries);
 
-        for entry in entries.iter_mut() {
-            if !entry.backtrace.is_empty() {
-                let _ = resolver.resolve_frames(&mut entry.backtrace);
+        eprintln!("Resolved {} unique addresses", resolver.cache_size());
+    }
+
+    match export.format {
+        ExportFormat::Json => {}
+        ExportFormat::Chrome => {
+            write_json(
+                &ChromeTrace::from_entries(&entries),
+                export.output,
+                export.pretty,
+            );
+            return;
+        }
+        ExportFormat::Folded => {
+            let stacks = folded_stacks(&entries);
+            if stacks.is_empty() {
+                eprintln!("Warning: no backtraces in the trace, record it with `strace -k`");
             }
+            write_output(&format_folded(&stacks), export.output);
+            return;
+        }
+        ExportFormat::FdLeaks => {
+            let leaks = fd_leaks(&entries);
+            write_output(&format_fd_leaks(&entries, &leaks), export.output);
+            return;
+        }
+        ExportFormat::SummaryTable => {
+            write_output(&tui::summary_table(&entries), export.output);
+            return;
         }
-
-        eprintln!("Resolved {} unique addresses", resolver.cache_size());
     }
 
     // Generate and output
-    output_results(entries, parser.errors, output, pretty);
+    let truncated_at = max_entries.filter(|_| parser.truncated);
+    output_results(
+        entries,
+        truncated_at,
+        parser.take_errors(),
+        parser.program_output,
+        notes,
+        export,
+    );
+}
+
+/// Resolve all backtraces, counting the addresses done on stderr when it is a terminal
+fn resolve_with_progress(resolver: &mut Addr2LineResolver, entries: &mut [parser::SyscallEntry]) {
+    if !std::io::stderr().is_terminal() {
+        resolver.resolve_all(entries);
+        return;
+    }
+
+    let progress = ResolveProgress::default();
+    std::thread::scope(|scope| {
+        let job = scope.spawn(|| resolver.resolve_all_with_progress(entries, &progress));
+        while !job.is_finished() {
+            let (done, total) = progress.get();
+            eprint!("\rResolved {}/{}", done, total);
+            std::thread::sleep(std::time::Duration::from_millis(100));
+        }
+    });
+    let (done, total) = progress.get();
+    eprintln!("\rResolved {}/{}", done, total);
+}
+
+/// Tell about resolver options that can't be followed, before resolving
+fn warn_resolver_options(options: &ResolverOptions) {
+    if let Some(warning) = options.warning() {
+        eprintln!("Warning: {}", warning);
+    }
 }
 
-fn run_strace(command: Vec<String>, trace_file: Option<String>, flags: String) -> String {
+/// Read the prefix pairs of a `--path-map` file, exiting if it can't be
+fn load_path_map(path: &str) -> Vec<(String, String)> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(err) => {
+            eprintln!("Error reading {}: {}", path, err);
+            std::process::exit(1);
+        }
+    };
+    match toml::from_str::<BTreeMap<String, String>>(&content) {
+        Ok(prefixes) => prefixes.into_iter().collect(),
+        Err(err) => {
+            eprintln!("Error parsing {}: {}", path, err);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Parse a trace file, or stdin for `-`
+fn parse_input(
+    parser: &mut StraceParser,
+    input: &str,
+    merge_resumed: bool,
+) -> parser::ParseResult<Vec<parser::SyscallEntry>> {
+    if input == "-" {
+        parser.parse_reader(std::io::stdin().lock(), merge_resumed)
+    } else {
+        parser.parse_file(input, merge_resumed)
+    }
+}
+
+/// Parse what a trace file has so far, keeping it open to read what is added later
+fn parse_followed(
+    parser: &mut StraceParser,
+    input: &str,
+    merge_resumed: bool,
+) -> std::io::Result<(Vec<parser::SyscallEntry>, tui::FileTail)> {
+    let mut tail = tui::FileTail::open(input)?;
+    let mut entries = Vec::new();
+    parser.parse_more(tail.read_lines()?, merge_resumed, &mut entries);
+    Ok((entries, tail))
+}
+
+/// Name to show for an input, as `-` isn't very telling
+fn display_path(input: &str) -> &str {
+    if input == "-" { "(stdin)" } else { input }
+}
+
+/// Parse strace output as it is written to the pipe, then export it once the command ends
+fn stream_export(
+    mut child: Child,
+    reader: PipeReader,
+    export: Export,
+    resolve: bool,
+    merge_resumed: bool,
+    max_entries: Option<usize>,
+    resolver_options: ResolverOptions,
+) {
+    let mut parser = StraceParser::new();
+    parser.max_entries = max_entries;
+    let entries = match parser.parse_reader(BufReader::new(reader), merge_resumed) {
+        Ok(e) => e,
+        Err(err) => {
+            eprintln!("Error parsing strace output: {}", err);
+            std::process::exit(1);
+        }
+    };
+
+    // The pipe is closed by now, so strace can't be stuck writing to it when parsing stopped
+    // at --max-entries
+    wait_strace(&mut child);
+
+    export_entries(
+        parser,
+        entries,
+        BTreeMap::new(),
+        export,
+        resolve,
+        max_entries,
+        resolver_options,
+    );
+}
+
+/// Open the TUI right away, adding the syscalls as strace writes them to the pipe
+fn stream_tui(
+    mut child: Child,
+    reader: PipeReader,
+    merge_resumed: bool,
+    max_entries: Option<usize>,
+    config: tui::Config,
+) {
+    let mut parser = StraceParser::new();
+    parser.max_entries = max_entries;
+    let trace = tui::TraceFile {
+        entries: Vec::new(),
+        summary: SummaryStats::from_entries(&[]),
+        file_path: None,
+        program_output: Vec::new(),
+        errors: Vec::new(),
+        truncated_at: None,
+        follow: Some(tui::Follow {
+            source: tui::LineSource::Pipe(tui::PipeFeed::spawn(reader)),
+            parser,
+            merge_resumed,
+        }),
+    };
+
+    let result = tui::run_tui(vec![trace], config);
+
+    // Closing the TUI stops the trace, if the command is still running
+    child.kill().ok();
+    child.wait().ok();
+
+    if let Err(e) = result {
+        eprintln!("TUI error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+/// Start strace on a command, with its output, and the command's, going to a pipe
+fn spawn_strace(
+    strace_bin: &str,
+    command: &[String],
+    strace_args: &[String],
+) -> (Child, PipeReader) {
+    let (reader, writer) = std::io::pipe().expect("Failed to create pipe");
+    let stdout = writer.try_clone().expect("Failed to create pipe");
+
+    // Without -o, strace writes the trace to its stderr. The command gets no stdin, as the TUI
+    // reads the terminal.
+    let child = Command::new(strace_bin)
+        .args(strace_args)
+        .args(command)
+        .stdin(Stdio::null())
+        .stdout(stdout)
+        .stderr(writer)
+        .spawn();
+
+    match child {
+        Ok(child) => (child, reader),
+        Err(e) => strace_not_run(strace_bin, e),
+    }
+}
+
+/// Report that strace couldn't be started, and exit
+fn strace_not_run(strace_bin: &str, error: std::io::Error) -> ! {
+    eprintln!("Error running strace ({}): {}", strace_bin, error);
+    if strace_bin == "strace" {
+        eprintln!("Make sure strace is installed and in PATH, or pass its path with --strace-bin");
+    }
+    std::process::exit(1);
+}
+
+fn wait_strace(child: &mut Child) {
+    match child.wait() {
+        Ok(status) if !status.success() => {
+            eprintln!("Warning: strace exited with status: {}", status);
+        }
+        Ok(_) => {}
+        Err(e) => eprintln!("Error waiting for strace: {}", e),
+    }
+}
+
+/// Arguments for strace: the flags, then the extra arguments. Where strace writes is up to us,
+/// so an output option is refused.
+fn strace_args(flags: &str, extra: Vec<String>) -> Vec<String> {
+    let args: Vec<String> = flags
+        .split_whitespace()
+        .map(str::to_string)
+        .chain(extra)
+        .collect();
+
+    if let Some(arg) = args
+        .iter()
+        .find(|arg| arg.starts_with("-o") || arg.starts_with("--output"))
+    {
+        eprintln!(
+            "Error: strace argument {} is not allowed, as strace-tui picks where the trace goes \
+             (see --trace-file)",
+            arg
+        );
+        std::process::exit(1);
+    }
+
+    args
+}
+
+fn run_strace(
+    strace_bin: &str,
+    command: Vec<String>,
+    trace_file: Option<String>,
+    strace_args: &[String],
+) -> String {
     if command.is_empty() {
         eprintln!("Error: No command specified");
         std::process::exit(1);
@@ -214,12 +822,9 @@ fn run_strace(command: Vec<String>, trace_file: Option<String>, flags: String) -
     eprintln!("Running strace on: {}", command.join(" "));
     eprintln!("Trace output: {}", trace_path);
 
-    // Parse strace flags from the flags string
-    let strace_args: Vec<&str> = flags.split_whitespace().collect();
-
     // Run strace
-    let status = Command::new("strace")
-        .args(&strace_args) // use parsed flags instead of hardcoded ones
+    let status = Command::new(strace_bin)
+        .args(strace_args)
         .arg("-o")
         .arg(&trace_path)
         .args(&command)
@@ -227,11 +832,7 @@ fn run_strace(command: Vec<String>, trace_file: Option<String>, flags: String) -
 
     let status = match status {
         Ok(s) => s,
-        Err(e) => {
-            eprintln!("Error running strace: {}", e);
-            eprintln!("Make sure strace is installed and in PATH");
-            std::process::exit(1);
-        }
+        Err(e) => strace_not_run(strace_bin, e),
     };
 
     if !status.success() {
@@ -249,33 +850,40 @@ fn run_strace(command: Vec<String>, trace_file: Option<String>, flags: String) -
 
 fn output_results(
     entries: Vec<parser::SyscallEntry>,
-    errors: Vec<(usize, parser::ParseError)>,
-    output_file: Option<String>,
-    pretty: bool,
+    truncated_at: Option<usize>,
+    errors: Vec<ParseErrorInfo>,
+    program_output: Vec<(usize, String)>,
+    notes: BTreeMap<usize, String>,
+    export: Export,
 ) {
     // Generate summary stats
-    let summary = generate_summary(&entries);
+    let summary = SummaryStats::from_entries(&entries);
 
-    // Convert parse errors
-    let error_info: Vec<ParseErrorInfo> = errors
-        .iter()
-        .map(|(line, err)| ParseErrorInfo {
-            line_number: *line,
-            message: err.to_string(),
-        })
+    let program_output = program_output
+        .into_iter()
+        .map(|(line_number, text)| ProgramOutputLine { line_number, text })
         .collect();
 
+    let process_tree = export.process_tree.then(|| tui::process_tree(&entries));
+
     let output = StraceOutput {
         entries,
         summary,
-        errors: error_info,
+        errors,
+        program_output,
+        truncated_at,
+        process_tree,
+        notes,
     };
+    write_json(&output, export.output, export.pretty);
+}
 
-    // Serialize to JSON
+/// Write JSON to a file, or stdout if there is none
+fn write_json<T: Serialize>(value: &T, output_file: Option<String>, pretty: bool) {
     let json = if pretty {
-        serde_json::to_string_pretty(&output)
+        serde_json::to_string_pretty(value)
     } else {
-        serde_json::to_string(&output)
+        serde_json::to_string(value)
     };
 
     let json = match json {
@@ -286,57 +894,18 @@ fn output_results(
         }
     };
 
-    // Write output
+    write_output(&format!("{}\n", json), output_file);
+}
+
+/// Write text to a file, or stdout if there is none
+fn write_output(text: &str, output_file: Option<String>) {
     if let Some(output_path) = output_file {
-        if let Err(err) = std::fs::write(&output_path, json) {
+        if let Err(err) = std::fs::write(&output_path, text) {
             eprintln!("Error writing to {}: {}", output_path, err);
             std::process::exit(1);
         }
         eprintln!("Output written to {}", output_path);
     } else {
-        println!("{}", json);
-    }
-}
-
-fn generate_summary(entries: &[parser::SyscallEntry]) -> SummaryStats {
-    let mut unique_pids = HashSet::new();
-    let mut failed = 0;
-    let mut signals = 0;
-    let mut unfinished = 0;
-    let mut total_duration = 0.0;
-
-    for entry in entries {
-        unique_pids.insert(entry.pid);
-
-        if entry.errno.is_some() {
-            failed += 1;
-        }
-
-        if entry.signal.is_some() {
-            signals += 1;
-        }
-
-        if entry.is_unfinished {
-            unfinished += 1;
-        }
-
-        if let Some(dur) = entry.duration {
-            total_duration += dur;
-        }
-    }
-
-    let unique_pids: Vec<u32> = unique_pids.into_iter().collect();
-
-    SummaryStats {
-        total_syscalls: entries.len(),
-        failed_syscalls: failed,
-        signals,
-        unfinished,
-        unique_pids,
-        total_duration: if total_duration > 0.0 {
-            Some(total_duration)
-        } else {
-            None
-        },
+        print!("{}", text);
     }
 }
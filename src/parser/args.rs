@@ -0,0 +1,177 @@
+use std::ops::Range;
+
+/// The arguments of a syscall, split at the top-level commas of its raw argument string.
+/// Commas inside strings, brackets and `/* ... */` annotations don't split, and an annotation at
+/// the top level, like the `/* 42 vars */` of execve, is an argument of its own.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyscallArgs<'a> {
+    raw: &'a str,
+    ranges: Vec<Range<usize>>, // Byte range of each argument in `raw`, trimmed
+}
+
+impl<'a> SyscallArgs<'a> {
+    pub fn new(raw: &'a str) -> Self {
+        let bytes = raw.as_bytes();
+        let mut args = Self {
+            raw,
+            ranges: Vec::new(),
+        };
+        let mut start = 0; // Where the current argument starts
+        let mut depth = 0; // Nesting of (), {} and []
+        let mut in_string = false;
+        let mut pos = 0;
+
+        while pos < bytes.len() {
+            match bytes[pos] {
+                b'\\' => pos += 1, // Skip the escaped character
+                b'"' => in_string = !in_string,
+                b'/' if !in_string && bytes.get(pos + 1) == Some(&b'*') => {
+                    // Comments nest, and their text doesn't split or nest
+                    let comment_start = pos;
+                    let mut comment_depth = 0;
+                    while pos < bytes.len() {
+                        if bytes[pos..].starts_with(b"/*") {
+                            comment_depth += 1;
+                            pos += 2;
+                        } else if bytes[pos..].starts_with(b"*/") {
+                            comment_depth -= 1;
+                            pos += 2;
+                            if comment_depth == 0 {
+                                break;
+                            }
+                        } else {
+                            pos += 1;
+                        }
+                    }
+
+                    if depth == 0 {
+                        args.push_trimmed(start..comment_start);
+                        args.ranges.push(comment_start..pos);
+                        start = pos;
+                    }
+                    continue;
+                }
+                b'(' | b'{' | b'[' if !in_string => depth += 1,
+                b')' | b'}' | b']' if !in_string => depth -= 1,
+                b',' if !in_string && depth == 0 => {
+                    args.push_trimmed(start..pos);
+                    start = pos + 1;
+                }
+                _ => {}
+            }
+            pos += 1;
+        }
+        args.push_trimmed(start..raw.len());
+
+        // Only separators, which still make an argument
+        if args.ranges.is_empty() && !raw.trim().is_empty() {
+            let range = args.trimmed(0..raw.len());
+            args.ranges.push(range);
+        }
+        args
+    }
+
+    fn trimmed(&self, range: Range<usize>) -> Range<usize> {
+        let text = &self.raw[range.clone()];
+        let start = range.start + text.len() - text.trim_start().len();
+        start..start + text.trim().len()
+    }
+
+    fn push_trimmed(&mut self, range: Range<usize>) {
+        let range = self.trimmed(range);
+        if !range.is_empty() {
+            self.ranges.push(range);
+        }
+    }
+
+    pub fn get(&self, index: usize) -> Option<&'a str> {
+        let raw = self.raw;
+        self.ranges.get(index).map(|range| &raw[range.clone()])
+    }
+
+    /// Byte range of an argument in the raw string
+    pub fn range(&self, index: usize) -> Option<Range<usize>> {
+        self.ranges.get(index).cloned()
+    }
+
+    pub fn len(&self) -> usize {
+        self.ranges.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &'a str> + '_ {
+        let raw = self.raw;
+        self.ranges.iter().map(move |range| &raw[range.clone()])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split(raw: &str) -> Vec<&str> {
+        SyscallArgs::new(raw).iter().collect()
+    }
+
+    #[test]
+    fn test_split_nested_and_quoted() {
+        let args =
+            SyscallArgs::new("3, {st_mode=S_IFREG|0644, st_size=12}, [{fd=4, events=POLLIN}]");
+        assert_eq!(args.len(), 3);
+        assert_eq!(args.get(1), Some("{st_mode=S_IFREG|0644, st_size=12}"));
+        assert_eq!(args.range(2), Some(39..62));
+        assert_eq!(args.get(3), None);
+
+        // Commas and brackets in strings, even after an escaped quote
+        assert_eq!(split(r#"1, "a, (b", 2"#), ["1", r#""a, (b""#, "2"]);
+        assert_eq!(
+            split(r#""say \"hi, there\"", 5"#),
+            [r#""say \"hi, there\"""#, "5"]
+        );
+        assert_eq!(split(r#""\\", 1"#), [r#""\\""#, "1"]);
+
+        assert!(SyscallArgs::new("").is_empty());
+        assert!(SyscallArgs::new("  ").is_empty());
+        assert_eq!(split(" , "), [","]);
+    }
+
+    #[test]
+    fn test_split_comments() {
+        assert_eq!(
+            split("\"/usr/bin/sh\", [\"sh\", \"-c\", \"echo\"], 0x7ffe /* 42 vars */"),
+            [
+                "\"/usr/bin/sh\"",
+                "[\"sh\", \"-c\", \"echo\"]",
+                "0x7ffe",
+                "/* 42 vars */"
+            ]
+        );
+
+        // Commas and brackets inside comments don't split or nest
+        assert_eq!(
+            split("3, 0x55d0 /* 2 entries, (1 hidden) */, 32768"),
+            ["3", "0x55d0", "/* 2 entries, (1 hidden) */", "32768"]
+        );
+        assert_eq!(
+            split("1 /* outer /* inner, x */ still outer */, 2"),
+            ["1", "/* outer /* inner, x */ still outer */", "2"]
+        );
+
+        assert_eq!(
+            split("3, [{d_ino=1}] /* 1 entries */, 10"),
+            ["3", "[{d_ino=1}]", "/* 1 entries */", "10"]
+        );
+
+        // Inside a nested value, the comment stays part of it
+        assert_eq!(
+            split("{a=1 /* x, y */, b=2}, 0"),
+            ["{a=1 /* x, y */, b=2}", "0"]
+        );
+
+        // A comment-looking string is just a string
+        assert_eq!(split("\"/* a, b */\", 1"), ["\"/* a, b */\"", "1"]);
+    }
+}
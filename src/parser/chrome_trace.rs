@@ -0,0 +1,109 @@
+use super::types::{SyscallEntry, Timestamp};
+use serde::Serialize;
+
+/// A trace in the Chrome Trace Event format, which Perfetto and chrome://tracing can load
+#[derive(Debug, Serialize)]
+pub struct ChromeTrace {
+    #[serde(rename = "traceEvents")]
+    pub trace_events: Vec<TraceEvent>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TraceEvent {
+    pub name: String,
+    pub cat: &'static str,
+
+    /// `X` for a syscall with a duration, `i` for anything else
+    pub ph: &'static str,
+
+    /// Start time in microseconds, since the first entry
+    pub ts: f64,
+
+    /// Duration in microseconds (`X` events only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dur: Option<f64>,
+
+    pub pid: u32,
+    pub tid: u32,
+
+    /// Scope of an instant event, drawn on its thread's track
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub s: Option<&'static str>,
+
+    pub args: TraceEventArgs,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TraceEventArgs {
+    pub arguments: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub return_value: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub errno: Option<String>,
+}
+
+const NANOS_PER_DAY: i64 = 86400 * 1_000_000_000;
+
+/// Exact, unlike `Timestamp::as_secs`, so event times don't pick up rounding noise
+fn nanos_of_day(time: &Timestamp) -> i64 {
+    let secs = time.hour as i64 * 3600 + time.minute as i64 * 60 + time.second as i64;
+    secs * 1_000_000_000 + time.nanos.unwrap_or(0) as i64
+}
+
+impl ChromeTrace {
+    /// One event per entry that has a timestamp. An unfinished syscall and its resumed half
+    /// make a single event.
+    pub fn from_entries(entries: &[SyscallEntry]) -> Self {
+        let Some(first) = entries.iter().find_map(|entry| entry.time) else {
+            return Self {
+                trace_events: Vec::new(),
+            };
+        };
+
+        let trace_events = entries
+            .iter()
+            .filter(|entry| !(entry.is_resumed && entry.unfinished_entry_idx.is_some()))
+            .filter_map(|entry| {
+                let time = entry.time?;
+                // Time of day, so wrap around midnight
+                let start = (nanos_of_day(&time) - nanos_of_day(&first)).rem_euclid(NANOS_PER_DAY);
+
+                // The resumed half has the return value and the duration of the whole call
+                let end = entry
+                    .resumed_entry_idx
+                    .and_then(|idx| entries.get(idx))
+                    .unwrap_or(entry);
+                let duration = end.duration;
+
+                let name = match (&entry.signal, &entry.exit_info) {
+                    (Some(signal), _) => signal.signal_name.clone(),
+                    (None, Some(_)) => "exit".to_string(),
+                    (None, None) => entry.syscall_name.clone(),
+                };
+                let cat = if entry.signal.is_some() || entry.exit_info.is_some() {
+                    "process"
+                } else {
+                    "syscall"
+                };
+
+                Some(TraceEvent {
+                    name,
+                    cat,
+                    ph: if duration.is_some() { "X" } else { "i" },
+                    ts: start as f64 / 1e3,
+                    dur: duration.map(|dur| dur * 1e6),
+                    pid: entry.pid,
+                    tid: entry.pid,
+                    s: duration.is_none().then_some("t"),
+                    args: TraceEventArgs {
+                        arguments: entry.arguments.clone(),
+                        return_value: end.return_value.clone(),
+                        errno: end.errno.as_ref().map(|errno| errno.code.clone()),
+                    },
+                })
+            })
+            .collect();
+
+        Self { trace_events }
+    }
+}
@@ -0,0 +1,177 @@
+use object::{Object, ObjectSection};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+
+/// Downloads a URL to a file, returning whether it succeeded
+pub type FetchFn = fn(url: &str, dest: &Path) -> bool;
+
+/// Client for debuginfod servers, to fetch the separate debug file of a binary by its build-id
+pub struct Debuginfod {
+    /// Server base URLs, tried in order
+    urls: Vec<String>,
+    /// Downloaded files are kept under `<cache_dir>/<build-id>/debuginfo`
+    cache_dir: PathBuf,
+    fetch: FetchFn,
+    /// Result per build-id, so a missing file is only asked for once
+    fetched: Mutex<HashMap<String, Option<PathBuf>>>,
+}
+
+impl Debuginfod {
+    /// Create a client for the servers in `DEBUGINFOD_URLS`, or None if it isn't set
+    pub fn from_env() -> Option<Self> {
+        let urls: Vec<String> = std::env::var("DEBUGINFOD_URLS")
+            .ok()?
+            .split_whitespace()
+            .map(str::to_string)
+            .collect();
+        if urls.is_empty() {
+            return None;
+        }
+
+        let cache_dir = dirs::cache_dir()?.join("strace-tui").join("debuginfod");
+        Some(Self::new(urls, cache_dir, fetch_with_curl))
+    }
+
+    pub fn new(urls: Vec<String>, cache_dir: PathBuf, fetch: FetchFn) -> Self {
+        Self {
+            urls,
+            cache_dir,
+            fetch,
+            fetched: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Path of the debug file for a build-id, downloading it if it isn't cached yet
+    pub fn debuginfo(&self, build_id: &str) -> Option<PathBuf> {
+        if let Some(path) = self.fetched.lock().unwrap().get(build_id) {
+            return path.clone();
+        }
+
+        let dest = self.cache_dir.join(build_id).join("debuginfo");
+        let path = if dest.exists() {
+            Some(dest)
+        } else {
+            std::fs::create_dir_all(dest.parent()?).ok()?;
+            self.urls
+                .iter()
+                .map(|url| {
+                    format!(
+                        "{}/buildid/{}/debuginfo",
+                        url.trim_end_matches('/'),
+                        build_id
+                    )
+                })
+                .any(|url| {
+                    log::debug!("Fetching {}", url);
+                    (self.fetch)(&url, &dest)
+                })
+                .then_some(dest)
+        };
+
+        self.fetched
+            .lock()
+            .unwrap()
+            .insert(build_id.to_string(), path.clone());
+        path
+    }
+}
+
+/// Download with curl, through a temporary file so an interrupted download isn't cached, and
+/// removed when it fails
+fn fetch_with_curl(url: &str, dest: &Path) -> bool {
+    let partial = dest.with_extension("part");
+    let ok = Command::new("curl")
+        .args([
+            "--silent",
+            "--fail",
+            "--location",
+            "--max-time",
+            "60",
+            "--output",
+        ])
+        .arg(&partial)
+        .arg(url)
+        .status()
+        .is_ok_and(|status| status.success());
+
+    if ok && std::fs::rename(&partial, dest).is_ok() {
+        return true;
+    }
+    let _ = std::fs::remove_file(&partial);
+    false
+}
+
+/// What a binary says about its debug info
+pub struct DebugInfo {
+    /// Whether the binary carries DWARF line info of its own
+    pub has_line_info: bool,
+    /// GNU build-id, as a hex string
+    pub build_id: Option<String>,
+    /// File name of the separate debug file, from `.gnu_debuglink`
+    pub debuglink: Option<String>,
+}
+
+impl DebugInfo {
+    /// Read the headers and notes of a binary, without the rest of it
+    pub fn read(path: &str) -> Option<Self> {
+        let data = object::ReadCache::new(std::fs::File::open(path).ok()?);
+        let file = object::File::parse(&data).ok()?;
+
+        let has_line_info = file
+            .section_by_name(".debug_line")
+            .is_some_and(|section| section.size() > 0);
+        let build_id = file
+            .build_id()
+            .ok()
+            .flatten()
+            .map(|id| id.iter().map(|byte| format!("{:02x}", byte)).collect());
+        let debuglink = file
+            .gnu_debuglink()
+            .ok()
+            .flatten()
+            .map(|(name, _crc)| String::from_utf8_lossy(name).into_owned());
+
+        Some(Self {
+            has_line_info,
+            build_id,
+            debuglink,
+        })
+    }
+
+    /// Find the separate debug file of `binary` on disk, looking where gdb does: by build-id
+    /// under `debug_dir`, then by debuglink next to the binary, in its `.debug` directory, and
+    /// under `debug_dir` mirroring the binary's directory
+    pub fn find_debug_file(&self, binary: &str, debug_dir: &Path) -> Option<PathBuf> {
+        let mut candidates = Vec::new();
+
+        if let Some(build_id) = &self.build_id
+            && build_id.len() > 2
+        {
+            let (prefix, rest) = build_id.split_at(2);
+            candidates.push(
+                debug_dir
+                    .join(".build-id")
+                    .join(prefix)
+                    .join(format!("{}.debug", rest)),
+            );
+        }
+
+        if let Some(link) = &self.debuglink {
+            let dir = Path::new(binary).parent().unwrap_or(Path::new("/"));
+            candidates.push(dir.join(link));
+            candidates.push(dir.join(".debug").join(link));
+            candidates.push(
+                debug_dir
+                    .join(dir.strip_prefix("/").unwrap_or(dir))
+                    .join(link),
+            );
+        }
+
+        // The debuglink may name the binary itself
+        candidates
+            .into_iter()
+            .find(|path| path.is_file() && path != Path::new(binary))
+    }
+}
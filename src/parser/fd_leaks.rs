@@ -0,0 +1,213 @@
+use super::types::SyscallEntry;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// A file descriptor that was never closed before the end of the trace
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FdLeak {
+    pub pid: u32,
+    pub fd: u32,
+    /// Index of the entry that opened it
+    pub entry_idx: usize,
+}
+
+/// Follow the fds each PID opens and closes, and report those left open at the end, in the
+/// order they were opened. Only the syscalls of the PID itself count: fds inherited through
+/// fork, or closed by another thread sharing the table, are not tracked.
+pub fn fd_leaks(entries: &[SyscallEntry]) -> Vec<FdLeak> {
+    let mut open: HashMap<(u32, u32), usize> = HashMap::new();
+
+    for (idx, entry) in entries.iter().enumerate() {
+        if entry.errno.is_some() || entry.signal.is_some() || entry.exit_info.is_some() {
+            continue;
+        }
+
+        match entry.syscall_name.as_str() {
+            "close" => {
+                if let Some(fd) = leading_fd(&entry.arguments) {
+                    open.remove(&(entry.pid, fd));
+                }
+            }
+            "close_range" => {
+                let mut bounds = entry.arguments.split(',').map(leading_fd);
+                if let (Some(Some(first)), Some(Some(last))) = (bounds.next(), bounds.next()) {
+                    open.retain(|&(pid, fd), _| pid != entry.pid || !(first..=last).contains(&fd));
+                }
+            }
+            // The fds are written to an array, e.g. `pipe([3, 4]) = 0`
+            "pipe" | "pipe2" | "socketpair" if entry.return_value.is_some() => {
+                for fd in fd_array(&entry.arguments).split(',').filter_map(leading_fd) {
+                    open.insert((entry.pid, fd), idx);
+                }
+            }
+            // The new fd is returned. dup2 and dup3 close the target fd first if it was open,
+            // which replacing it here accounts for.
+            name if returns_new_fd(name) => {
+                if let Some(fd) = entry.return_value.as_deref().and_then(leading_fd) {
+                    open.insert((entry.pid, fd), idx);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut leaks: Vec<FdLeak> = open
+        .into_iter()
+        .map(|((pid, fd), entry_idx)| FdLeak { pid, fd, entry_idx })
+        .collect();
+    leaks.sort_by_key(|leak| (leak.entry_idx, leak.fd));
+    leaks
+}
+
+/// Render leaks as `pid fd entry syscall` lines, with entries numbered from 1 like in the TUI
+pub fn format_fd_leaks(entries: &[SyscallEntry], leaks: &[FdLeak]) -> String {
+    leaks
+        .iter()
+        .map(|leak| {
+            let entry = &entries[leak.entry_idx];
+            // What the fd points to, when `-y` decoded it
+            let path = entry
+                .return_fd_path
+                .as_ref()
+                .map(|path| format!(" <{}>", path))
+                .unwrap_or_default();
+            format!(
+                "{} fd {}{} opened by entry #{}: {}({}) = {}\n",
+                leak.pid,
+                leak.fd,
+                path,
+                leak.entry_idx + 1,
+                entry.syscall_name,
+                entry.arguments,
+                entry.return_value.as_deref().unwrap_or("?")
+            )
+        })
+        .collect()
+}
+
+fn returns_new_fd(syscall_name: &str) -> bool {
+    matches!(
+        syscall_name,
+        "open"
+            | "openat"
+            | "openat2"
+            | "creat"
+            | "socket"
+            | "accept"
+            | "accept4"
+            | "dup"
+            | "dup2"
+            | "dup3"
+            | "epoll_create"
+            | "epoll_create1"
+            | "eventfd"
+            | "eventfd2"
+            | "memfd_create"
+            | "timerfd_create"
+            | "signalfd"
+            | "signalfd4"
+            | "inotify_init"
+            | "inotify_init1"
+            | "pidfd_open"
+    )
+}
+
+/// Inside of the first bracketed array of the arguments, past the brackets of the paths `-y`
+/// adds, like in `[3<pipe:[10]>, 4<pipe:[10]>]`
+fn fd_array(arguments: &str) -> &str {
+    let Some(start) = arguments.find('[') else {
+        return "";
+    };
+    let mut depth = 0;
+    for (pos, c) in arguments[start..].char_indices() {
+        match c {
+            '[' => depth += 1,
+            ']' => {
+                depth -= 1;
+                if depth == 0 {
+                    return &arguments[start + 1..start + pos];
+                }
+            }
+            _ => {}
+        }
+    }
+    ""
+}
+
+/// The fd a value starts with, ignoring the path `-y` decorates it with, e.g. `3` in
+/// `3</etc/passwd>`
+fn leading_fd(value: &str) -> Option<u32> {
+    let value = value.trim_start();
+    let digits = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    value[..digits].parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::StraceParser;
+
+    #[test]
+    fn test_fd_leaks() {
+        let trace = "1 10:00:00 openat(AT_FDCWD, \"/etc/passwd\", O_RDONLY) = 3\n\
+                     1 10:00:00 socket(AF_INET, SOCK_STREAM, 0) = 4\n\
+                     1 10:00:00 openat(AT_FDCWD, \"/tmp/x\", O_RDONLY) = 5\n\
+                     1 10:00:00 openat(AT_FDCWD, \"/nope\", O_RDONLY) = -1 ENOENT (No such file or directory)\n\
+                     1 10:00:00 close(4) = 0\n\
+                     2 10:00:00 close(3) = 0\n";
+        let entries = StraceParser::new()
+            .parse_lines(trace.lines().map(str::to_string), false)
+            .unwrap();
+
+        // PID 2 closing its own fd 3 doesn't close PID 1's
+        let leaks = fd_leaks(&entries);
+        assert_eq!(
+            leaks,
+            [
+                FdLeak {
+                    pid: 1,
+                    fd: 3,
+                    entry_idx: 0
+                },
+                FdLeak {
+                    pid: 1,
+                    fd: 5,
+                    entry_idx: 2
+                },
+            ]
+        );
+        assert!(
+            format_fd_leaks(&entries, &leaks)
+                .starts_with("1 fd 3 opened by entry #1: openat(AT_FDCWD, \"/etc/passwd\"")
+        );
+    }
+
+    #[test]
+    fn test_fd_leaks_pipes_and_dups() {
+        let trace = "1 10:00:00 pipe2([3<pipe:[10]>, 4<pipe:[10]>], O_CLOEXEC) = 0\n\
+                     1 10:00:00 dup(3<pipe:[10]>) = 5<pipe:[10]>\n\
+                     1 10:00:00 openat(AT_FDCWD, \"/dev/null\", O_RDWR) = 6\n\
+                     1 10:00:00 dup2(6</dev/null>, 4<pipe:[10]>) = 4</dev/null>\n\
+                     1 10:00:00 close(3<pipe:[10]>) = 0\n\
+                     1 10:00:00 close_range(5, 6, 0) = 0\n";
+        let entries = StraceParser::new()
+            .parse_lines(trace.lines().map(str::to_string), false)
+            .unwrap();
+
+        let opened: Vec<u32> = fd_leaks(&entries[..2]).iter().map(|leak| leak.fd).collect();
+        assert_eq!(opened, [3, 4, 5]);
+
+        // Only the dup2 copy is left
+        let leaks = fd_leaks(&entries);
+        assert_eq!(
+            leaks,
+            [FdLeak {
+                pid: 1,
+                fd: 4,
+                entry_idx: 3
+            }]
+        );
+    }
+}
@@ -0,0 +1,106 @@
+use super::types::{BacktraceFrame, SyscallEntry};
+use std::collections::BTreeMap;
+
+/// Count identical backtraces, as stacks in the folded format of `flamegraph.pl`: frames from the
+/// outermost to the innermost, separated by `;`, ending with the syscall name. Entries without a
+/// backtrace are left out.
+pub fn folded_stacks(entries: &[SyscallEntry]) -> BTreeMap<String, usize> {
+    let mut stacks = BTreeMap::new();
+
+    for entry in entries {
+        if entry.backtrace.is_empty() || entry.signal.is_some() || entry.exit_info.is_some() {
+            continue;
+        }
+
+        // strace prints the innermost frame first
+        let mut names: Vec<String> = entry.backtrace.iter().rev().flat_map(frame_names).collect();
+        names.push(entry.syscall_name.clone());
+
+        *stacks.entry(names.join(";")).or_insert(0) += 1;
+    }
+
+    stacks
+}
+
+/// Render counted stacks as `stack count` lines
+pub fn format_folded(stacks: &BTreeMap<String, usize>) -> String {
+    stacks
+        .iter()
+        .map(|(stack, count)| format!("{} {}\n", stack, count))
+        .collect()
+}
+
+/// Function names of a frame, outermost first: its inlined functions when resolved, else the
+/// symbol strace printed, else where it is in its binary
+fn frame_names(frame: &BacktraceFrame) -> Vec<String> {
+    if let Some(resolved) = &frame.resolved {
+        let names: Vec<String> = resolved
+            .iter()
+            .rev()
+            .filter(|resolved| resolved.function != "<unknown>")
+            .map(|resolved| resolved.function.clone())
+            .collect();
+        if !names.is_empty() {
+            return names;
+        }
+    }
+
+    if let Some(function) = frame.function.as_deref().filter(|f| !f.is_empty()) {
+        return vec![function.to_string()];
+    }
+
+    let binary = frame.binary.rsplit('/').next().unwrap_or(&frame.binary);
+    let location = frame.offset.as_deref().unwrap_or(&frame.address);
+    vec![format!("{}+{}", binary, location)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{ResolvedFrame, StraceParser};
+
+    #[test]
+    fn test_folded_stacks() {
+        let trace = "1 10:00:00 read(3, \"\", 1) = 0\n \
+                     > /usr/lib/libc.so.6(read+0x14) [0x10e53e]\n \
+                     > /usr/bin/app(+0x1234) [0x1234]\n \
+                     > /usr/lib/libc.so.6(__libc_start_main+0x80) [0x27680]\n\
+                     1 10:00:00 read(3, \"\", 1) = 0\n \
+                     > /usr/lib/libc.so.6(read+0x14) [0x10e53e]\n \
+                     > /usr/bin/app(+0x1234) [0x1234]\n \
+                     > /usr/lib/libc.so.6(__libc_start_main+0x80) [0x27680]\n\
+                     1 10:00:00 write(1, \"a\", 1) = 1\n \
+                     > /usr/lib/libc.so.6(write+0x14) [0x10e600]\n \
+                     > /usr/bin/app(+0x1300) [0x1300]\n \
+                     > /usr/lib/libc.so.6(__libc_start_main+0x80) [0x27680]\n\
+                     1 10:00:00 close(3) = 0\n";
+        let mut entries = StraceParser::new()
+            .parse_lines(trace.lines().map(str::to_string), false)
+            .unwrap();
+
+        let stacks = folded_stacks(&entries);
+        assert_eq!(
+            format_folded(&stacks),
+            "__libc_start_main;app+0x1234;read;read 2\n\
+             __libc_start_main;app+0x1300;write;write 1\n"
+        );
+
+        // Resolved frames take over, callers of inlined functions first
+        let resolved = |function: &str, is_inlined| ResolvedFrame {
+            function: function.to_string(),
+            file: "main.c".to_string(),
+            line: 1,
+            column: None,
+            is_inlined,
+        };
+        for entry in &mut entries[..2] {
+            entry.backtrace[1].resolved =
+                Some(vec![resolved("read_all", true), resolved("main", false)]);
+        }
+        let stacks = folded_stacks(&entries);
+        assert_eq!(
+            stacks.get("__libc_start_main;main;read_all;read;read"),
+            Some(&2)
+        );
+    }
+}
@@ -3,15 +3,30 @@ use nom::{
     branch::alt,
     bytes::complete::{tag, take_while1},
     character::complete::{char, digit1, space0, space1},
-    combinator::{opt, recognize},
+    combinator::{map_res, opt, recognize},
     sequence::{delimited, preceded, terminated},
 };
 
-use super::{Errno, ExitInfo, ParseError, ParseResult, SignalInfo, SyscallEntry};
+use std::collections::HashMap;
+
+use super::{Errno, ExitInfo, ParseError, ParseResult, SignalInfo, SyscallEntry, Timestamp};
 
 /// Parse a complete strace line
 pub fn parse_strace_line(line: &str) -> ParseResult<SyscallEntry> {
-    // Check for special lines first
+    let mut entry = parse_entry(line)?;
+    entry.time = parse_structured_timestamp(&entry.timestamp);
+    (entry.arguments, entry.output_args) = split_output_args(&entry.arguments);
+    entry.fd_paths = parse_argument_fd_paths(&entry.arguments);
+    Ok(entry)
+}
+
+/// Parse a line of ltrace output, with the library function called as `syscall_name`.
+///
+/// The prefixes, unfinished/resumed calls, signals and exits look like strace's. The call may
+/// name the library it was made from (`libfoo.so->malloc(16)`), and system calls traced with
+/// `-S` are prefixed with `SYS_`, both dropped here. Return values are kept as printed, as they
+/// can be strings or `<void>`, and there is no errno.
+pub fn parse_ltrace_line(line: &str) -> ParseResult<SyscallEntry> {
     if line.contains("+++") {
         return parse_exit_line(line);
     }
@@ -19,12 +34,98 @@ pub fn parse_strace_line(line: &str) -> ParseResult<SyscallEntry> {
         return parse_signal_line(line);
     }
 
-    // Parse regular syscall line - try different formats in order
-    let (rest, (pid, timestamp)) = parse_pid_and_timestamp(line)
+    let (rest, (pid, timestamp)) = parse_prefix(line)?;
+    let rest = rest.trim_start();
+    let rest = match rest.split_once("->") {
+        Some((library, call)) if !library.contains(['(', ' ']) => call,
+        _ => rest,
+    };
+
+    let mut entry = if let Some(resumed) = rest.strip_prefix("<...") {
+        // <... puts resumed> ) = 6
+        let (name, rest) = resumed.split_once("resumed>").ok_or_else(|| {
+            ParseError::InvalidSyscall(format!("Unterminated resumed call: {}", line))
+        })?;
+        let mut entry = SyscallEntry::new(pid, timestamp, ltrace_function_name(name.trim()));
+        entry.is_resumed = true;
+        // The arguments end at the last parenthesis before the (padded) `=`
+        let end = rest
+            .rmatch_indices(')')
+            .map(|(end, _)| end)
+            .find(|&end| rest[end + 1..].trim_start().starts_with('='));
+        match end {
+            Some(end) => {
+                entry.arguments = rest[..end + 1].trim().to_string();
+                parse_ltrace_return(&mut entry, &rest[end + 1..]);
+            }
+            None => entry.arguments = rest.trim().to_string(),
+        }
+        entry
+    } else {
+        let (rest, name) = parse_syscall_name(rest).map_err(|e| {
+            ParseError::InvalidSyscall(format!("Failed to parse function name: {}", e))
+        })?;
+        let mut entry = SyscallEntry::new(pid, timestamp, ltrace_function_name(&name));
+
+        let (rest, args) = parse_arguments(rest)
+            .map_err(|e| ParseError::InvalidSyscall(format!("Failed to parse arguments: {}", e)))?;
+        entry.arguments = args;
+
+        if rest.contains("<unfinished") {
+            entry.is_unfinished = true;
+        } else {
+            parse_ltrace_return(&mut entry, rest);
+        }
+        entry
+    };
+
+    entry.time = parse_structured_timestamp(&entry.timestamp);
+    Ok(entry)
+}
+
+/// Name of an ltrace function, without the `SYS_` of system calls
+fn ltrace_function_name(name: &str) -> String {
+    name.strip_prefix("SYS_").unwrap_or(name).to_string()
+}
+
+/// Parse the `= value <duration>` after an ltrace call, padded to align the `=` column
+fn parse_ltrace_return(entry: &mut SyscallEntry, rest: &str) {
+    let Some(value) = rest.trim_start().strip_prefix('=') else {
+        return;
+    };
+    let mut value = value.trim();
+
+    if let Some(start) = value.rfind(" <")
+        && let Ok((_, duration)) = parse_duration(&value[start..])
+    {
+        entry.duration = Some(duration);
+        value = value[..start].trim_end();
+    }
+    if !value.is_empty() {
+        entry.return_value = Some(value.to_string());
+    }
+}
+
+/// Split the optional PID and timestamp off the start of a line
+fn parse_prefix(line: &str) -> ParseResult<(&str, (u32, String))> {
+    parse_pid_and_timestamp(line)
         .or_else(|_| parse_timestamp_only(line))
         .or_else(|_| parse_pid_only(line))
         .or_else(|_| parse_no_prefix(line))
-        .map_err(|e| ParseError::InvalidFormat(format!("Failed to parse PID/timestamp: {}", e)))?;
+        .map_err(|e| ParseError::InvalidFormat(format!("Failed to parse PID/timestamp: {}", e)))
+}
+
+fn parse_entry(line: &str) -> ParseResult<SyscallEntry> {
+    // Check for special lines first
+    if line.contains("+++") {
+        return parse_exit_line(line);
+    }
+    if line.contains("---") {
+        return parse_signal_line(line);
+    }
+
+    // Parse regular syscall line - try different formats in order
+    let (rest, (pid, timestamp)) = parse_prefix(line)?;
 
     // Check for <... resumed> pattern
     if rest.trim_start().starts_with("<...") {
@@ -48,17 +149,68 @@ pub fn parse_strace_line(line: &str) -> ParseResult<SyscallEntry> {
         return Ok(entry);
     }
 
+    parse_return_part(&mut entry, rest);
+
+    Ok(entry)
+}
+
+/// Whether a line has the shape of strace output: an optional PID/timestamp prefix followed by
+/// `name(`, `<... resumed>`, a signal or an exit. Lines that don't are usually output of the
+/// traced program mixed into the trace, as with `2>&1`.
+pub fn looks_like_strace_line(line: &str) -> bool {
+    let prefixes = [
+        parse_pid_and_timestamp,
+        parse_timestamp_only,
+        parse_pid_only,
+        parse_no_prefix,
+    ];
+
+    prefixes.iter().any(|prefix| {
+        let Ok((rest, _)) = prefix(line) else {
+            return false;
+        };
+        let rest = rest.trim_start();
+        rest.starts_with("<...")
+            || rest.starts_with("--- ")
+            || rest.starts_with("+++ ")
+            || parse_syscall_name(rest).is_ok_and(|(rest, _)| rest.starts_with('('))
+    })
+}
+
+/// Parse a lone `= retval` line, continuing the call on the previous line, into `entry`.
+/// Returns whether the line was a continuation.
+pub fn parse_return_continuation(line: &str, entry: &mut SyscallEntry) -> bool {
+    if !line.trim_start().starts_with('=') {
+        return false;
+    }
+
+    parse_return_part(entry, line);
+    entry.return_value.is_some()
+}
+
+/// Parse the ` = retval ...` part of a syscall line: return value, fd path, annotation, errno
+/// and duration
+fn parse_return_part(entry: &mut SyscallEntry, rest: &str) {
     // Parse return value and errno
     let (rest, return_val) = parse_return_value(rest).unwrap_or((rest, None));
     entry.return_value = return_val;
 
+    // Parse fd path decoration (e.g. "= 3</etc/passwd>")
+    let (rest, fd_path) = parse_fd_path(rest).unwrap_or((rest, None));
+    entry.return_fd_path = fd_path;
+
+    // Parse return annotation (e.g. "= 0x2 (flags O_RDWR)")
+    let (rest, annotation) = parse_return_annotation(rest).unwrap_or((rest, None));
+    entry.return_annotation = annotation;
+
+    // Failed calls have an errno, and interrupted ones (`= ?`) a restart code like ERESTARTSYS
+    let mut rest = rest;
     if let Some(ref ret) = entry.return_value
         && (ret.starts_with("-1") || ret.starts_with("?"))
+        && let Ok((errno_rest, errno)) = parse_errno(rest)
     {
-        // Try to parse errno
-        if let Ok((_, errno)) = parse_errno(rest) {
-            entry.errno = Some(errno);
-        }
+        entry.errno = Some(errno);
+        rest = errno_rest;
     }
 
     // Parse duration
@@ -66,12 +218,33 @@ pub fn parse_strace_line(line: &str) -> ParseResult<SyscallEntry> {
         entry.duration = Some(duration);
     }
 
-    Ok(entry)
+    entry.child_pid = fork_child_pid(entry);
+}
+
+/// Process a fork-like syscall created, from its return value, or else from the `parent_tid`
+/// clone3 writes back, as in `<... clone3 resumed> => {parent_tid=[7197]}, 88) = 7197`
+fn fork_child_pid(entry: &SyscallEntry) -> Option<u32> {
+    if !matches!(
+        entry.syscall_name.as_str(),
+        "fork" | "vfork" | "clone" | "clone3"
+    ) {
+        return None;
+    }
+
+    let returned = entry
+        .return_value
+        .as_deref()
+        .and_then(|ret| ret.trim().parse::<u32>().ok());
+    let parent_tid = || {
+        let (_, rest) = entry.arguments.split_once("parent_tid=[")?;
+        rest.split(']').next()?.parse::<u32>().ok()
+    };
+    returned.or_else(parent_tid).filter(|&pid| pid > 0)
 }
 
 /// Parse PID and timestamp from the start of the line
 fn parse_pid_and_timestamp(input: &str) -> IResult<&str, (u32, String)> {
-    let (rest, pid) = terminated(digit1, space1).parse(input)?;
+    let (rest, pid) = terminated(parse_pid, space1).parse(input)?;
     let (rest, timestamp) = terminated(parse_timestamp, space1).parse(rest)?;
 
     Ok((rest, (pid.parse().unwrap_or(0), timestamp.to_string())))
@@ -86,30 +259,94 @@ fn parse_timestamp_only(input: &str) -> IResult<&str, (u32, String)> {
 
 /// Parse PID only (no timestamp) - for strace with -f but without -t
 fn parse_pid_only(input: &str) -> IResult<&str, (u32, String)> {
-    let (rest, pid) = terminated(digit1, space1).parse(input)?;
+    let (rest, pid) = terminated(parse_pid, space1).parse(input)?;
     // Use empty string for timestamp when no timestamp is present
     Ok((rest, (pid.parse().unwrap_or(0), String::new())))
 }
 
+/// Parse a PID, either bare or in the `[pid  1234]` form
+fn parse_pid(input: &str) -> IResult<&str, &str> {
+    alt((delimited((tag("[pid"), space1), digit1, char(']')), digit1)).parse(input)
+}
+
 /// Parse no prefix (no PID, no timestamp) - for strace without -f and without -t
 fn parse_no_prefix(input: &str) -> IResult<&str, (u32, String)> {
     // Just return the input as-is with PID 0 and empty timestamp
     Ok((input, (0, String::new())))
 }
 
-/// Parse timestamp in HH:MM:SS format
+/// Parse timestamp in HH:MM:SS format, or epoch seconds (-ttt)
 fn parse_timestamp(input: &str) -> IResult<&str, &str> {
-    recognize((
-        digit1,
-        char(':'),
-        digit1,
-        char(':'),
-        digit1,
-        opt((char('.'), digit1)),
+    alt((
+        recognize((
+            digit1,
+            char(':'),
+            digit1,
+            char(':'),
+            digit1,
+            opt((char('.'), digit1)),
+        )),
+        parse_epoch_timestamp,
     ))
     .parse(input)
 }
 
+/// Parse an epoch timestamp with fractional seconds, like `1700000000.123456` from `strace -ttt`.
+/// The fraction is required, so a bare PID is never taken for a timestamp.
+fn parse_epoch_timestamp(input: &str) -> IResult<&str, &str> {
+    recognize((digit1, char('.'), digit1)).parse(input)
+}
+
+/// Split a `HH:MM:SS[.fraction]` timestamp into its fields. The fraction, of any number of
+/// digits, is normalized to nanoseconds. Epoch timestamps are normalized to their UTC time of
+/// day.
+fn parse_structured_timestamp(timestamp: &str) -> Option<Timestamp> {
+    if let Some((secs, fraction)) = timestamp.split_once('.')
+        && !secs.contains(':')
+    {
+        let secs: u64 = secs.parse().ok()?;
+        let day_secs = secs % 86400;
+        return Some(Timestamp {
+            hour: (day_secs / 3600) as u8,
+            minute: (day_secs / 60 % 60) as u8,
+            second: (day_secs % 60) as u8,
+            nanos: Some(fraction_nanos(fraction)),
+        });
+    }
+
+    let number = || map_res(digit1, str::parse::<u8>);
+
+    let parsed: IResult<&str, _> = (
+        number(),
+        char(':'),
+        number(),
+        char(':'),
+        number(),
+        opt(preceded(char('.'), digit1)),
+    )
+        .parse(timestamp);
+    let (rest, (hour, _, minute, _, second, fraction)) = parsed.ok()?;
+    if !rest.is_empty() {
+        return None;
+    }
+
+    Some(Timestamp {
+        hour,
+        minute,
+        second,
+        nanos: fraction.map(fraction_nanos),
+    })
+}
+
+/// Nanoseconds of the fractional digits of a second, padding or cutting them to 9 digits
+fn fraction_nanos(digits: &str) -> u32 {
+    digits
+        .bytes()
+        .chain(std::iter::repeat(b'0'))
+        .take(9)
+        .fold(0, |acc, b| acc * 10 + (b - b'0') as u32)
+}
+
 /// Parse syscall name
 fn parse_syscall_name(input: &str) -> IResult<&str, String> {
     let (rest, name) = take_while1(|c: char| c.is_alphanumeric() || c == '_' || c == '$')(input)?;
@@ -121,47 +358,28 @@ fn parse_arguments(input: &str) -> IResult<&str, String> {
     let (rest, _) = space0(input)?;
     let (rest, _) = char('(')(rest)?;
 
-    // Find matching closing paren, handling nested structures
-    // But stop early if we see <unfinished
+    // Find the matching closing paren, handling nested structures, but stop early at an
+    // <unfinished marker. This is one pass over the bytes, as a big -s makes megabyte lines.
     let mut depth = 1;
-    let mut end_pos = 0;
-    let chars: Vec<char> = rest.chars().collect();
-    let rest_str = rest;
-
-    // Check if this contains <unfinished
-    if rest_str.contains("<unfinished") {
-        // Find where <unfinished starts and treat that as end
-        if let Some(unfinished_pos) = rest_str.find("<unfinished") {
-            let args: String = rest_str[..unfinished_pos]
-                .trim_end_matches([',', ' '])
-                .to_string();
-            return Ok((rest_str.get(unfinished_pos..).unwrap_or(""), args));
-        }
-    }
-
-    for (i, &c) in chars.iter().enumerate() {
-        match c {
-            '(' => depth += 1,
-            ')' => {
+    for (i, b) in rest.bytes().enumerate() {
+        match b {
+            b'(' => depth += 1,
+            b')' => {
                 depth -= 1;
                 if depth == 0 {
-                    end_pos = i;
-                    break;
+                    return Ok((&rest[i + 1..], rest[..i].to_string()));
                 }
             }
+            b'<' if rest[i..].starts_with("<unfinished") => {
+                let args = rest[..i].trim_end_matches([',', ' ']).to_string();
+                return Ok((&rest[i..], args));
+            }
             _ => {}
         }
     }
 
-    if depth != 0 {
-        // Unfinished or malformed
-        let args: String = chars.iter().collect();
-        return Ok(("", args));
-    }
-
-    let args: String = chars.iter().take(end_pos).collect();
-
-    Ok((rest.get(end_pos + 1..).unwrap_or(""), args))
+    // Unfinished or malformed
+    Ok(("", rest.to_string()))
 }
 
 /// Parse return value
@@ -189,17 +407,204 @@ fn parse_return_value(input: &str) -> IResult<&str, Option<String>> {
     Ok((rest, Some(value.to_string())))
 }
 
+/// Parse the `<path>` strace `-y` appends right after a returned fd. The path may itself
+/// contain `>`, as in `<TCP:[1.2.3.4:80->5.6.7.8:9]>`, so only a `>` outside brackets ends it.
+fn parse_fd_path(input: &str) -> IResult<&str, Option<String>> {
+    let Some(inner) = input.strip_prefix('<') else {
+        return Err(nom::Err::Error(nom::error::Error::new(
+            input,
+            nom::error::ErrorKind::Char,
+        )));
+    };
+
+    let mut depth = 0;
+    for (pos, ch) in inner.char_indices() {
+        match ch {
+            '[' => depth += 1,
+            ']' => depth -= 1,
+            '>' if depth == 0 => {
+                return Ok((&inner[pos + 1..], Some(inner[..pos].to_string())));
+            }
+            _ => {}
+        }
+    }
+
+    Err(nom::Err::Error(nom::error::Error::new(
+        input,
+        nom::error::ErrorKind::TakeUntil,
+    )))
+}
+
+/// Find the fds decorated with their path by `strace -y` in an argument string, like
+/// `3</dev/null>` or the ones in `[4<pipe:[200]>, 5<pipe:[200]>]`. Quoted strings are skipped, so
+/// data that merely looks like a decorated fd isn't taken for one.
+fn parse_argument_fd_paths(args: &str) -> Vec<(usize, String)> {
+    let bytes = args.as_bytes();
+    let mut paths = Vec::new();
+    let mut in_string = false;
+    let mut pos = 0;
+
+    while pos < bytes.len() {
+        let b = bytes[pos];
+        if in_string {
+            match b {
+                b'\\' => pos += 1, // Skip the escaped character
+                b'"' => in_string = false,
+                _ => {}
+            }
+            pos += 1;
+            continue;
+        }
+
+        // An fd is a whole number, not the tail of an identifier or hex literal
+        let at_word_start =
+            pos == 0 || !(bytes[pos - 1].is_ascii_alphanumeric() || bytes[pos - 1] == b'_');
+        if b == b'"' {
+            in_string = true;
+            pos += 1;
+        } else if b.is_ascii_digit() && at_word_start {
+            let end = pos
+                + bytes[pos..]
+                    .iter()
+                    .take_while(|b| b.is_ascii_digit())
+                    .count();
+            if let Ok((rest, Some(path))) = parse_fd_path(&args[end..])
+                && let Ok(fd) = args[pos..end].parse()
+            {
+                paths.push((fd, path));
+                pos = args.len() - rest.len();
+            } else {
+                pos = end;
+            }
+        } else {
+            pos += 1;
+        }
+    }
+
+    paths
+}
+
+/// Cut the `=> value` blocks strace prints for what the kernel wrote back out of an argument
+/// string, returning the arguments left and the values (joined with `, ` if several). Only those
+/// between arguments count: the ones inside a struct or array, like `[128 => 16]`, stay where
+/// they are, as they make no sense out of it.
+fn split_output_args(args: &str) -> (String, Option<String>) {
+    let bytes = args.as_bytes();
+    let mut kept = String::new();
+    let mut outputs = Vec::new();
+    let mut depth = 0i32;
+    let mut in_string = false;
+    let mut copied = 0; // Where the part of `args` not yet copied to `kept` starts
+    let mut pos = 0;
+
+    while pos < bytes.len() {
+        let b = bytes[pos];
+        if in_string {
+            match b {
+                b'\\' => pos += 1, // Skip the escaped character
+                b'"' => in_string = false,
+                _ => {}
+            }
+            pos += 1;
+            continue;
+        }
+
+        match b {
+            b'"' => in_string = true,
+            b'{' | b'[' | b'(' => depth += 1,
+            b'}' | b']' | b')' => depth -= 1,
+            b'=' if depth == 0 && bytes.get(pos + 1) == Some(&b'>') => {
+                // The value runs to the next argument, or the end of the call when resumed
+                let start = pos + 2;
+                let mut end = start;
+                let mut value_depth = 0i32;
+                let mut value_in_string = false;
+                while end < bytes.len() {
+                    let c = bytes[end];
+                    if value_in_string {
+                        match c {
+                            b'\\' => end += 1,
+                            b'"' => value_in_string = false,
+                            _ => {}
+                        }
+                    } else {
+                        match c {
+                            b'"' => value_in_string = true,
+                            b'{' | b'[' | b'(' => value_depth += 1,
+                            b'}' | b']' | b')' if value_depth == 0 => break,
+                            b'}' | b']' | b')' => value_depth -= 1,
+                            b',' if value_depth == 0 => break,
+                            _ => {}
+                        }
+                    }
+                    end += 1;
+                }
+                let end = end.min(bytes.len());
+
+                outputs.push(args[start..end].trim().to_string());
+                kept.push_str(args[copied..pos].trim_end());
+                copied = end;
+                pos = end;
+                continue;
+            }
+            _ => {}
+        }
+        pos += 1;
+    }
+
+    if outputs.is_empty() {
+        return (args.to_string(), None);
+    }
+    kept.push_str(&args[copied..]);
+    (kept, Some(outputs.join(", ")))
+}
+
+/// Parse the annotation following a successful return value, parenthesized as in
+/// `= 3 (NETLINK_ROUTE)` or a comment as in `= 0x7f12 /* 2 entries */`. Failed calls have the
+/// errno code first, so their "(message)" isn't taken as an annotation.
+fn parse_return_annotation(input: &str) -> IResult<&str, Option<String>> {
+    let (rest, _) = space1(input)?;
+    if let Some(comment) = rest.strip_prefix("/*")
+        && let Some(end) = comment.find("*/")
+    {
+        return Ok((&comment[end + 2..], Some(comment[..end].trim().to_string())));
+    }
+    let (rest, _) = char('(')(rest)?;
+
+    let mut depth = 1;
+    for (pos, ch) in rest.char_indices() {
+        match ch {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Ok((&rest[pos + 1..], Some(rest[..pos].to_string())));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Err(nom::Err::Error(nom::error::Error::new(
+        input,
+        nom::error::ErrorKind::TakeUntil,
+    )))
+}
+
 /// Parse errno information
 fn parse_errno(input: &str) -> IResult<&str, Errno> {
     let (rest, _) = space0(input)?;
-    let (rest, code) = take_while1(|c: char| c.is_uppercase() || c.is_numeric())(rest)?;
+    // Codes may have underscores, as ERESTART_RESTARTBLOCK
+    let (mut rest, code) =
+        take_while1(|c: char| c.is_uppercase() || c.is_numeric() || c == '_')(rest)?;
 
     // Try to parse message in parentheses
     let mut message = String::new();
-    if let Some(start) = rest.find('(')
-        && let Some(end) = rest[start..].find(')')
+    if let Some(inner) = rest.trim_start().strip_prefix('(')
+        && let Some(end) = inner.find(')')
     {
-        message = rest[start + 1..start + end].to_string();
+        message = inner[..end].to_string();
+        rest = &inner[end + 1..];
     }
 
     Ok((
@@ -264,23 +669,8 @@ fn parse_resumed_line(pid: u32, timestamp: String, input: &str) -> ParseResult<S
                 .map(|eq_pos| &after_resumed[eq_pos..])
         };
 
-        if let Some(ret_part) = ret_part
-            && let Ok((rest, ret_val)) = parse_return_value(ret_part)
-        {
-            entry.return_value = ret_val;
-
-            // Parse errno if present
-            if let Some(ref ret) = entry.return_value
-                && ret.starts_with("-1")
-                && let Ok((_, errno)) = parse_errno(rest)
-            {
-                entry.errno = Some(errno);
-            }
-
-            // Parse duration
-            if let Ok((_, duration)) = parse_duration(rest) {
-                entry.duration = Some(duration);
-            }
+        if let Some(ret_part) = ret_part {
+            parse_return_part(&mut entry, ret_part);
         }
     }
 
@@ -306,12 +696,20 @@ fn parse_signal_line(line: &str) -> ParseResult<SyscallEntry> {
         if let Some(end) = after_start.find("---") {
             let signal_text = after_start[..end].trim();
 
+            // "stopped by SIGSTOP" is a stop of the process, not a delivered signal
+            let (stopped, name_text) = match signal_text.strip_prefix("stopped by") {
+                Some(rest) => (true, rest),
+                None => (false, signal_text),
+            };
+
             // Extract signal name
-            let signal_name = signal_text.split_whitespace().next().unwrap_or("UNKNOWN");
+            let signal_name = name_text.split_whitespace().next().unwrap_or("UNKNOWN");
 
             entry.signal = Some(SignalInfo {
                 signal_name: signal_name.to_string(),
                 details: signal_text.to_string(),
+                fields: parse_siginfo_fields(signal_text),
+                stopped,
             });
         }
     }
@@ -319,6 +717,42 @@ fn parse_signal_line(line: &str) -> ParseResult<SyscallEntry> {
     Ok(entry)
 }
 
+/// Parse the `key=value` pairs of the siginfo block in `SIGCHLD {si_signo=SIGCHLD, ...}`
+fn parse_siginfo_fields(signal_text: &str) -> HashMap<String, String> {
+    let Some(block) = signal_text
+        .find('{')
+        .zip(signal_text.rfind('}'))
+        .and_then(|(start, end)| signal_text.get(start + 1..end))
+    else {
+        return HashMap::new();
+    };
+
+    // Split on top-level commas only, as values can be structs or arrays themselves
+    let mut fields = HashMap::new();
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut field_start = 0;
+    let mut escaped = false;
+    for (pos, ch) in block.char_indices().chain([(block.len(), ',')]) {
+        match ch {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            '{' | '[' if !in_string => depth += 1,
+            '}' | ']' if !in_string => depth -= 1,
+            ',' if !in_string && depth == 0 => {
+                if let Some((key, value)) = block[field_start..pos].split_once('=') {
+                    fields.insert(key.trim().to_string(), value.trim().to_string());
+                }
+                field_start = pos + 1;
+            }
+            _ => {}
+        }
+    }
+
+    fields
+}
+
 /// Parse exit line (+++ exited with N +++)
 fn parse_exit_line(line: &str) -> ParseResult<SyscallEntry> {
     let (pid, timestamp) = parse_pid_and_timestamp(line)
@@ -334,22 +768,29 @@ fn parse_exit_line(line: &str) -> ParseResult<SyscallEntry> {
     if let Some(start) = line.find("+++") {
         let after_start = &line[start + 3..];
 
-        let exit_code = if after_start.contains("exited with") {
-            // Normal exit
+        let exit_code = if after_start.contains("exited") {
+            // Normal exit, "exited with 1", or "exited (status 1)" from ltrace
             after_start
-                .split("with")
-                .nth(1)
-                .and_then(|s| s.split_whitespace().next())
-                .and_then(|s| s.parse().ok())
+                .split_once("with")
+                .or_else(|| after_start.split_once("status"))
+                .and_then(|(_, s)| s.split_whitespace().next())
+                .and_then(|s| s.trim_end_matches(')').parse().ok())
                 .unwrap_or(0)
         } else {
             // Killed by signal
             0
         };
 
+        // "killed by SIGKILL" or "killed by SIGSEGV (core dumped)"
+        let signal = after_start
+            .split_once("killed by")
+            .and_then(|(_, rest)| rest.split_whitespace().next())
+            .map(str::to_string);
+
         entry.exit_info = Some(ExitInfo {
             code: exit_code,
             killed: after_start.contains("killed"),
+            signal,
         });
     }
 
@@ -372,6 +813,68 @@ mod tests {
         assert_eq!(entry.return_value, Some("0x5602312ea000".to_string()));
     }
 
+    #[test]
+    fn test_parse_structured_timestamp() {
+        let time = |line: &str| parse_strace_line(line).unwrap().time;
+
+        let seconds = time("12311 12:59:24 brk(NULL) = 0").unwrap();
+        assert_eq!((seconds.hour, seconds.minute, seconds.second), (12, 59, 24));
+        assert_eq!(seconds.nanos, None);
+
+        let micros = time("12311 12:59:24.123456 brk(NULL) = 0").unwrap();
+        assert_eq!(micros.second, 24);
+        assert_eq!(micros.nanos, Some(123_456_000));
+
+        let millis = time("12:59:24.123 brk(NULL) = 0").unwrap();
+        assert_eq!(millis.nanos, Some(123_000_000));
+        assert!((millis.as_secs() - 46764.123).abs() < 1e-9);
+
+        // Without a timestamp there is nothing to structure
+        assert_eq!(time("12311 brk(NULL) = 0"), None);
+        assert_eq!(time("brk(NULL) = 0"), None);
+    }
+
+    #[test]
+    fn test_parse_epoch_timestamp() {
+        let entry = parse_strace_line("1700000000.123456 brk(NULL) = 0").unwrap();
+        assert_eq!(entry.pid, 0);
+        assert_eq!(entry.timestamp, "1700000000.123456");
+        assert_eq!(entry.syscall_name, "brk");
+
+        // 1700000000 is 22:13:20 UTC
+        let time = entry.time.unwrap();
+        assert_eq!((time.hour, time.minute, time.second), (22, 13, 20));
+        assert_eq!(time.nanos, Some(123_456_000));
+
+        let entry = parse_strace_line("12311 1700000000.123456 brk(NULL) = 0").unwrap();
+        assert_eq!(entry.pid, 12311);
+        assert_eq!(entry.timestamp, "1700000000.123456");
+        assert_eq!(entry.return_value, Some("0".to_string()));
+
+        // A bare PID isn't an epoch timestamp
+        let entry = parse_strace_line("12311 brk(NULL) = 0").unwrap();
+        assert_eq!(entry.pid, 12311);
+        assert_eq!(entry.timestamp, "");
+    }
+
+    #[test]
+    fn test_parse_bracket_pid() {
+        let entry = parse_strace_line("[pid  1234] 12:59:24 read(0, \"abc\\n\", 4) = 4").unwrap();
+        assert_eq!(entry.pid, 1234);
+        assert_eq!(entry.timestamp, "12:59:24");
+        assert_eq!(entry.syscall_name, "read");
+        assert_eq!(entry.return_value, Some("4".to_string()));
+
+        let entry = parse_strace_line("[pid 1234] read(0, \"abc\\n\", 4) = 4").unwrap();
+        assert_eq!(entry.pid, 1234);
+        assert_eq!(entry.timestamp, "");
+        assert_eq!(entry.syscall_name, "read");
+
+        let entry = parse_strace_line("[pid  1234] 12:59:24 +++ exited with 0 +++").unwrap();
+        assert_eq!(entry.pid, 1234);
+        assert!(entry.exit_info.is_some());
+    }
+
     #[test]
     fn test_parse_with_errno() {
         let line = "12311 12:59:24 access(\"/etc/ld.so.preload\", R_OK) = -1 ENOENT (No such file or directory)";
@@ -404,6 +907,17 @@ mod tests {
         assert!(entry.is_resumed);
         assert_eq!(entry.syscall_name, "execve");
         assert_eq!(entry.return_value, Some("0".to_string()));
+
+        // The return part parses as for a complete line
+        let line = "1 10:00:00 <... read resumed>\"\", 10) = -1 EAGAIN (Resource temporarily unavailable) <0.000012>";
+        let entry = parse_strace_line(line).unwrap();
+        assert_eq!(entry.errno.unwrap().code, "EAGAIN");
+        assert_eq!(entry.duration, Some(0.000012));
+
+        let line = "1 10:00:00 <... wait4 resumed>0x7ffc, 0, NULL) = ? ERESTARTSYS (To be restarted if SA_RESTART is set) <1.5>";
+        let entry = parse_strace_line(line).unwrap();
+        assert_eq!(entry.errno.unwrap().code, "ERESTARTSYS");
+        assert_eq!(entry.duration, Some(1.5));
     }
 
     #[test]
@@ -415,6 +929,50 @@ mod tests {
         assert!(entry.signal.is_some());
         let signal = entry.signal.unwrap();
         assert_eq!(signal.signal_name, "SIGCHLD");
+        assert!(!signal.stopped);
+
+        assert_eq!(signal.fields.len(), 7);
+        assert_eq!(signal.fields["si_signo"], "SIGCHLD");
+        assert_eq!(signal.fields["si_code"], "CLD_EXITED");
+        assert_eq!(signal.fields["si_pid"], "12312");
+        assert_eq!(signal.fields["si_uid"], "1000");
+        assert_eq!(signal.fields["si_status"], "0");
+        assert!(signal.details.starts_with("SIGCHLD {si_signo=SIGCHLD"));
+    }
+
+    #[test]
+    fn test_parse_siginfo_fields() {
+        let line =
+            "1 10:00:00 --- SIGSEGV {si_signo=SIGSEGV, si_code=SEGV_MAPERR, si_addr=NULL} ---";
+        let signal = parse_strace_line(line).unwrap().signal.unwrap();
+        assert_eq!(signal.fields["si_addr"], "NULL");
+        assert_eq!(signal.sorted_fields()[0], ("si_addr", "NULL"),);
+
+        // Nested values stay whole
+        let line = "1 10:00:00 --- SIGRTMIN {si_signo=SIGRTMIN, si_code=SI_QUEUE, si_value={int=1, ptr=0x1}} ---";
+        let signal = parse_strace_line(line).unwrap().signal.unwrap();
+        assert_eq!(signal.fields["si_value"], "{int=1, ptr=0x1}");
+
+        // Stops have no siginfo block
+        let line = "1 10:00:00 --- stopped by SIGSTOP ---";
+        assert!(
+            parse_strace_line(line)
+                .unwrap()
+                .signal
+                .unwrap()
+                .fields
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_parse_stopped_by_signal() {
+        let entry = parse_strace_line("12345 10:20:30 --- stopped by SIGSTOP ---").unwrap();
+
+        assert_eq!(entry.pid, 12345);
+        let signal = entry.signal.unwrap();
+        assert_eq!(signal.signal_name, "SIGSTOP");
+        assert!(signal.stopped);
     }
 
     #[test]
@@ -427,6 +985,21 @@ mod tests {
         let exit = entry.exit_info.unwrap();
         assert_eq!(exit.code, 0);
         assert!(!exit.killed);
+        assert_eq!(exit.signal, None);
+    }
+
+    #[test]
+    fn test_parse_killed_by_signal() {
+        let entry = parse_strace_line("12312 12:59:24 +++ killed by SIGSEGV +++").unwrap();
+        let exit = entry.exit_info.unwrap();
+        assert!(exit.killed);
+        assert_eq!(exit.signal, Some("SIGSEGV".to_string()));
+
+        let entry =
+            parse_strace_line("12312 12:59:24 +++ killed by SIGKILL (core dumped) +++").unwrap();
+        let exit = entry.exit_info.unwrap();
+        assert!(exit.killed);
+        assert_eq!(exit.signal, Some("SIGKILL".to_string()));
     }
 
     #[test]
@@ -539,6 +1112,227 @@ mod tests {
         assert_eq!(entry.syscall_name, "clone3");
         assert_eq!(entry.return_value, Some("7197".to_string()));
         assert!(entry.is_resumed);
+        assert_eq!(entry.child_pid, Some(7197));
+
+        // The child also shows in parent_tid when the return value is missing
+        let line = "7193 11:52:10 <... clone3 resumed> => {parent_tid=[7198]}, 88) = ?";
+        let entry = parse_strace_line(line).unwrap();
+        assert_eq!(entry.child_pid, Some(7198));
+
+        // The child side of a fork returns 0
+        let line = "7198 11:52:10 <... clone3 resumed>, 88) = 0";
+        assert_eq!(parse_strace_line(line).unwrap().child_pid, None);
+    }
+
+    #[test]
+    fn test_parse_output_args() {
+        let line = "7193 11:52:10 <... clone3 resumed> => {parent_tid=[7197]}, 88) = 7197";
+        let entry = parse_strace_line(line).unwrap();
+        assert_eq!(entry.arguments, ", 88)");
+        assert_eq!(entry.output_args.as_deref(), Some("{parent_tid=[7197]}"));
+        assert_eq!(entry.child_pid, Some(7197));
+
+        let line = "7193 11:52:10 clone3({flags=CLONE_VM|CLONE_VFORK, exit_signal=SIGCHLD, \
+                    stack=0x7fc52c21f000, stack_size=0x9000} => {parent_tid=[7197]}, 88) = 7197";
+        let entry = parse_strace_line(line).unwrap();
+        assert_eq!(
+            entry.arguments,
+            "{flags=CLONE_VM|CLONE_VFORK, exit_signal=SIGCHLD, stack=0x7fc52c21f000, \
+             stack_size=0x9000}, 88"
+        );
+        assert_eq!(entry.output_args.as_deref(), Some("{parent_tid=[7197]}"));
+        assert_eq!(entry.return_value.as_deref(), Some("7197"));
+
+        // The last argument, and arrows inside a value or a string, which stay
+        let line = "1 10:00:00 getsockname(3, {sa_family=AF_INET}, [128 => 16]) = 0";
+        let entry = parse_strace_line(line).unwrap();
+        assert_eq!(entry.arguments, "3, {sa_family=AF_INET}, [128 => 16]");
+        assert_eq!(entry.output_args, None);
+
+        let line = "1 10:00:00 ioctl(3, SIOCGIFNAME, {ifr_index=2} => {ifr_name=\"eth0\"}) = 0";
+        let entry = parse_strace_line(line).unwrap();
+        assert_eq!(entry.arguments, "3, SIOCGIFNAME, {ifr_index=2}");
+        assert_eq!(entry.output_args.as_deref(), Some("{ifr_name=\"eth0\"}"));
+
+        let line = "1 10:00:00 write(1, \"a => b\", 6) = 6";
+        assert_eq!(parse_strace_line(line).unwrap().output_args, None);
+    }
+
+    #[test]
+    fn test_parse_long_line() {
+        let data = "\\x41".repeat(1 << 20);
+        let line = format!("1 10:00:00 write(3, \"{}\"..., 1048576) = 1048576", data);
+        let start = std::time::Instant::now();
+        let entry = parse_strace_line(&line).unwrap();
+        assert_eq!(entry.arguments.len(), data.len() + 17);
+        assert!(entry.arguments.ends_with("\"..., 1048576"));
+        assert_eq!(entry.return_value.as_deref(), Some("1048576"));
+
+        let line = format!("1 10:00:00 write(3, \"{}\"... <unfinished ...>", data);
+        let entry = parse_strace_line(&line).unwrap();
+        assert!(entry.is_unfinished);
+        assert!(entry.arguments.ends_with("\"..."));
+
+        // Non-ASCII text before the end, where char and byte indices differ
+        let entry = parse_strace_line("1 10:00:00 write(1, \"é\", 2) = 2").unwrap();
+        assert_eq!(entry.arguments, "1, \"é\", 2");
+        assert_eq!(entry.return_value.as_deref(), Some("2"));
+
+        assert!(start.elapsed() < std::time::Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_parse_fd_path_decoration() {
+        let line = "1234 10:00:00 socket(AF_INET, SOCK_STREAM, IPPROTO_TCP) = 3<socket:[12345]> <0.000020>";
+        let entry = parse_strace_line(line).unwrap();
+
+        assert_eq!(entry.return_value, Some("3".to_string()));
+        assert_eq!(entry.return_fd_path, Some("socket:[12345]".to_string()));
+        assert_eq!(entry.duration, Some(0.00002));
+
+        let line = "1234 10:00:00 <... accept4 resumed>NULL, NULL, SOCK_CLOEXEC) = 5<TCP:[127.0.0.1:80->127.0.0.1:4242]>";
+        let entry = parse_strace_line(line).unwrap();
+        assert_eq!(
+            entry.return_fd_path,
+            Some("TCP:[127.0.0.1:80->127.0.0.1:4242]".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_argument_fd_paths() {
+        let line = "1234 10:00:00 openat(AT_FDCWD, \"/tmp/x\", O_RDONLY) = 3</tmp/x>";
+        let entry = parse_strace_line(line).unwrap();
+        assert_eq!(entry.return_fd_path, Some("/tmp/x".to_string()));
+        assert!(entry.fd_paths.is_empty());
+
+        let line = "1234 10:00:00 dup2(3</dev/null>, 1</dev/pts/0>) = 1</dev/null>";
+        let entry = parse_strace_line(line).unwrap();
+        assert_eq!(
+            entry.fd_paths,
+            vec![(3, "/dev/null".to_string()), (1, "/dev/pts/0".to_string())]
+        );
+        assert_eq!(entry.arguments, "3</dev/null>, 1</dev/pts/0>");
+
+        let line = "1234 10:00:00 pipe2([4<pipe:[200]>, 5<pipe:[200]>], O_CLOEXEC) = 0";
+        let entry = parse_strace_line(line).unwrap();
+        assert_eq!(
+            entry.fd_paths,
+            vec![(4, "pipe:[200]".to_string()), (5, "pipe:[200]".to_string())]
+        );
+
+        // Decorations inside string data aren't fds
+        let line = "1234 10:00:00 write(1</dev/pts/0>, \"7</etc/passwd>\\\"\", 16) = 16";
+        let entry = parse_strace_line(line).unwrap();
+        assert_eq!(entry.fd_paths, vec![(1, "/dev/pts/0".to_string())]);
+    }
+
+    #[test]
+    fn test_parse_restart_codes() {
+        let line = "1234 10:00:00 wait4(-1, 0x7ffd, 0, NULL) = ? ERESTARTSYS (To be restarted if SA_RESTART is set) <0.500000>";
+        let entry = parse_strace_line(line).unwrap();
+
+        assert_eq!(entry.return_value, Some("?".to_string()));
+        let errno = entry.errno.unwrap();
+        assert_eq!(errno.code, "ERESTARTSYS");
+        assert_eq!(errno.message, "To be restarted if SA_RESTART is set");
+        assert_eq!(entry.duration, Some(0.5));
+
+        let line = "1234 10:00:00 nanosleep({tv_sec=1, tv_nsec=0}, 0x7ffd) = ? ERESTART_RESTARTBLOCK (Interrupted by signal)";
+        let entry = parse_strace_line(line).unwrap();
+        assert_eq!(entry.errno.unwrap().code, "ERESTART_RESTARTBLOCK");
+
+        // A process exiting mid-call has no code at all
+        let entry = parse_strace_line("1234 10:00:00 exit_group(0) = ?").unwrap();
+        assert!(entry.errno.is_none());
+    }
+
+    #[test]
+    fn test_parse_return_flags_annotation() {
+        let line = "1234 10:00:00 fcntl(3, F_GETFL) = 0x8002 (flags O_RDWR|O_LARGEFILE) <0.000004>";
+        let entry = parse_strace_line(line).unwrap();
+
+        assert_eq!(entry.return_value, Some("0x8002".to_string()));
+        assert_eq!(
+            entry.return_annotation.as_deref(),
+            Some("flags O_RDWR|O_LARGEFILE")
+        );
+        assert_eq!(entry.duration, Some(0.000004));
+
+        // The errno message isn't an annotation
+        let line = "1234 10:00:00 fcntl(9, F_GETFD) = -1 EBADF (Bad file descriptor)";
+        let entry = parse_strace_line(line).unwrap();
+        assert_eq!(entry.return_annotation, None);
+        assert_eq!(entry.errno.unwrap().code, "EBADF");
+    }
+
+    #[test]
+    fn test_parse_return_annotations() {
+        let line = "1234 10:00:00 socket(AF_NETLINK, SOCK_RAW|SOCK_CLOEXEC, NETLINK_ROUTE) = 3 (NETLINK_ROUTE) <0.000010>";
+        let entry = parse_strace_line(line).unwrap();
+        assert_eq!(entry.return_value.as_deref(), Some("3"));
+        assert_eq!(entry.return_annotation.as_deref(), Some("NETLINK_ROUTE"));
+        assert_eq!(entry.duration, Some(0.000010));
+
+        let line = "1234 10:00:00 poll([{fd=3, events=POLLIN}], 1, 100) = 0 (Timeout) <0.100123>";
+        let entry = parse_strace_line(line).unwrap();
+        assert_eq!(entry.return_value.as_deref(), Some("0"));
+        assert_eq!(entry.return_annotation.as_deref(), Some("Timeout"));
+        assert!(entry.errno.is_none());
+        assert_eq!(entry.duration, Some(0.100123));
+
+        // A comment doesn't stop the duration from being found either
+        let line = "1234 10:00:00 get_mempolicy(NULL, NULL, 0, NULL, 0) = 0x7f12 /* 2 entries */ <0.000002>";
+        let entry = parse_strace_line(line).unwrap();
+        assert_eq!(entry.return_value.as_deref(), Some("0x7f12"));
+        assert_eq!(entry.return_annotation.as_deref(), Some("2 entries"));
+        assert_eq!(entry.duration, Some(0.000002));
+    }
+
+    #[test]
+    fn test_parse_ltrace_lines() {
+        let line =
+            "12345 10:20:30.123456 libc.so.6->malloc(16)           = 0x55d0a1b2c2a0 <0.000120>";
+        let entry = parse_ltrace_line(line).unwrap();
+        assert_eq!(entry.pid, 12345);
+        assert_eq!(entry.syscall_name, "malloc");
+        assert_eq!(entry.arguments, "16");
+        assert_eq!(entry.return_value.as_deref(), Some("0x55d0a1b2c2a0"));
+        assert_eq!(entry.duration, Some(0.000120));
+        assert!(entry.time.is_some());
+
+        let entry =
+            parse_ltrace_line("getenv(\"HOME\")                   = \"/home/user\"").unwrap();
+        assert_eq!(entry.syscall_name, "getenv");
+        assert_eq!(entry.return_value.as_deref(), Some("\"/home/user\""));
+
+        let entry = parse_ltrace_line("12345 free(0x55d0a1b2c2a0) = <void>").unwrap();
+        assert_eq!(entry.return_value.as_deref(), Some("<void>"));
+
+        // A system call, with -S
+        let entry = parse_ltrace_line("12345 SYS_write(1, \"x\", 1) = 1").unwrap();
+        assert_eq!(entry.syscall_name, "write");
+        assert_eq!(entry.return_value.as_deref(), Some("1"));
+    }
+
+    #[test]
+    fn test_parse_ltrace_unfinished_and_exit() {
+        let entry =
+            parse_ltrace_line("12345 10:00:00 waitpid(-1, 0x7ffd5a0c, 0 <unfinished ...>").unwrap();
+        assert!(entry.is_unfinished);
+        assert_eq!(entry.syscall_name, "waitpid");
+        assert_eq!(entry.arguments, "-1, 0x7ffd5a0c, 0");
+
+        let entry =
+            parse_ltrace_line("12345 10:00:01 <... waitpid resumed> )          = 12346").unwrap();
+        assert!(entry.is_resumed);
+        assert_eq!(entry.syscall_name, "waitpid");
+        assert_eq!(entry.arguments, ")");
+        assert_eq!(entry.return_value.as_deref(), Some("12346"));
+
+        let entry = parse_ltrace_line("12345 10:00:02 +++ exited (status 3) +++").unwrap();
+        let exit = entry.exit_info.unwrap();
+        assert_eq!(exit.code, 3);
+        assert!(!exit.killed);
     }
 
     #[test]
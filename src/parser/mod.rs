@@ -1,14 +1,30 @@
+mod args;
 mod backtrace_parser;
+mod chrome_trace;
+mod debuginfo;
+mod fd_leaks;
+mod folded;
 mod line_parser;
 mod resolver;
+mod streaming;
 mod types;
 
+pub use args::SyscallArgs;
 pub use backtrace_parser::parse_backtrace_line;
-pub use line_parser::parse_strace_line;
-pub use resolver::Addr2LineResolver;
+pub use chrome_trace::{ChromeTrace, TraceEvent, TraceEventArgs};
+pub use debuginfo::{Debuginfod, FetchFn};
+pub use fd_leaks::{FdLeak, fd_leaks, format_fd_leaks};
+pub use folded::{folded_stacks, format_folded};
+pub use line_parser::{
+    looks_like_strace_line, parse_ltrace_line, parse_return_continuation, parse_strace_line,
+};
+pub use resolver::{
+    Addr2LineResolver, ResolveJob, ResolveProgress, ResolvedAddresses, ResolverOptions,
+};
+pub use streaming::StreamingEntries;
 pub use types::*;
 
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 
@@ -28,18 +44,71 @@ pub enum ParseError {
     Io(String),
 }
 
+impl ParseError {
+    /// Short machine-readable category of the error, for the JSON output
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ParseError::InvalidFormat(_) => "invalid_format",
+            ParseError::InvalidSyscall(_) => "invalid_syscall",
+            ParseError::InvalidBacktrace(_) => "invalid_backtrace",
+            ParseError::Io(_) => "io",
+        }
+    }
+}
+
 /// Result type for parser operations
 pub type ParseResult<T> = Result<T, ParseError>;
 
+/// Tool that wrote the trace being parsed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum InputFormat {
+    #[default]
+    Strace,
+    /// Library calls from ltrace, each function being an entry's `syscall_name`
+    Ltrace,
+}
+
 /// Parser state for handling multi-line entries and unfinished syscalls
 #[derive(Debug)]
 pub struct StraceParser {
-    /// Pending unfinished syscalls, keyed by PID
+    /// Pending unfinished syscalls, keyed by PID, as the line number of their entry, which no
+    /// other entry has. [`take_unfinished`](Self::take_unfinished) finds the entry by it, and
+    /// checks it is still that PID's call before anything is patched.
     unfinished: HashMap<u32, usize>,
     /// Accumulated errors during parsing
     pub errors: Vec<(usize, ParseError)>,
+    /// Raw text of the lines that had errors, keyed by line number
+    pub error_lines: HashMap<usize, String>,
+    /// Lines that aren't strace output at all, like the traced program's own output, with
+    /// their line numbers
+    pub program_output: Vec<(usize, String)>,
+    /// Stop parsing after this many entries
+    pub max_entries: Option<usize>,
+    /// Format of the lines, strace's unless set
+    pub input_format: InputFormat,
+    /// Drop text a logger put before each line, like `[2024-01-01T00:00:00] `. See
+    /// [`without_log_prefix`](Self::without_log_prefix).
+    pub strip_log_prefix: bool,
+    /// Whether parsing stopped at `max_entries` before the end of the input
+    pub truncated: bool,
+    /// Number of unfinished syscalls still waiting for their resumption at the end of the input
+    pub dangling_unfinished: usize,
     /// Current line number
     line_number: usize,
+    /// Index of the entry that gets the next backtrace or continuation lines: the latest
+    /// syscall of the PID of the latest line. Signals never get any, so the lines after them go
+    /// to the syscall before of the same PID, and an exited PID gets none.
+    open_entry: Option<usize>,
+    /// Index of the latest syscall entry of each PID still running
+    open_entries: HashMap<u32, usize>,
+    /// Entries fed by [`push_line`](Self::push_line) that may still change, from the oldest one
+    /// that may
+    line_entries: Vec<SyscallEntry>,
+    /// Entries fed by [`push_line`](Self::push_line) that can't change anymore, waiting to be
+    /// taken
+    complete_entries: VecDeque<SyscallEntry>,
+    /// Number of entries taken out of `line_entries`, which is the index of its first one
+    lines_emitted: usize,
 }
 
 impl StraceParser {
@@ -47,7 +116,19 @@ impl StraceParser {
         Self {
             unfinished: HashMap::new(),
             errors: Vec::new(),
+            error_lines: HashMap::new(),
+            program_output: Vec::new(),
+            max_entries: None,
+            input_format: InputFormat::Strace,
+            strip_log_prefix: false,
+            truncated: false,
+            dangling_unfinished: 0,
             line_number: 0,
+            open_entry: None,
+            open_entries: HashMap::new(),
+            line_entries: Vec::new(),
+            complete_entries: VecDeque::new(),
+            lines_emitted: 0,
         }
     }
 
@@ -60,7 +141,15 @@ impl StraceParser {
         let file = File::open(path)
             .map_err(|e| ParseError::Io(format!("Failed to open {}: {}", path, e)))?;
 
-        let reader = BufReader::new(file);
+        self.parse_reader(BufReader::new(file), merge_resumed)
+    }
+
+    /// Parse strace output from a reader, like stdin
+    pub fn parse_reader<R: BufRead>(
+        &mut self,
+        reader: R,
+        merge_resumed: bool,
+    ) -> ParseResult<Vec<SyscallEntry>> {
         self.parse_lines(reader.lines().map(|l| l.unwrap_or_default()), merge_resumed)
     }
 
@@ -74,92 +163,427 @@ impl StraceParser {
         I: Iterator<Item = String>,
     {
         let mut entries = Vec::new();
-        let mut current_entry: Option<SyscallEntry> = None;
+        self.open_entry = None;
+        self.open_entries.clear();
+        self.parse_more(lines, merge_resumed, &mut entries);
+        self.finish(&mut entries);
+
+        Ok(entries)
+    }
+
+    /// Once the input ended, mark the calls still waiting for their resumption as never
+    /// resumed, unless the rest was cut off
+    pub fn finish(&mut self, entries: &mut [SyscallEntry]) {
+        if !self.truncated {
+            for (_, line_number) in std::mem::take(&mut self.unfinished) {
+                if let Some(idx) = entry_at_line(entries, line_number) {
+                    entries[idx].never_resumed = true;
+                    self.dangling_unfinished += 1;
+                }
+            }
+        }
+    }
+
+    /// Parse more lines of the same trace, adding their entries to `entries`, which must hold
+    /// everything parsed so far. An entry can still get backtrace or continuation lines from the
+    /// next call, and unfinished calls stay pending until their resumption shows up, so a trace
+    /// that is still being written can be read as it grows.
+    pub fn parse_more<I>(&mut self, lines: I, merge_resumed: bool, entries: &mut Vec<SyscallEntry>)
+    where
+        I: IntoIterator<Item = String>,
+    {
+        if self.truncated {
+            return;
+        }
 
         for line in lines {
-            self.line_number += 1;
+            self.parse_next_line(line, merge_resumed, entries, 0);
+            // Stop reading once the cap is reached, without holding the rest of the input
+            if self.truncated {
+                break;
+            }
+        }
+    }
+
+    /// Parse a line into `entries`, whose first one has the index `first_idx` in the whole
+    /// trace, returning the error of a line that looks like strace output but failed to parse
+    fn parse_next_line(
+        &mut self,
+        mut line: String,
+        merge_resumed: bool,
+        entries: &mut Vec<SyscallEntry>,
+        first_idx: usize,
+    ) -> Option<ParseError> {
+        self.line_number += 1;
+        let mut parsed = None;
+        if self.strip_log_prefix {
+            let (rest, entry) = self.without_log_prefix(&line);
+            (line, parsed) = (rest.to_string(), entry);
+        }
+
+        // Skip empty lines
+        if line.trim().is_empty() {
+            return None;
+        }
 
-            // Skip empty lines
-            if line.trim().is_empty() {
-                continue;
+        let current_entry = self
+            .open_entry
+            .and_then(|idx| idx.checked_sub(first_idx))
+            .and_then(|idx| entries.get_mut(idx));
+        if self.extend_entry(&line, current_entry) {
+            return None;
+        }
+
+        if self
+            .max_entries
+            .is_some_and(|max| first_idx + entries.len() >= max)
+        {
+            self.truncated = true;
+            return None;
+        }
+
+        // Parse the syscall line
+        let parsed = match parsed {
+            Some(entry) => Ok(entry),
+            None => self.parse_line(&line),
+        };
+        let mut entry = match parsed {
+            Ok(entry) => entry,
+            Err(e) => {
+                // The frames after a bad line are its own, so they are dropped until the next
+                // entry. Program output doesn't interrupt the entry it came between.
+                let error = self.record_unparsed(e, line);
+                if error.is_some() {
+                    self.open_entry = None;
+                }
+                return error;
             }
+        };
+
+        // Handle special cases
+        if entry.is_unfinished {
+            // Store unfinished syscall
+            self.unfinished.insert(entry.pid, entry.line_number);
+        } else if entry.is_resumed {
+            match self.take_unfinished(entry.pid, entries) {
+                Some(unfinished_idx) if merge_resumed => {
+                    let unfinished = entries.get_mut(unfinished_idx).unwrap();
+                    unfinished.return_value = entry.return_value;
+                    unfinished.output_args = entry.output_args;
+                    unfinished.return_fd_path = entry.return_fd_path;
+                    unfinished.return_annotation = entry.return_annotation;
+                    unfinished.errno = entry.errno;
+                    unfinished.duration = entry.duration;
+                    unfinished.child_pid = entry.child_pid;
+                    unfinished.is_resumed = false;
+                    unfinished.is_unfinished = false;
+                    unfinished.raw_line.push('\n');
+                    unfinished.raw_line.push_str(&line);
+                    // strace prints the stack when the call returns, so the one after the
+                    // resumed line replaces any the start had
+                    unfinished.backtrace.clear();
+                    self.open_entries
+                        .insert(entry.pid, first_idx + unfinished_idx);
+                    self.open_entry = Some(first_idx + unfinished_idx);
+                    return None;
+                }
+                Some(unfinished_idx) => {
+                    entry.unfinished_entry_idx = Some(first_idx + unfinished_idx);
 
-            // Check if this is a backtrace line (starts with " > ")
-            if line.trim_start().starts_with(">") {
-                if let Some(ref mut entry) = current_entry {
-                    match parse_backtrace_line(&line) {
-                        Ok(frame) => entry.backtrace.push(frame),
-                        Err(e) => self.errors.push((self.line_number, e)),
-                    }
+                    // Update unfinished entry with link to resumed, and the child it forked
+                    entries[unfinished_idx].resumed_entry_idx = Some(first_idx + entries.len());
+                    entries[unfinished_idx].child_pid = entry.child_pid;
                 }
-                continue;
+                // Its start came before the trace did, which isn't an error
+                None => entry.orphan_resumed = true,
             }
+        }
 
-            // If we have a pending entry, finalize it
-            if let Some(entry) = current_entry.take() {
-                entries.push(entry);
+        if entry.exit_info.is_some() {
+            // Nothing of an exited PID comes after, so its pending call never resumes
+            self.open_entries.remove(&entry.pid);
+            if let Some(idx) = self.take_unfinished(entry.pid, entries) {
+                entries[idx].never_resumed = true;
+                self.dangling_unfinished += 1;
             }
+        } else if entry.signal.is_none() {
+            self.open_entries
+                .insert(entry.pid, first_idx + entries.len());
+        }
+        self.open_entry = self.open_entries.get(&entry.pid).copied();
+        entries.push(entry);
+        None
+    }
 
-            // Parse the syscall line
-            match parse_strace_line(&line) {
-                Ok(entry) => {
-                    // Handle special cases
-                    if entry.is_unfinished {
-                        // Store unfinished syscall
-                        self.unfinished.insert(entry.pid, entries.len());
-                        current_entry = Some(entry);
-                    } else if entry.is_resumed {
-                        if merge_resumed {
-                            if let Some(unfinished_idx) = self.unfinished.remove(&entry.pid) {
-                                let unfinished = entries.get_mut(unfinished_idx).unwrap();
-                                unfinished.return_value = entry.return_value;
-                                unfinished.errno = entry.errno;
-                                unfinished.duration = entry.duration;
-                                unfinished.is_resumed = false;
-                                unfinished.is_unfinished = false;
-                            } else {
-                                // Resumed without unfinished - just store as-is with error
-                                self.errors.push((
-                                    self.line_number,
-                                    ParseError::InvalidFormat(
-                                        "resumed without unfinished".to_string(),
-                                    ),
-                                ));
-                                current_entry = Some(entry);
-                            }
-                        } else if let Some(unfinished_idx) = self.unfinished.remove(&entry.pid) {
-                            let mut resumed_entry = entry;
-                            resumed_entry.unfinished_entry_idx = Some(unfinished_idx);
-
-                            // Update unfinished entry with link to resumed
-                            entries[unfinished_idx].resumed_entry_idx = Some(entries.len());
-
-                            current_entry = Some(resumed_entry);
-                        } else {
-                            // Resumed without unfinished - just store as-is
-                            current_entry = Some(entry);
-                        }
-                    } else {
-                        current_entry = Some(entry);
-                    }
-                }
-                Err(e) => {
-                    self.errors.push((self.line_number, e));
+    /// Parse lines as an iterator of entries, emitting each one as soon as it is complete
+    /// (once the next entry starts). See [`StreamingEntries`] for how it differs from
+    /// [`parse_lines`](Self::parse_lines).
+    pub fn parse_streaming<I>(&mut self, lines: I) -> StreamingEntries<'_, I::IntoIter>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        StreamingEntries::new(self, lines.into_iter())
+    }
+
+    /// Parse a line that starts a new entry, in the parser's input format
+    fn parse_line(&self, line: &str) -> ParseResult<SyscallEntry> {
+        let mut entry = match self.input_format {
+            InputFormat::Strace => parse_strace_line(line),
+            InputFormat::Ltrace => parse_ltrace_line(line),
+        }?;
+        entry.raw_line = line.to_string();
+        entry.line_number = self.line_number;
+        Ok(entry)
+    }
+
+    /// The line without the words a logger put before it, up to where strace output starts: a
+    /// line that parses, a backtrace frame (`>`) or a lone `= retval`. Only bracketed tags and
+    /// timestamps are dropped, see [`after_log_word`], and a line where no strace output starts
+    /// after them is kept whole. The entry of a line that parsed is returned along, so it isn't
+    /// parsed again.
+    fn without_log_prefix<'a>(&self, line: &'a str) -> (&'a str, Option<SyscallEntry>) {
+        let mut rest = line;
+        loop {
+            if rest.starts_with('>') || rest.starts_with('=') {
+                return (rest, None);
+            }
+            if let Ok(entry) = self.parse_line(rest) {
+                return (rest, Some(entry));
+            }
+            match after_log_word(rest) {
+                Some(next) => rest = next,
+                None => return (line, None),
+            }
+        }
+    }
+
+    /// Remove the pending unfinished call of `pid`, returning the index of its entry in
+    /// `entries` only if that is still the unresumed call of the same PID. A missing or stale
+    /// entry is dropped, so the resumed line is kept on its own instead of patching an unrelated
+    /// entry.
+    fn take_unfinished(&mut self, pid: u32, entries: &[SyscallEntry]) -> Option<usize> {
+        let line_number = self.unfinished.remove(&pid)?;
+        let idx = entry_at_line(entries, line_number)?;
+        let entry = &entries[idx];
+        (entry.pid == pid && entry.is_unfinished && entry.resumed_entry_idx.is_none())
+            .then_some(idx)
+    }
+
+    /// Feed the next line of a trace, returning an entry it completed, if any. Lines go through
+    /// the same steps as with [`parse_lines`](Self::parse_lines) without merging resumed calls,
+    /// and entries come out the same, in order, once nothing can change them anymore: a PID's
+    /// latest syscall may still get backtrace or continuation lines, and an unfinished one its
+    /// resumption, so they and the entries after them are held until the PID moves on or exits.
+    /// As a line can complete several entries, [`pop_entry`](Self::pop_entry) takes the others,
+    /// and [`flush`](Self::flush) the ones still held at the end of the input.
+    pub fn push_line(&mut self, line: &str) -> Option<SyscallEntry> {
+        self.feed_line(line);
+        self.pop_entry()
+    }
+
+    /// Take an entry already completed by [`push_line`](Self::push_line)
+    pub fn pop_entry(&mut self) -> Option<SyscallEntry> {
+        self.complete_entries.pop_front()
+    }
+
+    /// Take the entries still waiting for more lines, one at a time, once the input ended
+    pub fn flush(&mut self) -> Option<SyscallEntry> {
+        let mut entries = std::mem::take(&mut self.line_entries);
+        self.finish(&mut entries);
+        self.lines_emitted += entries.len();
+        self.complete_entries.extend(entries);
+        self.pop_entry()
+    }
+
+    /// Step of [`push_line`](Self::push_line), returning the error of a line that looks like
+    /// strace output but failed to parse
+    fn feed_line(&mut self, line: &str) -> Option<ParseError> {
+        if self.truncated {
+            return None;
+        }
+
+        let mut entries = std::mem::take(&mut self.line_entries);
+        let error = self.parse_next_line(line.to_string(), false, &mut entries, self.lines_emitted);
+
+        let complete = entries
+            .iter()
+            .enumerate()
+            .take_while(|(idx, entry)| {
+                self.open_entries.get(&entry.pid) != Some(&(self.lines_emitted + idx))
+                    && self.unfinished.get(&entry.pid) != Some(&entry.line_number)
+            })
+            .count();
+        self.lines_emitted += complete;
+        self.complete_entries.extend(entries.drain(..complete));
+        self.line_entries = entries;
+        error
+    }
+
+    /// Add a line that continues the entry being parsed to it: a backtrace frame, or a lone
+    /// `= retval` when the entry has no return yet. Returns whether the line was consumed.
+    fn extend_entry(&mut self, line: &str, entry: Option<&mut SyscallEntry>) -> bool {
+        // Check if this is a backtrace line (starts with " > ")
+        if line.trim_start().starts_with(">") {
+            if let Some(entry) = entry
+                && entry.signal.is_none()
+                && entry.exit_info.is_none()
+            {
+                match parse_backtrace_line(line) {
+                    Ok(frame) => entry.backtrace.push(frame),
+                    Err(e) => self.push_error(e, line),
                 }
             }
+            return true;
         }
 
-        // Don't forget the last entry
-        if let Some(entry) = current_entry {
-            entries.push(entry);
+        // A lone "= retval" line completes the previous call, when it has no return yet
+        entry.is_some_and(|entry| {
+            let completed = entry.return_value.is_none()
+                && !entry.is_unfinished
+                && entry.signal.is_none()
+                && entry.exit_info.is_none()
+                && parse_return_continuation(line, entry);
+            if completed {
+                entry.raw_line.push('\n');
+                entry.raw_line.push_str(line);
+            }
+            completed
+        })
+    }
+
+    /// Remove the errors recorded so far, along with the text of their lines
+    pub fn take_errors(&mut self) -> Vec<ParseErrorInfo> {
+        let errors = std::mem::take(&mut self.errors)
+            .into_iter()
+            .map(|(line_number, err)| ParseErrorInfo {
+                line_number,
+                message: err.to_string(),
+                kind: err.kind().to_string(),
+                raw_line: self.error_lines.get(&line_number).cloned(),
+            })
+            .collect();
+        self.error_lines.clear();
+        errors
+    }
+
+    /// Keep a line that failed to parse: as program output when it doesn't look like strace
+    /// output at all, or else as an error, which is returned
+    fn record_unparsed(&mut self, error: ParseError, line: String) -> Option<ParseError> {
+        if looks_like_strace_line(&line) {
+            self.push_error(error.clone(), &line);
+            Some(error)
+        } else {
+            self.program_output.push((self.line_number, line));
+            None
         }
+    }
 
-        Ok(entries)
+    /// Record an error for the current line, keeping its text for reporting
+    fn push_error(&mut self, error: ParseError, line: &str) {
+        self.errors.push((self.line_number, error));
+        self.error_lines.insert(self.line_number, line.to_string());
     }
 }
 
+/// The rest of a line after its first word, if that looks like something a logger wrote: a tag
+/// in brackets, like `[info]` or `[2024-01-01 00:00:00]`, or a timestamp, like
+/// `2024-01-01T00:00:00.123Z` or `00:00:00,123`
+fn after_log_word(line: &str) -> Option<&str> {
+    let end = if line.starts_with('[') {
+        line.find(']')? + 1
+    } else {
+        let end = line.find(char::is_whitespace).unwrap_or(line.len());
+        let word = line[..end].trim_end_matches(':');
+        let is_timestamp = word.contains(|c: char| c.is_ascii_digit())
+            && word.contains([':', '-'])
+            && word
+                .chars()
+                .all(|c| c.is_ascii_digit() || "-:.,+TZ".contains(c));
+        if !is_timestamp {
+            return None;
+        }
+        end
+    };
+    Some(line[end..].trim_start_matches(':').trim_start())
+}
+
+/// Index of the entry started by a line, as entries are in the order of their lines
+fn entry_at_line(entries: &[SyscallEntry], line_number: usize) -> Option<usize> {
+    entries
+        .binary_search_by_key(&line_number, |entry| entry.line_number)
+        .ok()
+}
+
 impl Default for StraceParser {
     fn default() -> Self {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push_line() {
+        let mut parser = StraceParser::new();
+
+        assert!(
+            parser
+                .push_line("1 10:00:00 read(3, <unfinished ...>")
+                .is_none()
+        );
+        // The read waits for its resumption, and the write for its backtrace
+        assert!(
+            parser
+                .push_line("2 10:00:00 write(1, \"x\", 1) = 1")
+                .is_none()
+        );
+        assert!(
+            parser
+                .push_line(" > /usr/lib/libc.so.6(write+0x14) [0x10e5d4]")
+                .is_none()
+        );
+        let read = parser
+            .push_line("1 10:00:01 <... read resumed>\"y\", 1) = 1")
+            .unwrap();
+        assert!(read.is_unfinished);
+        assert_eq!(read.resumed_entry_idx, Some(2));
+        assert!(parser.pop_entry().is_none());
+
+        // The write is complete once its PID moves on
+        let write = parser.push_line("2 10:00:01 close(1) = 0").unwrap();
+        assert_eq!(write.syscall_name, "write");
+        assert_eq!(write.backtrace.len(), 1);
+
+        let resumed = parser.flush().unwrap();
+        assert!(resumed.is_resumed);
+        assert_eq!(resumed.unfinished_entry_idx, Some(0));
+        assert_eq!(resumed.return_value.as_deref(), Some("1"));
+        assert_eq!(parser.flush().unwrap().syscall_name, "close");
+        assert!(parser.flush().is_none());
+    }
+
+    #[test]
+    fn test_push_line_errors_and_output() {
+        let mut parser = StraceParser::new();
+
+        assert!(parser.push_line("1 10:00:00 close(3) = 0").is_none());
+        assert!(parser.push_line("").is_none());
+        // Program output doesn't complete the entry it came between
+        assert!(parser.push_line("hello, world").is_none());
+        assert_eq!(parser.program_output, [(3, "hello, world".to_string())]);
+        // Its PID exiting does, along with the exit
+        let close = parser
+            .push_line("1 10:00:00 +++ exited with 0 +++")
+            .unwrap();
+        assert_eq!(close.syscall_name, "close");
+        assert!(parser.pop_entry().unwrap().exit_info.is_some());
+        assert!(parser.flush().is_none());
+
+        // A resumed call whose start came before the first line
+        parser.push_line("2 10:00:00 <... poll resumed>) = 1");
+        assert!(parser.flush().unwrap().orphan_resumed);
+        assert!(parser.errors.is_empty());
+    }
+}
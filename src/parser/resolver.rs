@@ -1,20 +1,167 @@
-use super::{BacktraceFrame, ParseResult, ResolvedFrame};
-use std::collections::HashMap;
+use super::debuginfo::{DebugInfo, Debuginfod};
+use super::{BacktraceFrame, ParseResult, ResolvedFrame, SyscallEntry};
+use object::{Object, ObjectSymbol, SymbolKind};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::SystemTime;
 
 /// Resolver for converting addresses to source locations using addr2line
 pub struct Addr2LineResolver {
-    /// Cache of loaders per binary path
-    loaders: HashMap<String, addr2line::Loader>,
+    /// Cache of loaded binaries per path
+    binaries: HashMap<String, Binary>,
     /// Cache of resolved addresses to avoid redundant lookups
     cache: HashMap<String, Option<Vec<ResolvedFrame>>>,
+    /// Frames of link-time addresses per binary file, which hold whatever the load bias, so
+    /// they are the ones saved to disk
+    linked: HashMap<String, HashMap<u64, Option<Vec<ResolvedFrame>>>>,
+    /// Binary files whose linked frames were used by this run, kept first when saving
+    used: HashSet<String>,
+    /// Offset between the runtime and link-time addresses of each binary, once given or learned
+    load_biases: HashMap<String, u64>,
+    /// Root of the separate debug files installed locally
+    debug_dir: PathBuf,
+    /// Where to fetch debug files of binaries without line info, if enabled
+    debuginfod: Option<Arc<Debuginfod>>,
+    /// Directory of the on-disk cache, saved to when dropped, if enabled
+    cache_dir: Option<PathBuf>,
+    /// Where the binaries of the trace are on this machine, for traces of another one
+    host_paths: HostPaths,
+    /// Whether the cache has entries not saved yet
+    dirty: bool,
+}
+
+/// How to look up the source locations of backtrace addresses, from the command line and the
+/// config file
+#[derive(Debug, Clone, Default)]
+pub struct ResolverOptions {
+    /// Fetch debug files missing locally from the servers in `DEBUGINFOD_URLS`
+    pub debuginfod: bool,
+    /// Keep the resolved frames in the user's cache directory across runs
+    pub disk_cache: bool,
+    /// Where the traced binaries are, when traced on another machine
+    pub sysroot: Option<PathBuf>,
+    /// Rewrites of the binary paths, from `--path-map`
+    pub path_prefixes: Vec<(String, String)>,
+}
+
+impl ResolverOptions {
+    /// Why the options can't all be followed, to warn about before resolving
+    pub fn warning(&self) -> Option<&'static str> {
+        (self.debuginfod && Debuginfod::from_env().is_none())
+            .then_some("debuginfod needs DEBUGINFOD_URLS to be set")
+    }
 }
 
 impl Addr2LineResolver {
     pub fn new() -> Self {
         Self {
-            loaders: HashMap::new(),
+            binaries: HashMap::new(),
             cache: HashMap::new(),
+            linked: HashMap::new(),
+            used: HashSet::new(),
+            load_biases: HashMap::new(),
+            debug_dir: PathBuf::from("/usr/lib/debug"),
+            debuginfod: None,
+            cache_dir: None,
+            host_paths: HostPaths::default(),
+            dirty: false,
+        }
+    }
+
+    /// Create a resolver that asks debuginfod for the debug files missing locally
+    pub fn with_debuginfod(debuginfod: Debuginfod) -> Self {
+        let mut resolver = Self::new();
+        resolver.set_debuginfod(debuginfod);
+        resolver
+    }
+
+    /// Create a resolver whose cache persists in `cache_dir` across runs. Entries of binaries
+    /// modified since they were cached are dropped.
+    pub fn with_cache_dir(cache_dir: impl Into<PathBuf>) -> Self {
+        let cache_dir = cache_dir.into();
+        let mut resolver = Self::new();
+
+        for (binary, cached) in DiskCache::load(&cache_dir).binaries {
+            if cached.is_current(&binary) {
+                resolver.linked.insert(binary, cached.frames);
+            }
         }
+
+        resolver.cache_dir = Some(cache_dir);
+        resolver
+    }
+
+    /// Create a resolver cached in the user's cache directory (e.g. `~/.cache/strace-tui`)
+    pub fn with_user_cache() -> Self {
+        match dirs::cache_dir() {
+            Some(dir) => Self::with_cache_dir(dir.join("strace-tui")),
+            None => Self::new(),
+        }
+    }
+
+    /// Create a resolver set up as `options` say. Options that can't be followed, see
+    /// [`ResolverOptions::warning`], are left out.
+    pub fn from_options(options: &ResolverOptions) -> Self {
+        let mut resolver = if options.disk_cache {
+            Self::with_user_cache()
+        } else {
+            Self::new()
+        };
+        if let Some(sysroot) = &options.sysroot {
+            resolver.set_sysroot(sysroot);
+        }
+        for (from, to) in &options.path_prefixes {
+            resolver.add_path_prefix(from, to);
+        }
+        if options.debuginfod
+            && let Some(debuginfod) = Debuginfod::from_env()
+        {
+            resolver.set_debuginfod(debuginfod);
+        }
+        resolver
+    }
+
+    /// Ask debuginfod for the debug files missing locally
+    pub fn set_debuginfod(&mut self, debuginfod: Debuginfod) {
+        self.debuginfod = Some(Arc::new(debuginfod));
+    }
+
+    /// Look for the binaries of the trace under `sysroot`, e.g. `/lib/libc.so.6` at
+    /// `<sysroot>/lib/libc.so.6`, along with their debug files under `<sysroot>/usr/lib/debug`
+    pub fn set_sysroot(&mut self, sysroot: impl Into<PathBuf>) {
+        let sysroot = sysroot.into();
+        self.debug_dir = sysroot.join("usr/lib/debug");
+        self.host_paths.sysroot = Some(sysroot);
+    }
+
+    /// Read the binaries under `from` from `to` instead, e.g. `/app/bin/foo` from
+    /// `/home/me/build/foo` for `/app/bin` -> `/home/me/build`. The longest matching prefix
+    /// wins, and a binary matching none is looked for under the sysroot.
+    pub fn add_path_prefix(&mut self, from: &str, to: &str) {
+        self.host_paths
+            .prefixes
+            .push((from.trim_end_matches('/').to_string(), to.to_string()));
+    }
+
+    /// Where a binary of the trace is on this machine
+    fn host_path(&self, binary: &str) -> String {
+        self.host_paths.get(binary)
+    }
+
+    /// Key of a frame's address in the cache, which is the file actually read, so that the
+    /// on-disk cache never mixes up the binaries of different sysroots
+    fn cache_key(&self, frame: &BacktraceFrame) -> String {
+        format!("{}:{}", self.host_path(&frame.binary), frame.address)
+    }
+
+    /// Set the load bias of a binary, subtracted from its backtrace addresses before lookup.
+    /// Frames with a `function+offset` override it with the bias they imply.
+    pub fn set_load_bias(&mut self, binary: &str, bias: u64) {
+        self.load_biases.insert(binary.to_string(), bias);
     }
 
     /// Get the number of cached resolutions
@@ -22,121 +169,640 @@ impl Addr2LineResolver {
         self.cache.len()
     }
 
+    /// The frames of an address if already resolved, by this run or, once the load bias of its
+    /// binary is known, by an earlier one
+    fn cached(&mut self, frame: &BacktraceFrame) -> Option<Option<Vec<ResolvedFrame>>> {
+        let cache_key = self.cache_key(frame);
+        if let Some(cached) = self.cache.get(&cache_key) {
+            return Some(cached.clone());
+        }
+
+        let host_path = self.host_path(&frame.binary);
+        let load_bias = self.load_biases.get(&frame.binary)?;
+        let link_address = parse_hex(&frame.address)?.wrapping_sub(*load_bias);
+        let linked = self.linked.get(&host_path)?.get(&link_address)?.clone();
+        self.used.insert(host_path);
+        self.cache.insert(cache_key, linked.clone());
+        Some(linked)
+    }
+
+    /// Cache the frames of a link-time address, for this run and the next ones
+    fn insert_linked(
+        &mut self,
+        host_path: String,
+        link_address: u64,
+        frames: Option<Vec<ResolvedFrame>>,
+    ) {
+        self.linked
+            .entry(host_path.clone())
+            .or_default()
+            .insert(link_address, frames);
+        self.used.insert(host_path);
+        self.dirty = true;
+    }
+
     /// Resolve a single backtrace frame
     pub fn resolve_frame(&mut self, frame: &mut BacktraceFrame) -> ParseResult<()> {
-        let cache_key = format!("{}:{}", frame.binary, frame.address);
-
         // Check cache first
-        if let Some(cached) = self.cache.get(&cache_key) {
-            frame.resolved = cached.clone();
+        if let Some(cached) = self.cached(frame) {
+            frame.resolved = cached;
             return Ok(());
         }
+        let cache_key = self.cache_key(frame);
 
         // Try to resolve using addr2line
-        let resolved = self.resolve_address(&frame.binary, &frame.address);
+        log::debug!(
+            "Resolving address {} in binary {}",
+            frame.address,
+            frame.binary
+        );
+        if !self.binaries.contains_key(&frame.binary)
+            && let Some(binary) = Binary::load(
+                &self.host_path(&frame.binary),
+                &self.debug_dir,
+                self.debuginfod.as_deref(),
+            )
+        {
+            self.binaries.insert(frame.binary.clone(), binary);
+        }
+        let lookup = match self.binaries.get_mut(&frame.binary) {
+            Some(binary) => {
+                let mut load_bias = self.load_biases.get(&frame.binary).copied();
+                let lookup = binary.resolve(frame, &mut load_bias);
+                if let Some(load_bias) = load_bias {
+                    self.load_biases.insert(frame.binary.clone(), load_bias);
+                }
+                lookup
+            }
+            None => Lookup::Known(None),
+        };
 
-        // Cache the result
-        self.cache.insert(cache_key, resolved.clone());
-        frame.resolved = resolved;
+        // Cache the result, unless a later frame may teach the bias that gives the right one
+        frame.resolved = match lookup {
+            Lookup::Known(resolved) => {
+                self.cache.insert(cache_key, resolved.clone());
+                resolved
+            }
+            Lookup::Linked(link_address, resolved) => {
+                self.cache.insert(cache_key, resolved.clone());
+                let host_path = self.host_path(&frame.binary);
+                self.insert_linked(host_path, link_address, resolved.clone());
+                resolved
+            }
+            Lookup::Guess(resolved) => resolved,
+        };
 
         Ok(())
     }
 
     /// Resolve all frames in a list
     pub fn resolve_frames(&mut self, frames: &mut [BacktraceFrame]) -> ParseResult<()> {
-        for frame in frames.iter_mut() {
+        // Frames with a function come first, as they tell the load bias
+        for frame in frames.iter_mut().filter(|frame| frame.offset.is_some()) {
             // Ignore errors for individual frames
             let _ = self.resolve_frame(frame);
         }
+        for frame in frames.iter_mut().filter(|frame| frame.offset.is_none()) {
+            let _ = self.resolve_frame(frame);
+        }
         Ok(())
     }
 
-    /// Get or create a loader for the given binary
-    fn get_loader(&mut self, binary: &str) -> Option<&addr2line::Loader> {
-        // If already loaded, return it
-        if self.loaders.contains_key(binary) {
-            return self.loaders.get(binary);
+    /// Resolve the backtraces of all entries, spreading the work over several threads
+    pub fn resolve_all(&mut self, entries: &mut [SyscallEntry]) {
+        self.resolve_all_with_progress(entries, &ResolveProgress::default());
+    }
+
+    /// [`resolve_all`](Self::resolve_all), counting the addresses done in `progress` for
+    /// another thread to show
+    pub fn resolve_all_with_progress(
+        &mut self,
+        entries: &mut [SyscallEntry],
+        progress: &ResolveProgress,
+    ) {
+        let resolved = self.job_for(entries).run(progress);
+        self.finish_job(resolved, entries);
+    }
+
+    /// The addresses of `entries` missing from the cache, to look up with [`ResolveJob::run`],
+    /// possibly on another thread, and then hand to [`finish_job`](Self::finish_job).
+    ///
+    /// Uncached addresses are grouped by binary, so each binary is loaded and queried by a
+    /// single worker.
+    pub fn job_for(&mut self, entries: &[SyscallEntry]) -> ResolveJob {
+        let mut by_binary: HashMap<&str, Vec<BacktraceFrame>> = HashMap::new();
+        for frame in entries.iter().flat_map(|entry| &entry.backtrace) {
+            if self.cached(frame).is_none() {
+                let frames = by_binary.entry(&frame.binary).or_default();
+                if !frames.iter().any(|other| other.address == frame.address) {
+                    frames.push(frame.clone());
+                }
+            }
         }
 
-        // Try to load the binary
-        match addr2line::Loader::new(binary) {
-            Ok(loader) => {
-                self.loaders.insert(binary.to_string(), loader);
-                self.loaders.get(binary)
+        // Largest binaries first, so a big one isn't left to run alone at the end
+        let mut queue: Vec<(String, Vec<BacktraceFrame>)> = by_binary
+            .into_iter()
+            .map(|(binary, frames)| (binary.to_string(), frames))
+            .collect();
+        queue.sort_by_key(|(_, frames)| frames.len());
+
+        ResolveJob {
+            queue,
+            load_biases: self.load_biases.clone(),
+            debug_dir: self.debug_dir.clone(),
+            debuginfod: self.debuginfod.clone(),
+            host_paths: self.host_paths.clone(),
+        }
+    }
+
+    /// Merge what a [`ResolveJob`] found into the cache, then copy it into the frames of
+    /// `entries`
+    pub fn finish_job(&mut self, resolved: ResolvedAddresses, entries: &mut [SyscallEntry]) {
+        self.cache.extend(resolved.frames);
+        for (host_path, link_address, frames) in resolved.linked {
+            self.insert_linked(host_path, link_address, frames);
+        }
+        self.load_biases.extend(resolved.load_biases);
+        let guesses: HashMap<_, _> = resolved.guesses.into_iter().collect();
+
+        for frame in entries.iter_mut().flat_map(|entry| &mut entry.backtrace) {
+            frame.resolved = match self.cached(frame) {
+                Some(resolved) => resolved,
+                None => guesses.get(&self.cache_key(frame)).cloned().flatten(),
+            };
+        }
+    }
+
+    /// Write the cache to the cache directory, merged with what other runs saved meanwhile.
+    /// Past [`DiskCache::MAX_FRAMES`], the binaries used longest ago are dropped.
+    pub fn save(&mut self) -> std::io::Result<()> {
+        let Some(cache_dir) = &self.cache_dir else {
+            return Ok(());
+        };
+
+        let mut disk = DiskCache::load(cache_dir);
+        disk.binaries
+            .retain(|binary, cached| cached.is_current(binary));
+
+        let now = SystemTime::now();
+        for binary in &self.used {
+            let Some(frames) = self.linked.get(binary) else {
+                continue;
+            };
+            if !disk.binaries.contains_key(binary) {
+                let Some((modified, size)) = file_stamp(binary) else {
+                    continue;
+                };
+                disk.binaries.insert(
+                    binary.to_string(),
+                    CachedBinary {
+                        modified,
+                        size,
+                        used: now,
+                        frames: HashMap::new(),
+                    },
+                );
             }
-            Err(_) => None,
+            let cached = disk.binaries.get_mut(binary).unwrap();
+            cached.used = now;
+            cached.frames.extend(
+                frames
+                    .iter()
+                    .map(|(address, frames)| (*address, frames.clone())),
+            );
         }
+
+        disk.prune();
+        disk.save(cache_dir)?;
+        self.dirty = false;
+        Ok(())
+    }
+}
+
+impl Drop for Addr2LineResolver {
+    fn drop(&mut self) {
+        if self.dirty
+            && let Err(err) = self.save()
+        {
+            log::warn!("Failed to save the resolver cache: {}", err);
+        }
+    }
+}
+
+/// Uncached addresses of a trace, from [`Addr2LineResolver::job_for`]. It owns all it needs, so
+/// it can run on another thread while the resolver is still used for single frames.
+pub struct ResolveJob {
+    queue: Vec<(String, Vec<BacktraceFrame>)>,
+    load_biases: HashMap<String, u64>,
+    debug_dir: PathBuf,
+    debuginfod: Option<Arc<Debuginfod>>,
+    host_paths: HostPaths,
+}
+
+impl ResolveJob {
+    /// Number of addresses to resolve
+    pub fn len(&self) -> usize {
+        self.queue.iter().map(|(_, frames)| frames.len()).sum()
     }
 
-    /// Resolve an address using addr2line crate with find_frames
-    fn resolve_address(&mut self, binary: &str, address_str: &str) -> Option<Vec<ResolvedFrame>> {
-        log::debug!("Resolving address {} in binary {}", address_str, binary);
-        // Get or create loader for this binary
-        let loader = self.get_loader(binary)?;
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
 
-        // Parse address (handle 0x prefix)
-        let address_str = address_str.strip_prefix("0x").unwrap_or(address_str);
-        let address = u64::from_str_radix(address_str, 16).ok()?;
+    /// Look up every address, over as many threads as there are CPUs (but not binaries)
+    pub fn run(self, progress: &ResolveProgress) -> ResolvedAddresses {
+        progress.start(self.len());
 
-        // Find frames (can be multiple due to inlining)
-        match loader.find_frames(address) {
-            Ok(mut frames_iter) => {
-                let mut resolved_frames = Vec::new();
+        let workers = thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1)
+            .min(self.queue.len());
+        let queue = Mutex::new(self.queue);
+        let results = Mutex::new(Vec::new());
+        let linked = Mutex::new(Vec::new());
+        let guesses = Mutex::new(Vec::new());
+        let load_biases = Mutex::new(self.load_biases);
+        let debug_dir = self.debug_dir.as_path();
+        let debuginfod = self.debuginfod.as_deref();
+        let host_paths = &self.host_paths;
 
-                // Collect all frames
-                loop {
-                    match frames_iter.next() {
-                        Ok(Some(frame)) => {
-                            // Skip if file is unknown
-                            if let Some(location) = &frame.location {
-                                if location.file == Some("??") {
-                                    continue;
-                                }
+        thread::scope(|scope| {
+            for _ in 0..workers {
+                scope.spawn(|| {
+                    while let Some((path, mut frames)) = queue.lock().unwrap().pop() {
+                        let host_path = host_paths.get(&path);
+                        let mut binary = Binary::load(&host_path, debug_dir, debuginfod);
+                        let mut load_bias = load_biases.lock().unwrap().get(&path).copied();
 
-                                // Get function name (demangle it)
-                                let function_name = if let Some(func) = &frame.function {
-                                    match func.demangle() {
-                                        Ok(name) => name.to_string(),
-                                        Err(_) => "<unknown>".to_string(),
+                        // Frames with a function come first, as they tell the load bias
+                        frames.sort_by_key(|frame| frame.offset.is_none());
+                        let mut resolved = Vec::new();
+                        let mut linked_frames = Vec::new();
+                        let mut guessed = Vec::new();
+                        for frame in frames {
+                            let key = format!("{}:{}", host_path, frame.address);
+                            match binary.as_mut() {
+                                Some(binary) => match binary.resolve(&frame, &mut load_bias) {
+                                    Lookup::Known(frames) => resolved.push((key, frames)),
+                                    Lookup::Linked(link_address, frames) => {
+                                        resolved.push((key, frames.clone()));
+                                        linked_frames.push((
+                                            host_path.clone(),
+                                            link_address,
+                                            frames,
+                                        ));
                                     }
-                                } else {
-                                    "<unknown>".to_string()
-                                };
-
-                                let file = location.file?.to_string();
-                                let line = location.line?;
-                                let column = location.column;
-
-                                resolved_frames.push(ResolvedFrame {
-                                    function: function_name,
-                                    file,
-                                    line,
-                                    column,
-                                    is_inlined: false, // Will be set after collecting all
-                                });
+                                    Lookup::Guess(frames) => guessed.push((key, frames)),
+                                },
+                                None => resolved.push((key, None)),
                             }
+                            progress.advance();
+                        }
+
+                        results.lock().unwrap().extend(resolved);
+                        linked.lock().unwrap().extend(linked_frames);
+                        guesses.lock().unwrap().extend(guessed);
+                        if let Some(load_bias) = load_bias {
+                            load_biases.lock().unwrap().insert(path, load_bias);
                         }
-                        Ok(None) => break,
-                        Err(_) => break,
                     }
+                });
+            }
+        });
+
+        ResolvedAddresses {
+            frames: results.into_inner().unwrap(),
+            linked: linked.into_inner().unwrap(),
+            guesses: guesses.into_inner().unwrap(),
+            load_biases: load_biases.into_inner().unwrap(),
+        }
+    }
+}
+
+/// What a [`ResolveJob`] found, for [`Addr2LineResolver::finish_job`]
+pub struct ResolvedAddresses {
+    frames: Vec<(String, Option<Vec<ResolvedFrame>>)>,
+    /// Frames of link-time addresses, per binary file
+    linked: Vec<(String, u64, Option<Vec<ResolvedFrame>>)>,
+    /// Frames looked up without knowing the load bias, shown but not cached
+    guesses: Vec<(String, Option<Vec<ResolvedFrame>>)>,
+    load_biases: HashMap<String, u64>,
+}
+
+/// How far a [`ResolveJob`] got, readable from any thread while it runs
+#[derive(Debug, Default)]
+pub struct ResolveProgress {
+    done: AtomicUsize,
+    total: AtomicUsize,
+}
+
+impl ResolveProgress {
+    /// Addresses resolved so far, and the total
+    pub fn get(&self) -> (usize, usize) {
+        (
+            self.done.load(Ordering::Relaxed),
+            self.total.load(Ordering::Relaxed),
+        )
+    }
+
+    fn start(&self, total: usize) {
+        self.done.store(0, Ordering::Relaxed);
+        self.total.store(total, Ordering::Relaxed);
+    }
+
+    fn advance(&self) {
+        self.done.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Resolved frames saved between runs, per binary
+#[derive(Default, Serialize, Deserialize)]
+struct DiskCache {
+    binaries: HashMap<String, CachedBinary>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedBinary {
+    /// Modification time and size of the binary when resolved, to notice rebuilds
+    modified: SystemTime,
+    size: u64,
+    /// When a run last used the binary's frames
+    used: SystemTime,
+    /// Resolved frames per link-time address
+    frames: HashMap<u64, Option<Vec<ResolvedFrame>>>,
+}
+
+impl CachedBinary {
+    fn is_current(&self, binary: &str) -> bool {
+        file_stamp(binary) == Some((self.modified, self.size))
+    }
+}
+
+impl DiskCache {
+    const FILE_NAME: &str = "resolved.json";
+    /// Frames kept over all binaries, past which the binaries used longest ago are dropped
+    const MAX_FRAMES: usize = 100_000;
+
+    /// Drop the binaries used longest ago until the frames fit in [`Self::MAX_FRAMES`], but
+    /// always keep the last used one
+    fn prune(&mut self) {
+        let mut binaries: Vec<_> = self.binaries.drain().collect();
+        binaries.sort_by_key(|(_, cached)| std::cmp::Reverse(cached.used));
+
+        let mut total = 0;
+        for (binary, cached) in binaries {
+            total += cached.frames.len();
+            if total > Self::MAX_FRAMES && !self.binaries.is_empty() {
+                break;
+            }
+            self.binaries.insert(binary, cached);
+        }
+    }
+
+    /// Load the cache file, starting empty if it's missing or unreadable
+    fn load(cache_dir: &Path) -> Self {
+        std::fs::read(cache_dir.join(Self::FILE_NAME))
+            .ok()
+            .and_then(|data| serde_json::from_slice(&data).ok())
+            .unwrap_or_default()
+    }
+
+    /// Save through a temporary file, so a concurrent load never sees half of it
+    fn save(&self, cache_dir: &Path) -> std::io::Result<()> {
+        std::fs::create_dir_all(cache_dir)?;
+        let path = cache_dir.join(Self::FILE_NAME);
+        let partial = path.with_extension("json.part");
+        std::fs::write(&partial, serde_json::to_vec(self)?)?;
+        std::fs::rename(partial, path)
+    }
+}
+
+/// Rewrites of the binary paths of a trace into paths of this machine
+#[derive(Debug, Clone, Default)]
+struct HostPaths {
+    /// (from, to) prefix pairs, `from` without a trailing slash
+    prefixes: Vec<(String, String)>,
+    sysroot: Option<PathBuf>,
+}
+
+impl HostPaths {
+    fn get(&self, binary: &str) -> String {
+        let rewrite = self
+            .prefixes
+            .iter()
+            .filter_map(|(from, to)| {
+                // Whole path components only: /app/bin doesn't match /app/binaries
+                let rest = binary.strip_prefix(from.as_str())?;
+                (rest.is_empty() || rest.starts_with('/')).then_some((from.len(), to, rest))
+            })
+            .max_by_key(|(len, _, _)| *len);
+        if let Some((_, to, rest)) = rewrite {
+            return format!("{}{}", to.trim_end_matches('/'), rest);
+        }
+
+        match &self.sysroot {
+            Some(sysroot) => sysroot
+                .join(binary.trim_start_matches('/'))
+                .to_string_lossy()
+                .into_owned(),
+            None => binary.to_string(),
+        }
+    }
+}
+
+fn file_stamp(path: &str) -> Option<(SystemTime, u64)> {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// A binary loaded for resolving its addresses
+struct Binary {
+    path: String,
+    loader: addr2line::Loader,
+    /// Link-time address of each function symbol, read on first use
+    symbols: Option<HashMap<String, u64>>,
+}
+
+impl Binary {
+    /// Load a binary, or its separate debug file when it has no line info of its own. The
+    /// debug file is looked for locally first, then on debuginfod.
+    fn load(path: &str, debug_dir: &Path, debuginfod: Option<&Debuginfod>) -> Option<Self> {
+        let binary = |loader| Self {
+            path: path.to_string(),
+            loader,
+            symbols: None,
+        };
+
+        if let Some(info) = DebugInfo::read(path)
+            && !info.has_line_info
+        {
+            let debug_file = info.find_debug_file(path, debug_dir).or_else(|| {
+                let build_id = info.build_id.as_ref()?;
+                debuginfod?.debuginfo(build_id)
+            });
+
+            if let Some(debug_path) = debug_file {
+                log::debug!("Using debug file {} for {}", debug_path.display(), path);
+                match addr2line::Loader::new(&debug_path) {
+                    Ok(loader) => return Some(binary(loader)),
+                    Err(err) => log::debug!("Failed to load {}: {}", debug_path.display(), err),
                 }
+            }
+        }
 
-                // Mark all but the last as inlined
-                let len = resolved_frames.len();
-                if len > 1 {
-                    for frame in resolved_frames.iter_mut().take(len - 1) {
-                        frame.is_inlined = true;
+        addr2line::Loader::new(path).ok().map(binary)
+    }
+
+    /// Resolve a frame of this binary, at its link-time address.
+    ///
+    /// Backtrace addresses of position-independent binaries are shifted by where the binary
+    /// was loaded. A `function+offset` pins the link-time address down from the symbol table,
+    /// and the shift it reveals is kept in `load_bias` for the frames that lack one. Until
+    /// then, those are looked up as if there was no shift, which only holds for non-PIE
+    /// binaries, so that is only a guess.
+    fn resolve(&mut self, frame: &BacktraceFrame, load_bias: &mut Option<u64>) -> Lookup {
+        let Some(address) = parse_hex(&frame.address) else {
+            return Lookup::Known(None);
+        };
+
+        if let Some(function) = &frame.function
+            && let Some(offset) = frame.offset.as_deref().and_then(parse_hex)
+            && let Some(symbol) = self.symbol_address(function)
+        {
+            let link_address = symbol + offset;
+            *load_bias = Some(address.wrapping_sub(link_address));
+            return Lookup::Linked(link_address, find_frames(&self.loader, link_address));
+        }
+
+        match *load_bias {
+            Some(load_bias) => {
+                let link_address = address.wrapping_sub(load_bias);
+                Lookup::Linked(link_address, find_frames(&self.loader, link_address))
+            }
+            None => Lookup::Guess(find_frames(&self.loader, address)),
+        }
+    }
+
+    fn symbol_address(&mut self, name: &str) -> Option<u64> {
+        let path = &self.path;
+        self.symbols
+            .get_or_insert_with(|| symbol_addresses(path))
+            .get(name)
+            .copied()
+    }
+}
+
+/// Frames of an address from [`Binary::resolve`]
+enum Lookup {
+    /// Not of a link-time address, e.g. an unparsable one
+    Known(Option<Vec<ResolvedFrame>>),
+    /// At a link-time address, the same in any run
+    Linked(u64, Option<Vec<ResolvedFrame>>),
+    /// Made without knowing the load bias, so not to be cached
+    Guess(Option<Vec<ResolvedFrame>>),
+}
+
+/// Link-time addresses of the function symbols of a binary, from both symbol tables. Names
+/// given to several functions, like static functions of different files, are left out, as
+/// they can't tell which one a frame is in.
+fn symbol_addresses(path: &str) -> HashMap<String, u64> {
+    let Ok(data) = std::fs::read(path) else {
+        return HashMap::new();
+    };
+    let Ok(file) = object::File::parse(&*data) else {
+        return HashMap::new();
+    };
+
+    let mut addresses: HashMap<String, Option<u64>> = HashMap::new();
+    let symbols = file
+        .symbols()
+        .chain(file.dynamic_symbols())
+        .filter(|symbol| symbol.kind() == SymbolKind::Text && symbol.address() != 0);
+    for symbol in symbols {
+        let Ok(name) = symbol.name() else {
+            continue;
+        };
+        addresses
+            .entry(name.to_string())
+            .and_modify(|address| {
+                if *address != Some(symbol.address()) {
+                    *address = None;
+                }
+            })
+            .or_insert(Some(symbol.address()));
+    }
+
+    addresses
+        .into_iter()
+        .filter_map(|(name, address)| Some((name, address?)))
+        .collect()
+}
+
+fn parse_hex(text: &str) -> Option<u64> {
+    u64::from_str_radix(text.strip_prefix("0x").unwrap_or(text), 16).ok()
+}
+
+/// Look up the (possibly inlined) source frames of an address in a loaded binary
+fn find_frames(loader: &addr2line::Loader, address: u64) -> Option<Vec<ResolvedFrame>> {
+    // Find frames (can be multiple due to inlining)
+    match loader.find_frames(address) {
+        Ok(mut frames_iter) => {
+            let mut resolved_frames = Vec::new();
+
+            // Collect all frames
+            loop {
+                match frames_iter.next() {
+                    Ok(Some(frame)) => {
+                        // Skip if file is unknown
+                        if let Some(location) = &frame.location {
+                            if location.file == Some("??") {
+                                continue;
+                            }
+
+                            // Get function name (demangle it)
+                            let function_name = if let Some(func) = &frame.function {
+                                match func.demangle() {
+                                    Ok(name) => name.to_string(),
+                                    Err(_) => "<unknown>".to_string(),
+                                }
+                            } else {
+                                "<unknown>".to_string()
+                            };
+
+                            let file = location.file?.to_string();
+                            let line = location.line?;
+                            let column = location.column;
+
+                            resolved_frames.push(ResolvedFrame {
+                                function: function_name,
+                                file,
+                                line,
+                                column,
+                                is_inlined: false, // Will be set after collecting all
+                            });
+                        }
                     }
+                    Ok(None) => break,
+                    Err(_) => break,
                 }
+            }
 
-                if resolved_frames.is_empty() {
-                    None
-                } else {
-                    Some(resolved_frames)
+            // Mark all but the last as inlined
+            let len = resolved_frames.len();
+            if len > 1 {
+                for frame in resolved_frames.iter_mut().take(len - 1) {
+                    frame.is_inlined = true;
                 }
             }
-            Err(_) => None,
+
+            if resolved_frames.is_empty() {
+                None
+            } else {
+                Some(resolved_frames)
+            }
         }
+        Err(_) => None,
     }
 }
 
@@ -150,6 +816,40 @@ impl Default for Addr2LineResolver {
 mod tests {
     use super::*;
     use crate::parser::BacktraceFrame;
+    use std::io::Write;
+
+    /// Resolve an address without function info
+    fn resolve(
+        resolver: &mut Addr2LineResolver,
+        binary: &str,
+        address: &str,
+    ) -> Option<Vec<ResolvedFrame>> {
+        let mut frame = BacktraceFrame {
+            binary: binary.to_string(),
+            function: None,
+            offset: None,
+            address: address.to_string(),
+            resolved: None,
+        };
+        resolver.resolve_frame(&mut frame).unwrap();
+        frame.resolved
+    }
+
+    /// Find an address that resolves in a binary, and what it resolves to
+    fn resolvable_address(binary: &str) -> (String, Vec<ResolvedFrame>) {
+        let text = addr2line::Loader::new(binary)
+            .unwrap()
+            .get_section_range(b".text")
+            .unwrap();
+        let mut resolver = Addr2LineResolver::new();
+        (text.begin..text.end)
+            .step_by(0x10)
+            .find_map(|address| {
+                let address = format!("{:#x}", address);
+                resolve(&mut resolver, binary, &address).map(|frames| (address, frames))
+            })
+            .unwrap()
+    }
 
     #[test]
     fn test_resolver_caching() {
@@ -163,11 +863,476 @@ mod tests {
             resolved: None,
         };
 
-        // First call - may or may not resolve depending on debug symbols
+        // /bin/echo is stripped, so without the symbol nor a known bias it is only a guess
         let _ = resolver.resolve_frame(&mut frame);
-
-        // Check that it's cached
         let cache_key = format!("{}:{}", frame.binary, frame.address);
+        assert!(!resolver.cache.contains_key(&cache_key));
+
+        resolver.set_load_bias("/bin/echo", 0);
+        let _ = resolver.resolve_frame(&mut frame);
         assert!(resolver.cache.contains_key(&cache_key));
+
+        // Binaries that can't be loaded resolve to nothing whatever the bias
+        assert!(resolve(&mut resolver, "/nonexistent/prog", "0x10").is_none());
+        assert!(matches!(
+            resolver.cache.get("/nonexistent/prog:0x10"),
+            Some(None)
+        ));
+    }
+
+    #[test]
+    fn test_guess_until_bias_known() {
+        let exe = std::env::current_exe().unwrap();
+        let exe = exe.to_str().unwrap();
+        let (address, expected) = resolvable_address(exe);
+        let expected = format!("{:?}", Some(&expected));
+
+        // Loaded somewhere else, so the address only resolves once the bias is learned
+        let bias = 0x5555_0000_0000;
+        let runtime = format!("{:#x}", parse_hex(&address).unwrap() + bias);
+        let mut resolver = Addr2LineResolver::new();
+        assert!(resolve(&mut resolver, exe, &runtime).is_none());
+        assert_eq!(resolver.cache_size(), 0);
+
+        let (function, symbol) = symbol_addresses(exe).into_iter().next().unwrap();
+        let mut frame = BacktraceFrame {
+            binary: exe.to_string(),
+            function: Some(function),
+            offset: Some("0x0".to_string()),
+            address: format!("{:#x}", symbol + bias),
+            resolved: None,
+        };
+        resolver.resolve_frame(&mut frame).unwrap();
+        assert_eq!(
+            format!("{:?}", resolve(&mut resolver, exe, &runtime)),
+            expected
+        );
+        assert!(resolver.cache.contains_key(&format!("{}:{}", exe, runtime)));
+
+        // The same through a job, where the frame with a function goes first
+        let mut entry = SyscallEntry::new(1, String::new(), "read".to_string());
+        entry.backtrace = vec![
+            BacktraceFrame {
+                function: None,
+                offset: None,
+                address: runtime.clone(),
+                ..frame.clone()
+            },
+            frame,
+        ];
+        let mut entries = vec![entry];
+        let mut resolver = Addr2LineResolver::new();
+        resolver.resolve_all(&mut entries);
+        assert_eq!(format!("{:?}", entries[0].backtrace[0].resolved), expected);
+    }
+
+    #[test]
+    fn test_ambiguous_symbols() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = |name: &str| dir.path().join(name).to_str().unwrap().to_string();
+        std::fs::write(
+            path("a.c"),
+            "static int helper(void) { return 1; }\nint a(void) { return helper(); }\n",
+        )
+        .unwrap();
+        std::fs::write(
+            path("b.c"),
+            "static int helper(void) { return 2; }\nint a(void);\n\
+             int main(void) { return a() + helper(); }\n",
+        )
+        .unwrap();
+        let status = std::process::Command::new("gcc")
+            .args(["-O0", "-o", &path("prog"), &path("a.c"), &path("b.c")])
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        // Either `helper` could be the one of a frame
+        let symbols = symbol_addresses(&path("prog"));
+        assert!(symbols.contains_key("main"));
+        assert!(symbols.contains_key("a"));
+        assert!(!symbols.contains_key("helper"));
+    }
+
+    #[test]
+    fn test_resolve_progress() {
+        // Binaries that don't exist resolve to nothing, but still count as done
+        let frame = |binary: &str, address: &str| BacktraceFrame {
+            binary: binary.to_string(),
+            function: None,
+            offset: None,
+            address: address.to_string(),
+            resolved: None,
+        };
+        let mut entry = SyscallEntry::new(1, String::new(), "read".to_string());
+        entry.backtrace = vec![
+            frame("/nonexistent/a", "0x10"),
+            frame("/nonexistent/a", "0x20"),
+            frame("/nonexistent/b", "0x10"),
+        ];
+        let mut entries = vec![entry.clone(), entry];
+
+        // Addresses already cached, or seen twice, are only counted once
+        let mut resolver = Addr2LineResolver::new();
+        resolver
+            .resolve_frame(&mut frame("/nonexistent/b", "0x10"))
+            .unwrap();
+        let job = resolver.job_for(&entries);
+        assert_eq!(job.len(), 2);
+
+        let progress = ResolveProgress::default();
+        let resolved = job.run(&progress);
+        assert_eq!(progress.get(), (2, 2));
+        resolver.finish_job(resolved, &mut entries);
+        assert_eq!(resolver.cache_size(), 3);
+
+        let job = resolver.job_for(&entries);
+        assert!(job.is_empty());
+        job.run(&progress);
+        assert_eq!(progress.get(), (0, 0));
+    }
+
+    #[test]
+    fn test_debuginfod_fetch() {
+        // Stands in for the server, serving the test binary (which has line info) as the
+        // debug file of any build-id
+        fn fetch(url: &str, dest: &std::path::Path) -> bool {
+            assert!(url.starts_with("http://debuginfod.test/buildid/"));
+            assert!(url.ends_with("/debuginfo"));
+            std::fs::copy(std::env::current_exe().unwrap(), dest).is_ok()
+        }
+
+        let exe = std::env::current_exe().unwrap();
+        let (address, expected) = resolvable_address(exe.to_str().unwrap());
+
+        // /bin/ls is stripped, so only the fetched debug file can resolve it
+        let cache_dir = tempfile::tempdir().unwrap();
+        let debuginfod = Debuginfod::new(
+            vec!["http://debuginfod.test/".to_string()],
+            cache_dir.path().to_path_buf(),
+            fetch,
+        );
+        let mut resolver = Addr2LineResolver::with_debuginfod(debuginfod);
+        // The address is a link-time one
+        resolver.set_load_bias("/bin/ls", 0);
+
+        let mut frame = BacktraceFrame {
+            binary: "/bin/ls".to_string(),
+            function: None,
+            offset: None,
+            address,
+            resolved: None,
+        };
+        resolver.resolve_frame(&mut frame).unwrap();
+
+        assert_eq!(
+            format!("{:?}", frame.resolved),
+            format!("{:?}", Some(&expected))
+        );
+        let cache_key = format!("{}:{}", frame.binary, frame.address);
+        assert!(resolver.cache[&cache_key].is_some());
+
+        let build_id = DebugInfo::read("/bin/ls").unwrap().build_id.unwrap();
+        assert!(cache_dir.path().join(build_id).join("debuginfo").exists());
+    }
+
+    #[test]
+    fn test_sysroot() {
+        let sysroot = tempfile::tempdir().unwrap();
+        std::fs::create_dir(sysroot.path().join("lib")).unwrap();
+        let binary = sysroot.path().join("lib").join("prog");
+        std::fs::copy(std::env::current_exe().unwrap(), &binary).unwrap();
+        let binary = binary.to_str().unwrap();
+        let (address, expected) = resolvable_address(binary);
+
+        let mut resolver = Addr2LineResolver::new();
+        resolver.set_sysroot(sysroot.path());
+        assert_eq!(resolver.host_path("/lib/prog"), binary);
+        assert_eq!(resolver.debug_dir, sysroot.path().join("usr/lib/debug"));
+        assert_eq!(
+            format!("{:?}", resolve(&mut resolver, "/lib/prog", &address)),
+            format!("{:?}", Some(&expected))
+        );
+
+        // Same through a job, which loads binaries on its own
+        let mut entry = SyscallEntry::new(1, String::new(), "read".to_string());
+        entry.backtrace = vec![BacktraceFrame {
+            binary: "/lib/prog".to_string(),
+            function: None,
+            offset: None,
+            address: address.clone(),
+            resolved: None,
+        }];
+        let mut entries = vec![entry];
+        let mut resolver = Addr2LineResolver::new();
+        resolver.set_sysroot(sysroot.path());
+        resolver.resolve_all(&mut entries);
+        assert_eq!(
+            format!("{:?}", entries[0].backtrace[0].resolved),
+            format!("{:?}", Some(&expected))
+        );
+
+        // Without the sysroot, the path doesn't exist on this machine
+        assert!(resolve(&mut Addr2LineResolver::new(), "/lib/prog", &address).is_none());
+    }
+
+    #[test]
+    fn test_path_prefixes() {
+        let resolver = Addr2LineResolver::from_options(&ResolverOptions {
+            sysroot: Some(PathBuf::from("/sysroot")),
+            path_prefixes: vec![
+                ("/app".to_string(), "/srv/app".to_string()),
+                ("/app/bin/".to_string(), "/home/me/build/".to_string()),
+            ],
+            ..ResolverOptions::default()
+        });
+
+        // The longest matching prefix wins
+        assert_eq!(resolver.host_path("/app/bin/foo"), "/home/me/build/foo");
+        assert_eq!(
+            resolver.host_path("/app/lib/libfoo.so"),
+            "/srv/app/lib/libfoo.so"
+        );
+        assert_eq!(resolver.host_path("/app/bin"), "/home/me/build");
+        // Only whole components match
+        assert_eq!(
+            resolver.host_path("/app/binaries/foo"),
+            "/srv/app/binaries/foo"
+        );
+        assert_eq!(resolver.host_path("/apps/foo"), "/sysroot/apps/foo");
+        // Paths matching no prefix fall back to the sysroot
+        assert_eq!(
+            resolver.host_path("/lib/libc.so.6"),
+            "/sysroot/lib/libc.so.6"
+        );
+
+        // Binaries are loaded from the rewritten path
+        let dir = tempfile::tempdir().unwrap();
+        let binary = dir.path().join("prog");
+        std::fs::copy(std::env::current_exe().unwrap(), &binary).unwrap();
+        let (address, expected) = resolvable_address(binary.to_str().unwrap());
+        let mut resolver = Addr2LineResolver::new();
+        resolver.add_path_prefix("/app/bin", dir.path().to_str().unwrap());
+        assert_eq!(
+            format!("{:?}", resolve(&mut resolver, "/app/bin/prog", &address)),
+            format!("{:?}", Some(&expected))
+        );
+    }
+
+    #[test]
+    fn test_disk_cache_opt_out() {
+        let options = ResolverOptions::default();
+        assert!(
+            Addr2LineResolver::from_options(&options)
+                .cache_dir
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_disk_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let binary = dir.path().join("prog");
+        let binary = binary.to_str().unwrap();
+        std::fs::copy(std::env::current_exe().unwrap(), binary).unwrap();
+        let (address, expected) = resolvable_address(binary);
+
+        let cache_dir = dir.path().join("cache");
+        let mut resolver = Addr2LineResolver::with_cache_dir(&cache_dir);
+        assert!(resolver.linked.is_empty());
+
+        // Guesses aren't saved
+        resolve(&mut resolver, binary, &address);
+        assert!(!resolver.dirty);
+        resolver.set_load_bias(binary, 0);
+        resolve(&mut resolver, binary, "0x0");
+        resolve(&mut resolver, binary, &address);
+        drop(resolver);
+
+        // Answered from the saved cache, without loading the binary, even when loaded elsewhere
+        let mut resolver = Addr2LineResolver::with_cache_dir(&cache_dir);
+        assert_eq!(resolver.linked[binary].len(), 2);
+        resolver.set_load_bias(binary, 0x1000);
+        let runtime = format!("{:#x}", parse_hex(&address).unwrap() + 0x1000);
+        assert_eq!(
+            format!("{:?}", resolve(&mut resolver, binary, &runtime)),
+            format!("{:?}", Some(&expected))
+        );
+        assert!(resolver.binaries.is_empty());
+        drop(resolver);
+
+        // A rebuilt binary invalidates its entries
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(binary)
+            .unwrap()
+            .write_all(b"rebuilt")
+            .unwrap();
+        let resolver = Addr2LineResolver::with_cache_dir(&cache_dir);
+        assert!(resolver.linked.is_empty());
+    }
+
+    #[test]
+    fn test_disk_cache_prune() {
+        let cached = |used: u64, frames: usize| CachedBinary {
+            modified: SystemTime::UNIX_EPOCH,
+            size: 0,
+            used: SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(used),
+            frames: (0..frames as u64).map(|address| (address, None)).collect(),
+        };
+        let mut disk = DiskCache::default();
+        disk.binaries.insert("old".to_string(), cached(1, 10));
+        disk.binaries
+            .insert("new".to_string(), cached(3, DiskCache::MAX_FRAMES - 20));
+        disk.binaries.insert("mid".to_string(), cached(2, 20));
+        disk.prune();
+        let mut kept: Vec<_> = disk.binaries.keys().cloned().collect();
+        kept.sort();
+        assert_eq!(kept, ["mid", "new"]);
+
+        // The last used binary stays, however big
+        let mut disk = DiskCache::default();
+        disk.binaries
+            .insert("big".to_string(), cached(2, DiskCache::MAX_FRAMES + 1));
+        disk.binaries.insert("old".to_string(), cached(1, 1));
+        disk.prune();
+        assert_eq!(disk.binaries.keys().collect::<Vec<_>>(), ["big"]);
+    }
+
+    #[test]
+    fn test_separate_debug_file() {
+        use std::process::Command;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = |name: &str| dir.path().join(name).to_str().unwrap().to_string();
+        let run = |program: &str, args: &[&str]| {
+            let status = Command::new(program).args(args).status().unwrap();
+            assert!(status.success(), "{} {:?} failed", program, args);
+        };
+
+        std::fs::write(
+            path("prog.c"),
+            "int add(int a, int b) { return a + b; }\nint main(void) { return add(1, 2); }\n",
+        )
+        .unwrap();
+        run(
+            "gcc",
+            &[
+                "-g",
+                "-O0",
+                "-Wl,--build-id",
+                "-o",
+                &path("prog"),
+                &path("prog.c"),
+            ],
+        );
+
+        // Find an address that resolves while the binary still has its debug info
+        let (address, expected) = resolvable_address(&path("prog"));
+
+        // Split the debug info out, into the build-id layout of the debug dir
+        let build_id = DebugInfo::read(&path("prog")).unwrap().build_id.unwrap();
+        let debug_dir = dir.path().join("debug");
+        let by_build_id = debug_dir.join(".build-id").join(&build_id[..2]);
+        std::fs::create_dir_all(&by_build_id).unwrap();
+        let debug_file = by_build_id.join(format!("{}.debug", &build_id[2..]));
+        let debug_file = debug_file.to_str().unwrap();
+        run("objcopy", &["--only-keep-debug", &path("prog"), debug_file]);
+        run("objcopy", &["--strip-debug", &path("prog")]);
+        assert!(!DebugInfo::read(&path("prog")).unwrap().has_line_info);
+
+        let resolve = |binary: &str, debug_dir: &Path| {
+            let mut resolver = Addr2LineResolver::new();
+            resolver.debug_dir = debug_dir.to_path_buf();
+            resolve(&mut resolver, binary, &address)
+        };
+        assert_eq!(
+            format!("{:?}", resolve(&path("prog"), &debug_dir)),
+            format!("{:?}", Some(&expected))
+        );
+
+        // Without a build-id match, the debuglink is followed to the `.debug` directory
+        let empty_dir = dir.path().join("empty");
+        std::fs::create_dir(dir.path().join(".debug")).unwrap();
+        std::fs::rename(debug_file, dir.path().join(".debug").join("prog.debug")).unwrap();
+        assert!(resolve(&path("prog"), &empty_dir).is_none());
+
+        run(
+            "objcopy",
+            &[
+                &format!("--add-gnu-debuglink={}", path(".debug/prog.debug")),
+                &path("prog"),
+            ],
+        );
+        assert_eq!(
+            format!("{:?}", resolve(&path("prog"), &empty_dir)),
+            format!("{:?}", Some(&expected))
+        );
+    }
+
+    #[test]
+    fn test_resolve_all_matches_sequential() {
+        // The test binary has debug info, so addresses around one of its lines really resolve
+        let exe = std::env::current_exe().unwrap();
+        let binaries = [exe.to_str().unwrap(), "/bin/ls", "/nonexistent/libfoo.so"];
+        let (base, expected) = resolvable_address(binaries[0]);
+        let base = u64::from_str_radix(base.trim_start_matches("0x"), 16).unwrap();
+
+        let mut trace = String::new();
+        for i in 0..2000 {
+            trace += &format!("1 10:00:00 close({}) = 0\n", i);
+            for depth in 0..4 {
+                let binary = binaries[(i + depth) % binaries.len()];
+                let address = base + ((i * 7 + depth) % 50) as u64 * 0x40;
+                trace += &format!(" > {}() [{:#x}]\n", binary, address);
+            }
+        }
+        let entries = crate::parser::StraceParser::new()
+            .parse_lines(trace.lines().map(str::to_string), false)
+            .unwrap();
+
+        let mut sequential = entries.clone();
+        let mut sequential_resolver = Addr2LineResolver::new();
+        for entry in sequential.iter_mut() {
+            sequential_resolver
+                .resolve_frames(&mut entry.backtrace)
+                .unwrap();
+        }
+
+        let mut parallel = entries;
+        let mut parallel_resolver = Addr2LineResolver::new();
+        parallel_resolver.resolve_all(&mut parallel);
+
+        // Frames of the test binary resolve, and those of a missing one don't
+        let frames = || parallel.iter().flat_map(|entry| &entry.backtrace);
+        let base = format!("{:#x}", base);
+        let first = frames()
+            .find(|frame| frame.binary == binaries[0] && frame.address == base)
+            .unwrap();
+        assert_eq!(
+            format!("{:?}", first.resolved),
+            format!("{:?}", Some(expected))
+        );
+        assert!(
+            frames()
+                .filter(|frame| frame.binary == binaries[2])
+                .all(|frame| frame.resolved.is_none())
+        );
+
+        assert_eq!(
+            parallel_resolver.cache_size(),
+            sequential_resolver.cache_size()
+        );
+        for (key, resolved) in &sequential_resolver.cache {
+            assert_eq!(
+                format!("{:?}", parallel_resolver.cache.get(key)),
+                format!("{:?}", Some(resolved)),
+                "cache mismatch for {}",
+                key
+            );
+        }
+        for (a, b) in parallel.iter().zip(&sequential) {
+            assert_eq!(format!("{:?}", a.backtrace), format!("{:?}", b.backtrace));
+        }
     }
 }
@@ -0,0 +1,46 @@
+use super::{ParseError, ParseResult, StraceParser, SyscallEntry};
+
+/// Iterator over the entries of a trace, from [`StraceParser::parse_streaming`].
+///
+/// Entries come out as [`StraceParser::parse_lines`] gives them without merging resumed calls,
+/// but each one is emitted as soon as nothing can change it anymore, see
+/// [`StraceParser::push_line`]. Only the entries from the oldest one still open are held in
+/// memory, so large traces can be processed without holding them whole, as long as their
+/// processes don't stay idle or blocked for long.
+///
+/// Lines that look like strace output but fail to parse are yielded as errors, and also kept
+/// in the parser's `errors` like [`StraceParser::parse_lines`] does.
+pub struct StreamingEntries<'a, I> {
+    parser: &'a mut StraceParser,
+    lines: I,
+    pending_error: Option<ParseError>, // Found on the line that also completed entries
+}
+
+impl<'a, I: Iterator<Item = String>> StreamingEntries<'a, I> {
+    pub(super) fn new(parser: &'a mut StraceParser, lines: I) -> Self {
+        Self {
+            parser,
+            lines,
+            pending_error: None,
+        }
+    }
+}
+
+impl<I: Iterator<Item = String>> Iterator for StreamingEntries<'_, I> {
+    type Item = ParseResult<SyscallEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(entry) = self.parser.pop_entry() {
+                return Some(Ok(entry));
+            }
+            if let Some(error) = self.pending_error.take() {
+                return Some(Err(error));
+            }
+            let Some(line) = self.lines.next() else {
+                return self.parser.flush().map(Ok);
+            };
+            self.pending_error = self.parser.feed_line(&line);
+        }
+    }
+}
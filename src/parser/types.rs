@@ -1,6 +1,5 @@
-#[cfg(test)]
-use serde::Deserialize;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 /// A single syscall entry from strace output
 #[derive(Debug, Clone, Serialize)]
@@ -9,18 +8,41 @@ pub struct SyscallEntry {
     /// Process ID
     pub pid: u32,
 
-    /// Timestamp (HH:MM:SS format from strace -t)
+    /// Timestamp (HH:MM:SS format from strace -t, or epoch seconds from -ttt)
     pub timestamp: String,
 
+    /// Structured form of `timestamp`, when the line has one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time: Option<Timestamp>,
+
     /// Syscall name
     pub syscall_name: String,
 
     /// Raw argument string
     pub arguments: String,
 
+    /// Values the kernel wrote back, printed by strace after `=>` as in
+    /// `clone3({flags=CLONE_VM, ...} => {parent_tid=[7197]}, 88)`, and cut from `arguments`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_args: Option<String>,
+
     /// Return value (if available)
     pub return_value: Option<String>,
 
+    /// Text strace appends to some return values, parenthesized like `flags O_RDWR` in
+    /// `= 0x2 (flags O_RDWR)` or as a `/* ... */` comment
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub return_annotation: Option<String>,
+
+    /// Path decoded for a returned file descriptor (with `-y`), e.g. `socket:[12345]`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub return_fd_path: Option<String>,
+
+    /// Paths decoded for fd arguments (with `-y`), as (fd, path) pairs in argument order, e.g.
+    /// `(3, "/dev/null")` for `read(3</dev/null>, ...)`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub fd_paths: Vec<(usize, String)>,
+
     /// Error number and message (if syscall failed)
     pub errno: Option<Errno>,
 
@@ -44,11 +66,57 @@ pub struct SyscallEntry {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub resumed_entry_idx: Option<usize>,
 
+    /// Whether this unfinished syscall was never resumed, e.g. because the process was killed
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub never_resumed: bool,
+
+    /// Whether this resumed syscall has no unfinished half, as when the trace was started while
+    /// the call was already blocked
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub orphan_resumed: bool,
+
+    /// Process created by a fork/vfork/clone/clone3, on both halves when it was unfinished
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub child_pid: Option<u32>,
+
     /// Signal information (if this line is a signal)
     pub signal: Option<SignalInfo>,
 
     /// Exit information (if this is an exit line)
     pub exit_info: Option<ExitInfo>,
+
+    /// Text of the input line, followed by the resumed line when the two halves were merged
+    /// (and the `= retval` line of a split call)
+    #[serde(skip)]
+    pub raw_line: String,
+
+    /// Line number of the entry in the input, starting at 1
+    #[serde(skip)]
+    pub line_number: usize,
+}
+
+/// Time of day of an entry, as printed by `strace -t` (seconds) or `-tt` (fractional). Epoch
+/// timestamps from `-ttt` are converted to their UTC time of day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[cfg_attr(test, derive(Deserialize))]
+pub struct Timestamp {
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+
+    /// Fractional part of the second in nanoseconds, if printed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nanos: Option<u32>,
+}
+
+impl Timestamp {
+    /// Seconds since midnight
+    pub fn as_secs(&self) -> f64 {
+        self.hour as f64 * 3600.0
+            + self.minute as f64 * 60.0
+            + self.second as f64
+            + self.nanos.unwrap_or(0) as f64 / 1e9
+    }
 }
 
 /// Error information from a failed syscall
@@ -83,8 +151,7 @@ pub struct BacktraceFrame {
 }
 
 /// A resolved frame (can be inlined)
-#[derive(Debug, Clone, Serialize)]
-#[cfg_attr(test, derive(Deserialize))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResolvedFrame {
     /// Function name (demangled)
     pub function: String,
@@ -111,6 +178,14 @@ pub struct SignalInfo {
 
     /// Raw signal details
     pub details: String,
+
+    /// Fields of the siginfo block, e.g. `si_pid` → `12312`
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub fields: HashMap<String, String>,
+
+    /// Whether this is a ptrace stop (`--- stopped by SIGSTOP ---`) rather than a delivery
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub stopped: bool,
 }
 
 /// Process exit information
@@ -122,17 +197,44 @@ pub struct ExitInfo {
 
     /// Whether it was killed by signal
     pub killed: bool,
+
+    /// Signal that killed the process (e.g., "SIGKILL")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signal: Option<String>,
+}
+
+impl SignalInfo {
+    /// Siginfo fields sorted by name, for a stable display order
+    pub fn sorted_fields(&self) -> Vec<(&str, &str)> {
+        let mut fields: Vec<(&str, &str)> = self
+            .fields
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+            .collect();
+        fields.sort();
+        fields
+    }
 }
 
 impl SyscallEntry {
+    /// The arguments, split
+    pub fn args(&self) -> super::SyscallArgs<'_> {
+        super::SyscallArgs::new(&self.arguments)
+    }
+
     /// Create a new syscall entry with basic information
     pub fn new(pid: u32, timestamp: String, syscall_name: String) -> Self {
         Self {
             pid,
             timestamp,
+            time: None,
             syscall_name,
             arguments: String::new(),
+            output_args: None,
             return_value: None,
+            return_annotation: None,
+            return_fd_path: None,
+            fd_paths: Vec::new(),
             errno: None,
             duration: None,
             backtrace: Vec::new(),
@@ -140,8 +242,13 @@ impl SyscallEntry {
             is_resumed: false,
             unfinished_entry_idx: None,
             resumed_entry_idx: None,
+            never_resumed: false,
+            orphan_resumed: false,
+            child_pid: None,
             signal: None,
             exit_info: None,
+            raw_line: String::new(),
+            line_number: 0,
         }
     }
 }
@@ -158,6 +265,44 @@ pub struct StraceOutput {
 
     /// Parse errors encountered
     pub errors: Vec<ParseErrorInfo>,
+
+    /// Lines that weren't strace output, like the traced program's own output
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub program_output: Vec<ProgramOutputLine>,
+
+    /// Number of entries parsing stopped at, when capped with `--max-entries`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub truncated_at: Option<usize>,
+
+    /// Processes with their parents and children, when asked for with `--process-tree`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub process_tree: Option<Vec<ProcessNode>>,
+
+    /// Notes written on entries in the TUI, by entry index
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub notes: BTreeMap<usize, String>,
+}
+
+/// A process of the trace and where it sits in the fork hierarchy
+#[derive(Debug, Serialize)]
+#[cfg_attr(test, derive(Deserialize))]
+pub struct ProcessNode {
+    pub pid: u32,
+
+    /// Process that forked this one, if it is in the trace
+    pub parent_pid: Option<u32>,
+
+    /// Processes this one forked, in order of first appearance
+    pub children: Vec<u32>,
+
+    /// Index of the first entry of the process, or of the fork that created it
+    pub first_entry_idx: usize,
+
+    /// Index of the last entry of the process, or of the wait that reaped it
+    pub last_entry_idx: usize,
+
+    /// Program of the last successful execve, if any
+    pub program: Option<String>,
 }
 
 /// Summary statistics about the trace
@@ -176,11 +321,123 @@ pub struct SummaryStats {
     /// Number of unfinished syscalls
     pub unfinished: usize,
 
+    /// Number of unfinished syscalls that were never resumed
+    pub never_resumed: usize,
+
     /// Unique PIDs seen
     pub unique_pids: Vec<u32>,
 
     /// Total duration (if available)
     pub total_duration: Option<f64>,
+
+    /// Bytes read and written by the I/O syscalls, per syscall name
+    pub io_bytes: BTreeMap<String, u64>,
+
+    /// Sum of `io_bytes` over the reading syscalls
+    pub bytes_read: u64,
+
+    /// Sum of `io_bytes` over the writing syscalls
+    pub bytes_written: u64,
+}
+
+impl SummaryStats {
+    pub fn from_entries(entries: &[SyscallEntry]) -> Self {
+        let mut unique_pids = HashSet::new();
+        let mut failed = 0;
+        let mut signals = 0;
+        let mut unfinished = 0;
+        let mut never_resumed = 0;
+        let mut total_duration = 0.0;
+        let mut io_bytes = BTreeMap::new();
+        let mut bytes_read = 0;
+        let mut bytes_written = 0;
+
+        for entry in entries {
+            unique_pids.insert(entry.pid);
+
+            if entry.errno.is_some() {
+                failed += 1;
+            }
+
+            if entry.signal.is_some() {
+                signals += 1;
+            }
+
+            if entry.is_unfinished {
+                unfinished += 1;
+            }
+
+            if entry.never_resumed {
+                never_resumed += 1;
+            }
+
+            if let Some(dur) = entry.duration {
+                total_duration += dur;
+            }
+
+            // These return the byte count. Failures return -1, which doesn't parse.
+            if let Some(direction) = io_direction(&entry.syscall_name)
+                && entry.errno.is_none()
+                && let Some(bytes) = entry
+                    .return_value
+                    .as_deref()
+                    .and_then(|value| value.parse::<u64>().ok())
+            {
+                *io_bytes.entry(entry.syscall_name.clone()).or_insert(0) += bytes;
+                match direction {
+                    IoDirection::Read => bytes_read += bytes,
+                    IoDirection::Write => bytes_written += bytes,
+                }
+            }
+        }
+
+        let unique_pids: Vec<u32> = unique_pids.into_iter().collect();
+
+        Self {
+            total_syscalls: entries.len(),
+            failed_syscalls: failed,
+            signals,
+            unfinished,
+            never_resumed,
+            unique_pids,
+            total_duration: if total_duration > 0.0 {
+                Some(total_duration)
+            } else {
+                None
+            },
+            io_bytes,
+            bytes_read,
+            bytes_written,
+        }
+    }
+}
+
+enum IoDirection {
+    Read,
+    Write,
+}
+
+/// Whether a syscall returns how many bytes it read or wrote
+fn io_direction(syscall_name: &str) -> Option<IoDirection> {
+    match syscall_name {
+        "read" | "pread64" | "readv" | "preadv" | "preadv2" | "recv" | "recvfrom" | "recvmsg" => {
+            Some(IoDirection::Read)
+        }
+        "write" | "pwrite64" | "writev" | "pwritev" | "pwritev2" | "send" | "sendto"
+        | "sendmsg" => Some(IoDirection::Write),
+        _ => None,
+    }
+}
+
+/// A line of the input that wasn't strace output
+#[derive(Debug, Serialize)]
+#[cfg_attr(test, derive(Deserialize))]
+pub struct ProgramOutputLine {
+    /// Line number in the input
+    pub line_number: usize,
+
+    /// Text of the line
+    pub text: String,
 }
 
 /// Information about a parse error
@@ -192,4 +449,11 @@ pub struct ParseErrorInfo {
 
     /// Error message
     pub message: String,
+
+    /// Error category, e.g. "invalid_syscall"
+    pub kind: String,
+
+    /// Text of the offending line
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub raw_line: Option<String>,
 }
@@ -1,7 +1,25 @@
-use super::process_graph::ProcessGraph;
-use crate::parser::{Addr2LineResolver, SummaryStats, SyscallEntry};
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
-use std::collections::HashSet;
+use super::config::Config;
+use super::fd_links::FdLinks;
+use super::follow::Follow;
+use super::process_graph::{ProcessGraph, ProcessTreeRow};
+use super::session::Session;
+use super::stats::{
+    DurationBucket, PidSummary, SyscallStats, duration_histogram, pid_deltas, pid_summaries,
+    syscall_stats,
+};
+use super::theme::Theme;
+use crate::parser::{
+    Addr2LineResolver, ParseErrorInfo, ResolveProgress, ResolvedAddresses, SummaryStats,
+    SyscallArgs, SyscallEntry,
+};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::Rect;
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
 
 pub const MAX_TREE_DEPTH: usize = 4;
 
@@ -32,6 +50,40 @@ pub enum DisplayLine {
     ArgumentLine {
         entry_idx: usize,
         arg_idx: usize,
+        wrap_row: usize, // Row of the argument when wrapped, 0 for its first
+        tree_prefix: TreePrefix,
+        is_search_match: bool,
+    },
+    ArgumentField {
+        entry_idx: usize,
+        arg_idx: usize,
+        field: Range<usize>, // Byte range of the struct member or flag within the argument
+        tree_prefix: TreePrefix,
+        is_search_match: bool,
+    },
+    /// The user's note on the entry, shown even while the entry is collapsed
+    Note {
+        entry_idx: usize,
+        tree_prefix: TreePrefix,
+        is_search_match: bool,
+    },
+    /// All the arguments as strace printed them, unsplit, when `raw_arguments` is on
+    RawArguments {
+        entry_idx: usize,
+        wrap_row: usize, // Row of the text, as it is always wrapped
+        tree_prefix: TreePrefix,
+        is_search_match: bool,
+    },
+    /// Stands for the arguments past `max_arguments`, which Enter lists
+    MoreArguments {
+        entry_idx: usize,
+        count: usize,
+        tree_prefix: TreePrefix,
+        is_search_match: bool,
+    },
+    /// What the kernel wrote back, given after `=>` in the strace line
+    OutputArguments {
+        entry_idx: usize,
         tree_prefix: TreePrefix,
         is_search_match: bool,
     },
@@ -55,6 +107,12 @@ pub enum DisplayLine {
         tree_prefix: TreePrefix,
         is_search_match: bool,
     },
+    SignalField {
+        entry_idx: usize,
+        field_idx: usize, // Index into SignalInfo::sorted_fields()
+        tree_prefix: TreePrefix,
+        is_search_match: bool,
+    },
     Exit {
         entry_idx: usize,
         tree_prefix: TreePrefix,
@@ -65,6 +123,12 @@ pub enum DisplayLine {
         tree_prefix: TreePrefix,
         is_search_match: bool,
     },
+    FdReference {
+        entry_idx: usize,
+        ref_idx: usize, // Index into the entry's `FdLinks` references
+        tree_prefix: TreePrefix,
+        is_search_match: bool,
+    },
     BacktraceHeader {
         entry_idx: usize,
         tree_prefix: TreePrefix,
@@ -83,23 +147,72 @@ pub enum DisplayLine {
         tree_prefix: TreePrefix,
         is_search_match: bool,
     },
+    /// Heads the entries of a syscall when grouping by syscall, standing for its first one
+    SyscallGroup {
+        entry_idx: usize,
+        count: usize,
+        is_search_match: bool,
+    },
+    /// Stands for all the shown entries of a collapsed process, where its first one would be
+    CollapsedProcess {
+        entry_idx: usize,
+        pid: u32,
+        count: usize,
+        is_search_match: bool,
+    },
 }
 
 impl DisplayLine {
-    fn entry_idx(&self) -> usize {
+    /// Tree drawing before the line, which all but syscall headers have
+    fn tree_prefix(&self) -> Option<&TreePrefix> {
+        match self {
+            DisplayLine::SyscallHeader { .. }
+            | DisplayLine::SyscallGroup { .. }
+            | DisplayLine::CollapsedProcess { .. } => None,
+            DisplayLine::ArgumentsHeader { tree_prefix, .. }
+            | DisplayLine::ArgumentLine { tree_prefix, .. }
+            | DisplayLine::ArgumentField { tree_prefix, .. }
+            | DisplayLine::MoreArguments { tree_prefix, .. }
+            | DisplayLine::RawArguments { tree_prefix, .. }
+            | DisplayLine::Note { tree_prefix, .. }
+            | DisplayLine::OutputArguments { tree_prefix, .. }
+            | DisplayLine::ReturnValue { tree_prefix, .. }
+            | DisplayLine::Error { tree_prefix, .. }
+            | DisplayLine::Duration { tree_prefix, .. }
+            | DisplayLine::Signal { tree_prefix, .. }
+            | DisplayLine::SignalField { tree_prefix, .. }
+            | DisplayLine::Exit { tree_prefix, .. }
+            | DisplayLine::EntryReference { tree_prefix, .. }
+            | DisplayLine::FdReference { tree_prefix, .. }
+            | DisplayLine::BacktraceHeader { tree_prefix, .. }
+            | DisplayLine::BacktraceFrame { tree_prefix, .. }
+            | DisplayLine::BacktraceResolved { tree_prefix, .. } => Some(tree_prefix),
+        }
+    }
+
+    pub fn entry_idx(&self) -> usize {
         match self {
             DisplayLine::SyscallHeader { entry_idx, .. } => *entry_idx,
             DisplayLine::ArgumentsHeader { entry_idx, .. } => *entry_idx,
             DisplayLine::ArgumentLine { entry_idx, .. } => *entry_idx,
+            DisplayLine::ArgumentField { entry_idx, .. } => *entry_idx,
+            DisplayLine::MoreArguments { entry_idx, .. } => *entry_idx,
+            DisplayLine::RawArguments { entry_idx, .. } => *entry_idx,
+            DisplayLine::Note { entry_idx, .. } => *entry_idx,
+            DisplayLine::OutputArguments { entry_idx, .. } => *entry_idx,
             DisplayLine::ReturnValue { entry_idx, .. } => *entry_idx,
             DisplayLine::Error { entry_idx, .. } => *entry_idx,
             DisplayLine::Duration { entry_idx, .. } => *entry_idx,
             DisplayLine::Signal { entry_idx, .. } => *entry_idx,
+            DisplayLine::SignalField { entry_idx, .. } => *entry_idx,
             DisplayLine::Exit { entry_idx, .. } => *entry_idx,
             DisplayLine::EntryReference { entry_idx, .. } => *entry_idx,
+            DisplayLine::FdReference { entry_idx, .. } => *entry_idx,
             DisplayLine::BacktraceHeader { entry_idx, .. } => *entry_idx,
             DisplayLine::BacktraceFrame { entry_idx, .. } => *entry_idx,
             DisplayLine::BacktraceResolved { entry_idx, .. } => *entry_idx,
+            DisplayLine::SyscallGroup { entry_idx, .. } => *entry_idx,
+            DisplayLine::CollapsedProcess { entry_idx, .. } => *entry_idx,
         }
     }
 }
@@ -110,6 +223,69 @@ pub struct FilterModalState {
     pub scroll_offset: usize,
 }
 
+pub struct ErrnoFilterState {
+    pub codes: Vec<(String, usize)>, // (errno code, count)
+    pub selected_index: usize,
+    pub scroll_offset: usize,
+}
+
+pub struct StatsModalState {
+    pub rows: Vec<SyscallStats>, // Sorted by total time, descending
+    pub selected_index: usize,
+    pub scroll_offset: usize,
+}
+
+pub struct HistogramState {
+    pub buckets: Vec<DurationBucket>,
+    pub syscall: Option<String>, // Syscall under the cursor when opened, if any
+    pub filtered: bool,          // Only count entries of `syscall`
+    pub selected_index: usize,
+    pub scroll_offset: usize,
+}
+
+pub struct PidPickerState {
+    pub rows: Vec<PidSummary>, // In order of first appearance
+    pub selected_index: usize,
+    pub scroll_offset: usize,
+}
+
+pub struct ProcessTreeState {
+    pub rows: Vec<ProcessTreeRow>,
+    pub selected_index: usize,
+    pub scroll_offset: usize,
+}
+
+pub struct ProgramOutputState {
+    pub selected_index: usize,
+    pub scroll_offset: usize,
+}
+
+/// Order of the entries in the main list
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortMode {
+    Trace,        // As they appear in the trace
+    DurationDesc, // Slowest first
+    DurationAsc,  // Fastest first
+}
+
+impl SortMode {
+    fn next(self) -> Self {
+        match self {
+            SortMode::Trace => SortMode::DurationDesc,
+            SortMode::DurationDesc => SortMode::DurationAsc,
+            SortMode::DurationAsc => SortMode::Trace,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SortMode::Trace => "trace order",
+            SortMode::DurationDesc => "duration ↓",
+            SortMode::DurationAsc => "duration ↑",
+        }
+    }
+}
+
 pub struct SearchState {
     pub active: bool,
     pub query: String,
@@ -117,6 +293,52 @@ pub struct SearchState {
     pub current_match_idx: usize, // Index into matches vec
     pub original_position: usize, // Position before search (for Esc)
     pub original_scroll: usize,   // Scroll offset before search
+    pub matched_entries: usize,   // Number of distinct entries among matches
+    /// Longest query typed so far that still had matches, with its match count
+    pub last_hit: Option<(String, usize)>,
+    pub scope: SearchScope,
+}
+
+/// Which lines a search looks at
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchScope {
+    All,
+    Arguments, // Syscall headers and their argument lines
+    Backtrace, // Backtrace frames, raw or resolved
+}
+
+impl SearchScope {
+    fn next(self) -> Self {
+        match self {
+            SearchScope::All => SearchScope::Arguments,
+            SearchScope::Arguments => SearchScope::Backtrace,
+            SearchScope::Backtrace => SearchScope::All,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SearchScope::All => "all",
+            SearchScope::Arguments => "arguments",
+            SearchScope::Backtrace => "backtraces",
+        }
+    }
+
+    fn includes(self, line: &DisplayLine) -> bool {
+        match self {
+            SearchScope::All => true,
+            SearchScope::Arguments => matches!(
+                line,
+                DisplayLine::SyscallHeader { .. }
+                    | DisplayLine::ArgumentLine { .. }
+                    | DisplayLine::ArgumentField { .. }
+            ),
+            SearchScope::Backtrace => matches!(
+                line,
+                DisplayLine::BacktraceFrame { .. } | DisplayLine::BacktraceResolved { .. }
+            ),
+        }
+    }
 }
 
 impl SearchState {
@@ -128,6 +350,9 @@ impl SearchState {
             current_match_idx: 0,
             original_position: 0,
             original_scroll: 0,
+            matched_entries: 0,
+            last_hit: None,
+            scope: SearchScope::All,
         }
     }
 }
@@ -138,7 +363,13 @@ pub struct App {
     pub resolver: Addr2LineResolver,
     pub summary: SummaryStats,
     pub file_path: Option<String>,
+    pub file_index: usize, // Position of this trace among the loaded files
+    pub file_count: usize,
+    pub truncated_at: Option<usize>, // Parsing stopped after this many entries (--max-entries)
     pub process_graph: ProcessGraph,
+    pub fd_links: FdLinks,
+    pub config: Config,
+    pub theme: Theme,
 
     // UI State
     pub display_lines: Vec<DisplayLine>,
@@ -146,16 +377,60 @@ pub struct App {
     pub scroll_offset: usize,
     pub expanded_items: HashSet<usize>,
     pub expanded_arguments: HashSet<usize>,
+    pub all_arguments: HashSet<usize>, // Entries listing their arguments past the cap
     pub expanded_backtraces: HashSet<usize>,
+    pub bookmarks: HashSet<usize>, // Entries marked to come back to, by index
+    pub notes: HashMap<usize, String>, // The user's notes on entries, by index
+    pub collapsed_pids: HashSet<u32>, // Processes shown as a single line
     pub last_visible_height: usize, // Track for page scrolling
+    pub list_area: Rect,           // Where the list was last drawn, to map mouse clicks
     pub last_collapsed_position: Option<usize>, // Remember position before collapse for right arrow
     pub last_collapsed_scroll: Option<usize>, // Remember scroll_offset before collapse
+    pub show_graph: bool,          // Draw the process graph (when there is more than one process)
+    pub hide_backtraces: bool,     // Omit backtraces entirely, which also never resolves them
+    pub has_backtraces: bool,      // Whether any entry has a backtrace, to skip them otherwise
+    pub strip_path_prefix: bool,   // Show backtrace paths relative to common_path_prefix
+    pub common_path_prefix: String, // Longest directory prefix shared by all backtrace paths
+    pub sort_mode: SortMode,
+    pub group_by_syscall: bool, // Cluster the entries under a header per syscall name
+    pub expanded_groups: HashSet<String>, // Syscall groups listing their entries
+    pub wrap_arguments: bool,   // Wrap long arguments over several rows instead of truncating
+    pub raw_arguments: bool,    // Show the arguments unsplit, in case splitting them went wrong
+    pub show_entry_numbers: bool, // Number the entries, as the "entry #N" references do
+    pub show_deltas: bool, // Show the time since the previous syscall of the PID, not the timestamp
+    pub show_timestamps: bool, // Time (or delta) at the right of the headers
+    pub show_pid_tags: bool, // `[pid]` at the right of the headers
+    pub deltas: Vec<Option<f64>>, // Per entry, see `pid_deltas`
 
     // Filter state
     pub hidden_syscalls: HashSet<String>,
     pub show_hidden: bool,
+    pub min_duration: Option<f64>, // Hide entries faster than this many seconds
+    pub keep_untimed: bool,        // Keep entries without a duration when min_duration is set
+    pub duration_prompt: Option<String>, // Threshold being typed, while the prompt is open
+    pub jump_prompt: Option<String>, // Entry number being typed, while the prompt is open
+    pub note_prompt: Option<(usize, String)>, // Entry index and the note being typed for it
     pub show_filter_modal: bool,
     pub filter_modal_state: FilterModalState,
+    pub errno_filter: HashSet<String>, // When not empty, only failures with these codes show
+    pub show_errno_filter: bool,
+    pub errno_filter_state: ErrnoFilterState,
+    pub show_stats_modal: bool,
+    pub stats_modal_state: StatsModalState,
+    pub show_histogram: bool,
+    pub histogram_state: HistogramState,
+    pub show_pid_picker: bool,
+    pub pid_picker_state: PidPickerState,
+    pub show_process_tree: bool, // Side panel, which takes the keys while open
+    pub process_tree_state: ProcessTreeState,
+    pub program_output: Vec<(usize, String)>, // Non-strace lines of the input, with line numbers
+    pub show_program_output: bool,
+    pub program_output_state: ProgramOutputState,
+    pub show_raw_line: bool,     // Input text of the entry under the cursor
+    pub show_syscall_help: bool, // Description of the syscall under the cursor
+    pub parse_errors: Vec<ParseErrorInfo>, // Lines of the input that failed to parse
+    pub show_parse_errors: bool,
+    pub parse_errors_state: ProgramOutputState,
 
     // Search state
     pub search_state: SearchState,
@@ -163,8 +438,28 @@ pub struct App {
 
     // Flags
     pub should_quit: bool,
+    quit_warned: bool, // Quitting was held back once, as notes would be lost
     pub show_help: bool,
+    pub show_follow_fork_hint: bool, // Suggest `strace -f` until the first key press
     pub pending_editor_open: Option<(String, u32, Option<u32>)>, // (file, line, column)
+    pub pending_url_open: Option<String>,
+    pub pending_file_switch: Option<isize>, // Offset to the next file to show (+1/-1)
+    pub resolving: Option<BackgroundResolve>, // Backtraces being resolved on another thread
+    pub pending_count: Option<usize>,       // Digits typed before a motion, as in `10j` or `42G`
+    pending_g: bool,                        // First `g` of `gg`
+    pub status_message: Option<String>,     // Transient message shown in the footer until next key
+    clipboard: Option<arboard::Clipboard>, // Opened on first copy and kept alive, as X11 serves it from this process
+    pub follow: Option<Follow>,            // Trace still being written, polled for new lines
+    follow_refreshed: Option<(Instant, Duration)>, // When the followed entries were last refreshed, and how long it took
+    follow_stale: bool, // Followed entries were added since the last refresh
+}
+
+/// Backtraces being resolved on another thread, started with `R`
+pub struct BackgroundResolve {
+    pub progress: Arc<ResolveProgress>,
+    handle: JoinHandle<ResolvedAddresses>,
+    entry_idx: Option<usize>, // The only entry resolved, or None for all of them
+    report: bool,             // Tell how many frames resolved once done
 }
 
 impl App {
@@ -172,57 +467,291 @@ impl App {
         entries: Vec<SyscallEntry>,
         summary: SummaryStats,
         file_path: Option<String>,
+        config: Config,
     ) -> Self {
-        let process_graph = ProcessGraph::build(&entries);
-
-        // Build syscall list for filter modal
-        let mut syscall_counts: std::collections::HashMap<String, usize> =
-            std::collections::HashMap::new();
-        for entry in &entries {
-            if !entry.syscall_name.is_empty() {
-                *syscall_counts
-                    .entry(entry.syscall_name.clone())
-                    .or_insert(0) += 1;
-            }
-        }
-        let mut syscall_list: Vec<(String, usize)> = syscall_counts.into_iter().collect();
-        syscall_list.sort_by(|a, b| a.0.cmp(&b.0)); // Sort by name
+        let show_graph = config.show_graph;
 
         let mut app = Self {
             entries,
             resolver: Addr2LineResolver::new(),
             summary,
             file_path,
-            process_graph,
+            file_index: 0,
+            file_count: 1,
+            truncated_at: None,
+            process_graph: ProcessGraph::default(),
+            fd_links: FdLinks::default(),
+            theme: Theme::new(config.theme.unwrap_or_default()),
+            config,
             display_lines: Vec::new(),
             selected_line: 0,
             scroll_offset: 0,
             expanded_items: HashSet::new(),
             expanded_arguments: HashSet::new(),
+            all_arguments: HashSet::new(),
             expanded_backtraces: HashSet::new(),
+            bookmarks: HashSet::new(),
+            notes: HashMap::new(),
+            collapsed_pids: HashSet::new(),
             last_visible_height: 20, // Default, will be updated on first draw
+            list_area: Rect::default(),
             last_collapsed_position: None,
             last_collapsed_scroll: None,
+            show_graph,
+            hide_backtraces: false,
+            has_backtraces: false,
+            strip_path_prefix: false,
+            common_path_prefix: String::new(),
             hidden_syscalls: HashSet::new(),
             show_hidden: false,
+            min_duration: None,
+            keep_untimed: false,
+            duration_prompt: None,
+            jump_prompt: None,
+            note_prompt: None,
+            sort_mode: SortMode::Trace,
+            group_by_syscall: false,
+            expanded_groups: HashSet::new(),
+            wrap_arguments: false,
+            raw_arguments: false,
+            show_entry_numbers: false,
+            show_deltas: false,
+            show_timestamps: true,
+            show_pid_tags: true,
+            deltas: Vec::new(),
             show_filter_modal: false,
             filter_modal_state: FilterModalState {
-                syscall_list,
+                syscall_list: Vec::new(),
+                selected_index: 0,
+                scroll_offset: 0,
+            },
+            errno_filter: HashSet::new(),
+            show_errno_filter: false,
+            errno_filter_state: ErrnoFilterState {
+                codes: Vec::new(),
+                selected_index: 0,
+                scroll_offset: 0,
+            },
+            show_stats_modal: false,
+            stats_modal_state: StatsModalState {
+                rows: Vec::new(),
+                selected_index: 0,
+                scroll_offset: 0,
+            },
+            show_histogram: false,
+            histogram_state: HistogramState {
+                buckets: Vec::new(),
+                syscall: None,
+                filtered: false,
+                selected_index: 0,
+                scroll_offset: 0,
+            },
+            show_pid_picker: false,
+            pid_picker_state: PidPickerState {
+                rows: Vec::new(),
+                selected_index: 0,
+                scroll_offset: 0,
+            },
+            show_process_tree: false,
+            process_tree_state: ProcessTreeState {
+                rows: Vec::new(),
+                selected_index: 0,
+                scroll_offset: 0,
+            },
+            program_output: Vec::new(),
+            show_program_output: false,
+            program_output_state: ProgramOutputState {
+                selected_index: 0,
+                scroll_offset: 0,
+            },
+            show_raw_line: false,
+            show_syscall_help: false,
+            parse_errors: Vec::new(),
+            show_parse_errors: false,
+            parse_errors_state: ProgramOutputState {
                 selected_index: 0,
                 scroll_offset: 0,
             },
             search_state: SearchState::new(),
             modal_search_state: SearchState::new(),
             should_quit: false,
+            quit_warned: false,
             show_help: false,
+            show_follow_fork_hint: false,
             pending_editor_open: None,
+            pending_url_open: None,
+            pending_file_switch: None,
+            resolving: None,
+            pending_count: None,
+            pending_g: false,
+            status_message: None,
+            clipboard: None,
+            follow: None,
+            follow_refreshed: None,
+            follow_stale: false,
         };
-        app.rebuild_display_lines();
+        app.refresh_entries();
+        app.show_follow_fork_hint = app.process_graph.untraced_children > 0;
         app
     }
 
-    pub fn update_visible_height(&mut self, height: usize) {
-        self.last_visible_height = height;
+    /// Recompute everything derived from the entries, and the lines showing them
+    fn refresh_entries(&mut self) {
+        self.process_graph = ProcessGraph::build(&self.entries);
+        self.process_tree_state.rows = self.process_graph.tree();
+        self.fd_links = FdLinks::build(&self.entries);
+        self.has_backtraces = self.entries.iter().any(|e| !e.backtrace.is_empty());
+
+        // Build syscall list for filter modal
+        let mut syscall_counts: std::collections::HashMap<String, usize> =
+            std::collections::HashMap::new();
+        for entry in &self.entries {
+            if !entry.syscall_name.is_empty() {
+                *syscall_counts
+                    .entry(entry.syscall_name.clone())
+                    .or_insert(0) += 1;
+            }
+        }
+        let mut syscall_list: Vec<(String, usize)> = syscall_counts.into_iter().collect();
+        syscall_list.sort_by(|a, b| a.0.cmp(&b.0)); // Sort by name
+        self.filter_modal_state.syscall_list = syscall_list;
+
+        // Same for the errno filter
+        let mut errno_counts: std::collections::HashMap<String, usize> =
+            std::collections::HashMap::new();
+        for errno in self.entries.iter().filter_map(|entry| entry.errno.as_ref()) {
+            *errno_counts.entry(errno.code.clone()).or_insert(0) += 1;
+        }
+        let mut errno_codes: Vec<(String, usize)> = errno_counts.into_iter().collect();
+        errno_codes.sort_by(|a, b| a.0.cmp(&b.0));
+        self.errno_filter_state.codes = errno_codes;
+
+        self.stats_modal_state.rows = syscall_stats(&self.entries);
+        self.pid_picker_state.rows = pid_summaries(&self.entries);
+        self.deltas = pid_deltas(&self.entries);
+
+        self.refresh_common_path_prefix();
+        self.rebuild_display_lines();
+    }
+
+    /// Add what was written to the followed trace since the last call. When the cursor was on
+    /// the last line it moves to the new last line, so the newest entries stay in view.
+    ///
+    /// Refreshing goes over all the entries, so on a big trace it is held back until it takes at
+    /// most a tenth of the time, and the entries parsed meanwhile show up together.
+    pub fn poll_follow(&mut self) {
+        let Some(follow) = &mut self.follow else {
+            return;
+        };
+
+        let lines = match follow.source.read_lines() {
+            Ok(lines) => lines,
+            Err(e) => {
+                self.status_message = Some(format!("Stopped following the trace: {}", e));
+                self.follow = None;
+                return;
+            }
+        };
+        let closed = follow.source.is_closed();
+        if !lines.is_empty() || closed {
+            follow
+                .parser
+                .parse_more(lines, follow.merge_resumed, &mut self.entries);
+            if closed {
+                follow.parser.finish(&mut self.entries);
+            }
+            self.program_output
+                .append(&mut follow.parser.program_output);
+            self.parse_errors.append(&mut follow.parser.take_errors());
+            if follow.parser.truncated {
+                self.truncated_at = follow.parser.max_entries;
+            }
+            self.follow_stale = true;
+        }
+
+        let throttled = self
+            .follow_refreshed
+            .is_some_and(|(at, took)| at.elapsed() < took * 9);
+        if !self.follow_stale || (throttled && !closed) {
+            return;
+        }
+        let started = Instant::now();
+
+        let at_bottom = self.selected_line + 1 >= self.display_lines.len();
+        self.summary = SummaryStats::from_entries(&self.entries);
+        self.refresh_entries();
+        if at_bottom && !self.display_lines.is_empty() {
+            self.selected_line = self.display_lines.len() - 1;
+            self.ensure_visible();
+        }
+        self.follow_refreshed = Some((Instant::now(), started.elapsed()));
+        self.follow_stale = false;
+
+        if closed {
+            self.follow = None;
+            self.status_message = Some("End of the trace".to_string());
+        }
+    }
+
+    /// Width of the entry number column, when shown: the digits of the last entry and a space
+    pub fn entry_number_width(&self) -> usize {
+        if self.show_entry_numbers {
+            self.entries.len().to_string().len() + 1
+        } else {
+            0
+        }
+    }
+
+    /// Width of the bookmark markers, only drawn once something is bookmarked
+    pub fn bookmark_width(&self) -> usize {
+        if self.bookmarks.is_empty() { 0 } else { 2 }
+    }
+
+    /// Width left for the lines of the list, besides the entry numbers and bookmarks
+    pub fn list_content_width(&self) -> usize {
+        (self.list_area.width as usize)
+            .saturating_sub(self.entry_number_width() + self.bookmark_width())
+    }
+
+    /// Whether the argument lines depend on the width of the list
+    fn wraps_arguments(&self) -> bool {
+        self.wrap_arguments || self.raw_arguments
+    }
+
+    pub fn update_list_area(&mut self, area: Rect) {
+        let rewrap = self.wraps_arguments() && area.width != self.list_area.width;
+        self.last_visible_height = area.height as usize;
+        self.list_area = area;
+        if rewrap {
+            self.rebuild_display_lines();
+        }
+    }
+
+    /// Recompute the common prefix of all backtrace binaries and resolved source files. Must be
+    /// called again after resolving frames, as new source paths may shorten the prefix.
+    fn refresh_common_path_prefix(&mut self) {
+        let paths = self.entries.iter().flat_map(|entry| {
+            entry.backtrace.iter().flat_map(|frame| {
+                std::iter::once(frame.binary.as_str()).chain(
+                    frame
+                        .resolved
+                        .iter()
+                        .flatten()
+                        .map(|resolved| resolved.file.as_str()),
+                )
+            })
+        });
+        self.common_path_prefix = common_path_prefix(paths);
+    }
+
+    /// Returns the path as it should be displayed, stripping the common prefix if enabled
+    pub fn display_path<'a>(&self, path: &'a str) -> &'a str {
+        if self.strip_path_prefix && !self.common_path_prefix.is_empty() {
+            path.strip_prefix(self.common_path_prefix.as_str())
+                .unwrap_or(path)
+        } else {
+            path
+        }
     }
 
     /// Converts TreePrefix array to display string. Each element renders to fixed-width string
@@ -302,6 +831,45 @@ impl App {
         prefix
     }
 
+    /// Entry indices in display order. Sorting by duration only moves the entries that have
+    /// one, among the positions they hold, so the others stay where they are. Grouping by
+    /// syscall then gathers the entries of each syscall, by name, in that order.
+    fn entry_order(&self) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.entries.len()).collect();
+        if self.sort_mode != SortMode::Trace {
+            self.sort_by_duration(&mut order);
+        }
+        if self.group_by_syscall {
+            order.sort_by(|&a, &b| {
+                self.entries[a]
+                    .syscall_name
+                    .cmp(&self.entries[b].syscall_name)
+            });
+        }
+        order
+    }
+
+    fn sort_by_duration(&self, order: &mut [usize]) {
+        let slots: Vec<usize> = order
+            .iter()
+            .copied()
+            .filter(|&idx| self.entries[idx].duration.is_some())
+            .collect();
+        let mut sorted = slots.clone();
+        sorted.sort_by(|&a, &b| {
+            let (a, b) = (self.entries[a].duration, self.entries[b].duration);
+            let (a, b) = (a.unwrap_or_default(), b.unwrap_or_default());
+            match self.sort_mode {
+                SortMode::DurationAsc => a.total_cmp(&b),
+                _ => b.total_cmp(&a),
+            }
+        });
+
+        for (slot, idx) in slots.into_iter().zip(sorted) {
+            order[slot] = idx;
+        }
+    }
+
     fn rebuild_display_lines(&mut self) {
         // Remember which entry we're looking at before rebuilding
         let current_entry_idx = if self.selected_line < self.display_lines.len() {
@@ -312,8 +880,14 @@ impl App {
         let cursor_screen_pos = self.selected_line.saturating_sub(self.scroll_offset);
 
         self.display_lines.clear();
+        let mut collapsed_lines = HashMap::new(); // Line of each collapsed process
+        let mut group_line: Option<usize> = None; // Line of the header of the current group
+        // Most traces are recorded without -k, so don't look for frames entry by entry
+        let show_backtraces = self.has_backtraces && !self.hide_backtraces;
+
+        for idx in self.entry_order() {
+            let entry = &self.entries[idx];
 
-        for (idx, entry) in self.entries.iter().enumerate() {
             // Check if this syscall should be hidden
             let is_hidden = self.hidden_syscalls.contains(&entry.syscall_name);
 
@@ -322,6 +896,75 @@ impl App {
                 continue;
             }
 
+            // Only keep the selected failures when filtering by errno
+            if !self.errno_filter.is_empty()
+                && entry
+                    .errno
+                    .as_ref()
+                    .is_none_or(|errno| !self.errno_filter.contains(&errno.code))
+            {
+                continue;
+            }
+
+            // Skip fast entries (and those without timing, unless kept) when a threshold is set
+            if let Some(min) = self.min_duration
+                && entry.duration.map_or(!self.keep_untimed, |dur| dur < min)
+            {
+                continue;
+            }
+
+            // Count the entry in its syscall's group, which only lists it when expanded
+            if self.group_by_syscall {
+                match group_line {
+                    Some(line)
+                        if self.entries[self.display_lines[line].entry_idx()].syscall_name
+                            == entry.syscall_name =>
+                    {
+                        if let DisplayLine::SyscallGroup { count, .. } =
+                            &mut self.display_lines[line]
+                        {
+                            *count += 1;
+                        }
+                    }
+                    _ => {
+                        // Collapsed processes get a line in each group
+                        collapsed_lines.clear();
+                        group_line = Some(self.display_lines.len());
+                        self.display_lines.push(DisplayLine::SyscallGroup {
+                            entry_idx: idx,
+                            count: 1,
+                            is_search_match: false,
+                        });
+                    }
+                }
+                if !self.expanded_groups.contains(&entry.syscall_name) {
+                    continue;
+                }
+            }
+
+            // Fold the entries of collapsed processes into one line, counting them
+            if self.collapsed_pids.contains(&entry.pid) {
+                match collapsed_lines.get(&entry.pid) {
+                    Some(&line) => {
+                        if let DisplayLine::CollapsedProcess { count, .. } =
+                            &mut self.display_lines[line]
+                        {
+                            *count += 1;
+                        }
+                    }
+                    None => {
+                        collapsed_lines.insert(entry.pid, self.display_lines.len());
+                        self.display_lines.push(DisplayLine::CollapsedProcess {
+                            entry_idx: idx,
+                            pid: entry.pid,
+                            count: 1,
+                            is_search_match: false,
+                        });
+                    }
+                }
+                continue;
+            }
+
             // Always add the syscall header
             self.display_lines.push(DisplayLine::SyscallHeader {
                 entry_idx: idx,
@@ -329,10 +972,15 @@ impl App {
                 is_search_match: false,
             });
 
+            // Base prefix: empty (leading spaces added during rendering)
+            let base_prefix: TreePrefix = [TreeElement::Null; MAX_TREE_DEPTH];
+            let has_note = self.notes.contains_key(&idx);
+
             // Add expanded details if item is expanded
             if self.expanded_items.contains(&idx) {
                 // Collect all top-level items to determine which is last
                 let has_arguments = !entry.arguments.is_empty();
+                let has_output = entry.output_args.is_some();
                 let has_return = entry.return_value.is_some();
                 let has_error = entry.errno.is_some();
                 let has_duration = entry.duration.is_some();
@@ -340,12 +988,19 @@ impl App {
                 let has_exit = entry.exit_info.is_some();
                 let has_reference =
                     entry.unfinished_entry_idx.is_some() || entry.resumed_entry_idx.is_some();
-                let has_backtrace = !entry.backtrace.is_empty();
+                let fd_references = self.fd_links.get(idx).len();
+                let has_backtrace = show_backtraces && !entry.backtrace.is_empty();
 
                 let mut items = Vec::new();
+                if has_note {
+                    items.push("note");
+                }
                 if has_arguments {
                     items.push("arguments");
                 }
+                if has_output {
+                    items.push("output");
+                }
                 if has_return {
                     items.push("return");
                 }
@@ -364,16 +1019,25 @@ impl App {
                 if has_reference {
                     items.push("reference");
                 }
+                items.extend(std::iter::repeat_n("fd_reference", fd_references));
                 if has_backtrace {
                     items.push("backtrace");
                 }
 
                 let total_items = items.len();
-
-                // Base prefix: empty (leading spaces added during rendering)
-                let base_prefix: TreePrefix = [TreeElement::Null; MAX_TREE_DEPTH];
                 let mut item_idx = 0;
 
+                // Note
+                if has_note {
+                    let is_last = item_idx == total_items - 1;
+                    self.display_lines.push(DisplayLine::Note {
+                        entry_idx: idx,
+                        tree_prefix: Self::build_tree_prefix(&base_prefix, is_last),
+                        is_search_match: false,
+                    });
+                    item_idx += 1;
+                }
+
                 // Arguments
                 if has_arguments {
                     let is_last = item_idx == total_items - 1;
@@ -386,38 +1050,122 @@ impl App {
                     });
 
                     // Add arguments if expanded
-                    if self.expanded_arguments.contains(&idx) {
-                        let args = split_arguments(&entry.arguments);
+                    if self.expanded_arguments.contains(&idx) && self.raw_arguments {
                         let nested_base = Self::build_nested_prefix(&prefix, is_last);
+                        let raw_prefix = Self::build_tree_prefix(&nested_base, true);
+                        let rows = if self.list_area.width > 0 {
+                            wrap_chunks(
+                                &entry.arguments,
+                                argument_width(&raw_prefix, self.list_content_width()),
+                            )
+                            .len()
+                        } else {
+                            1
+                        };
+                        let continuation_prefix = Self::build_nested_prefix(&raw_prefix, true);
+                        for wrap_row in 0..rows {
+                            self.display_lines.push(DisplayLine::RawArguments {
+                                entry_idx: idx,
+                                wrap_row,
+                                tree_prefix: if wrap_row == 0 {
+                                    raw_prefix
+                                } else {
+                                    continuation_prefix
+                                },
+                                is_search_match: false,
+                            });
+                        }
+                    } else if self.expanded_arguments.contains(&idx) {
+                        let args = entry.args();
+                        let nested_base = Self::build_nested_prefix(&prefix, is_last);
+                        let max_arguments = self.config.max_arguments;
+                        let shown = if max_arguments > 0
+                            && args.len() > max_arguments
+                            && !self.all_arguments.contains(&idx)
+                        {
+                            max_arguments
+                        } else {
+                            args.len()
+                        };
 
-                        for (arg_idx, _arg) in args.iter().enumerate() {
+                        for (arg_idx, arg) in args.iter().enumerate().take(shown) {
                             let is_last_arg = arg_idx == args.len() - 1;
                             let arg_prefix = Self::build_tree_prefix(&nested_base, is_last_arg);
 
                             self.display_lines.push(DisplayLine::ArgumentLine {
                                 entry_idx: idx,
                                 arg_idx,
+                                wrap_row: 0,
                                 tree_prefix: arg_prefix,
                                 is_search_match: false,
                             });
+
+                            if self.wrap_arguments && self.list_area.width > 0 {
+                                let rows = wrap_chunks(
+                                    arg,
+                                    argument_width(&arg_prefix, self.list_content_width()),
+                                )
+                                .len();
+                                let continuation_prefix =
+                                    Self::build_nested_prefix(&arg_prefix, is_last_arg);
+                                for wrap_row in 1..rows {
+                                    self.display_lines.push(DisplayLine::ArgumentLine {
+                                        entry_idx: idx,
+                                        arg_idx,
+                                        wrap_row,
+                                        tree_prefix: continuation_prefix,
+                                        is_search_match: false,
+                                    });
+                                }
+                            }
+
+                            push_argument_fields(
+                                &mut self.display_lines,
+                                idx,
+                                arg_idx,
+                                arg,
+                                0..arg.len(),
+                                &arg_prefix,
+                                is_last_arg,
+                            );
+                        }
+
+                        if shown < args.len() {
+                            self.display_lines.push(DisplayLine::MoreArguments {
+                                entry_idx: idx,
+                                count: args.len() - shown,
+                                tree_prefix: Self::build_tree_prefix(&nested_base, true),
+                                is_search_match: false,
+                            });
                         }
                     }
                     item_idx += 1;
                 }
 
-                // Return value
-                if has_return {
+                // Output arguments
+                if has_output {
                     let is_last = item_idx == total_items - 1;
-                    let prefix = Self::build_tree_prefix(&base_prefix, is_last);
-                    self.display_lines.push(DisplayLine::ReturnValue {
+                    self.display_lines.push(DisplayLine::OutputArguments {
                         entry_idx: idx,
-                        tree_prefix: prefix,
+                        tree_prefix: Self::build_tree_prefix(&base_prefix, is_last),
                         is_search_match: false,
                     });
                     item_idx += 1;
                 }
 
-                // Error
+                // Return value
+                if has_return {
+                    let is_last = item_idx == total_items - 1;
+                    let prefix = Self::build_tree_prefix(&base_prefix, is_last);
+                    self.display_lines.push(DisplayLine::ReturnValue {
+                        entry_idx: idx,
+                        tree_prefix: prefix,
+                        is_search_match: false,
+                    });
+                    item_idx += 1;
+                }
+
+                // Error
                 if has_error {
                     let is_last = item_idx == total_items - 1;
                     let prefix = Self::build_tree_prefix(&base_prefix, is_last);
@@ -450,6 +1198,20 @@ impl App {
                         tree_prefix: prefix,
                         is_search_match: false,
                     });
+
+                    // Siginfo fields, under the signal
+                    let field_count = entry.signal.as_ref().map_or(0, |s| s.fields.len());
+                    let nested_base = Self::build_nested_prefix(&prefix, is_last);
+                    for field_idx in 0..field_count {
+                        let field_prefix =
+                            Self::build_tree_prefix(&nested_base, field_idx == field_count - 1);
+                        self.display_lines.push(DisplayLine::SignalField {
+                            entry_idx: idx,
+                            field_idx,
+                            tree_prefix: field_prefix,
+                            is_search_match: false,
+                        });
+                    }
                     item_idx += 1;
                 }
 
@@ -477,6 +1239,19 @@ impl App {
                     item_idx += 1;
                 }
 
+                // Sockets/pipes created by other entries
+                for ref_idx in 0..fd_references {
+                    let is_last = item_idx == total_items - 1;
+                    let prefix = Self::build_tree_prefix(&base_prefix, is_last);
+                    self.display_lines.push(DisplayLine::FdReference {
+                        entry_idx: idx,
+                        ref_idx,
+                        tree_prefix: prefix,
+                        is_search_match: false,
+                    });
+                    item_idx += 1;
+                }
+
                 // Backtrace
                 if has_backtrace {
                     let is_last = item_idx == total_items - 1;
@@ -534,6 +1309,12 @@ impl App {
                         }
                     }
                 }
+            } else if has_note {
+                self.display_lines.push(DisplayLine::Note {
+                    entry_idx: idx,
+                    tree_prefix: Self::build_tree_prefix(&base_prefix, true),
+                    is_search_match: false,
+                });
             }
         }
 
@@ -554,10 +1335,16 @@ impl App {
                 .get(self.selected_line)
                 .is_none_or(|x| x.entry_idx() != entry_idx)
         {
+            // Entries may be out of trace order when sorted or grouped
             self.selected_line = self
                 .display_lines
                 .iter()
-                .position(|line| line.entry_idx() >= entry_idx)
+                .position(|line| line.entry_idx() == entry_idx)
+                .or_else(|| {
+                    self.display_lines
+                        .iter()
+                        .position(|line| line.entry_idx() >= entry_idx)
+                })
                 .unwrap_or(0);
 
             // Restore cursor screen position
@@ -565,20 +1352,144 @@ impl App {
         }
     }
 
+    /// Clicking a line selects it and acts like Enter on it, and the wheel scrolls the list.
+    /// Ignored while a modal or prompt is open.
+    pub fn handle_mouse_event(&mut self, event: MouseEvent) {
+        if self.search_state.active
+            || self.duration_prompt.is_some()
+            || self.jump_prompt.is_some()
+            || self.note_prompt.is_some()
+            || self.show_filter_modal
+            || self.show_stats_modal
+            || self.show_histogram
+            || self.show_pid_picker
+            || self.show_program_output
+            || self.show_errno_filter
+            || self.show_raw_line
+            || self.show_syscall_help
+            || self.show_parse_errors
+            || self.show_help
+        {
+            return;
+        }
+
+        match event.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                let area = self.list_area;
+                if event.column < area.x
+                    || event.column >= area.right()
+                    || event.row < area.y
+                    || event.row >= area.bottom()
+                {
+                    return;
+                }
+
+                let line = self.scroll_offset + (event.row - area.y) as usize;
+                if line < self.display_lines.len() {
+                    self.status_message = None;
+                    self.selected_line = line;
+                    self.toggle_current_line();
+                }
+            }
+            MouseEventKind::ScrollUp => self.scroll_viewport(true, 3),
+            MouseEventKind::ScrollDown => self.scroll_viewport(false, 3),
+            _ => {}
+        }
+    }
+
     pub fn handle_event(&mut self, event: KeyEvent) {
+        self.status_message = None;
+        self.show_follow_fork_hint = false;
+
         // Priority 1: Search mode
         if self.search_state.active {
             self.handle_search_event(event);
             return;
         }
 
+        // Priority 1b: Duration threshold prompt
+        if self.duration_prompt.is_some() {
+            self.handle_duration_prompt_event(event);
+            return;
+        }
+
+        // Priority 1c: Jump to entry prompt
+        if self.jump_prompt.is_some() {
+            self.handle_jump_prompt_event(event);
+            return;
+        }
+
+        // Priority 1d: Note prompt
+        if self.note_prompt.is_some() {
+            self.handle_note_prompt_event(event);
+            return;
+        }
+
         // Priority 2: Filter modal
         if self.show_filter_modal {
             self.handle_filter_modal_event(event);
             return;
         }
 
-        // Priority 3: Help screen
+        // Priority 3: Stats modal
+        if self.show_stats_modal {
+            self.handle_stats_modal_event(event);
+            return;
+        }
+
+        // Priority 3b: Duration histogram
+        if self.show_histogram {
+            self.handle_histogram_event(event);
+            return;
+        }
+
+        // Priority 4: PID picker
+        if self.show_pid_picker {
+            self.handle_pid_picker_event(event);
+            return;
+        }
+
+        // Priority 5: Program output pane
+        if self.show_program_output {
+            self.handle_program_output_event(event);
+            return;
+        }
+
+        // Priority 6: Errno filter
+        if self.show_errno_filter {
+            self.handle_errno_filter_event(event);
+            return;
+        }
+
+        // Priority 6b: Parse errors
+        if self.show_parse_errors {
+            self.handle_parse_errors_event(event);
+            return;
+        }
+
+        // Priority 6c: Raw line popup
+        if self.show_raw_line {
+            if matches!(
+                event.code,
+                KeyCode::Char('v') | KeyCode::Char('q') | KeyCode::Esc
+            ) {
+                self.show_raw_line = false;
+            }
+            return;
+        }
+
+        // Priority 6d: Syscall description popup
+        if self.show_syscall_help {
+            if matches!(
+                event.code,
+                KeyCode::Char('K') | KeyCode::Char('q') | KeyCode::Esc
+            ) {
+                self.show_syscall_help = false;
+            }
+            return;
+        }
+
+        // Priority 7: Help screen
         if self.show_help {
             if matches!(event.code, KeyCode::Char('?') | KeyCode::Esc) {
                 self.show_help = false;
@@ -586,15 +1497,35 @@ impl App {
             return;
         }
 
+        // Priority 8: Process tree panel
+        if self.show_process_tree {
+            self.handle_process_tree_event(event);
+            return;
+        }
+
+        let Some(event) = self.config.keys.translate(event) else {
+            self.pending_count = None;
+            self.pending_g = false;
+            return;
+        };
         let ctrl = event.modifiers.contains(KeyModifiers::CONTROL);
+
+        // Vim-style count, consumed by the next key. A leading 0 isn't a count.
+        let count = self.pending_count.take();
+        let pending_g = std::mem::take(&mut self.pending_g);
+        if let KeyCode::Char(c @ '0'..='9') = event.code
+            && !ctrl
+            && (count.is_some() || c != '0')
+        {
+            let digit = c as usize - '0' as usize;
+            self.pending_count = Some(count.unwrap_or(0).saturating_mul(10).saturating_add(digit));
+            return;
+        }
+
         match event.code {
             // Quit
-            KeyCode::Char('q') | KeyCode::Char('Q') => {
-                self.should_quit = true;
-            }
-            KeyCode::Char('c') if ctrl => {
-                self.should_quit = true;
-            }
+            KeyCode::Char('q') | KeyCode::Char('Q') => self.quit(),
+            KeyCode::Char('c') if ctrl => self.quit(),
 
             // Help
             KeyCode::Char('?') => {
@@ -611,6 +1542,135 @@ impl App {
             KeyCode::Char('.') => {
                 self.toggle_show_hidden();
             }
+            KeyCode::Char('E') => {
+                if self.errno_filter_state.codes.is_empty() {
+                    self.status_message = Some("No failed syscalls in this trace".to_string());
+                } else {
+                    self.show_errno_filter = true;
+                }
+            }
+            KeyCode::Char('%') => {
+                self.show_stats_modal = true;
+            }
+            KeyCode::Char('D') => {
+                self.open_histogram();
+            }
+            KeyCode::Char('p') => {
+                self.show_pid_picker = true;
+            }
+            KeyCode::Char('t') => {
+                self.show_process_tree = true;
+            }
+            KeyCode::Char('O') => {
+                if self.program_output.is_empty() {
+                    self.status_message = Some("No program output in this trace".to_string());
+                } else {
+                    self.show_program_output = true;
+                }
+            }
+            KeyCode::Char('!') => {
+                if self.parse_errors.is_empty() {
+                    self.status_message = Some("No parse errors in this trace".to_string());
+                } else {
+                    self.show_parse_errors = true;
+                }
+            }
+            KeyCode::Char('+') => {
+                self.step_min_duration(true);
+            }
+            KeyCode::Char('-') => {
+                self.step_min_duration(false);
+            }
+            KeyCode::Char('>') => {
+                self.duration_prompt = Some(String::new());
+            }
+
+            // Display options
+            KeyCode::Char('f') => {
+                self.strip_path_prefix = !self.strip_path_prefix;
+            }
+            KeyCode::Char('|') => {
+                self.show_graph = !self.show_graph;
+            }
+            KeyCode::Char('F') => {
+                self.hide_backtraces = !self.hide_backtraces;
+                self.rebuild_display_lines();
+            }
+            KeyCode::Char('o') => {
+                self.sort_mode = self.sort_mode.next();
+                self.rebuild_display_lines();
+                self.ensure_visible();
+                self.status_message = Some(format!("Sorted by {}", self.sort_mode.label()));
+            }
+            KeyCode::Char('Z') => self.toggle_group_by_syscall(),
+            KeyCode::Char('i') => {
+                self.show_deltas = !self.show_deltas;
+            }
+            KeyCode::Char('T') => {
+                self.show_timestamps = !self.show_timestamps;
+            }
+            KeyCode::Char('I') => {
+                self.show_pid_tags = !self.show_pid_tags;
+            }
+            KeyCode::Char('W') => {
+                self.wrap_arguments = !self.wrap_arguments;
+                self.rebuild_display_lines();
+                self.ensure_visible();
+            }
+            KeyCode::Char('V') => {
+                self.raw_arguments = !self.raw_arguments;
+                self.rebuild_display_lines();
+                self.ensure_visible();
+            }
+            KeyCode::Char('#') => {
+                self.show_entry_numbers = !self.show_entry_numbers;
+                if self.wraps_arguments() {
+                    self.rebuild_display_lines();
+                    self.ensure_visible();
+                }
+            }
+            KeyCode::Char(':') => {
+                self.jump_prompt = Some(String::new());
+            }
+            KeyCode::Char('a') => {
+                if let Some(line) = self.display_lines.get(self.selected_line) {
+                    let entry_idx = line.entry_idx();
+                    let note = self.notes.get(&entry_idx).cloned();
+                    self.note_prompt = Some((entry_idx, note.unwrap_or_default()));
+                }
+            }
+            KeyCode::Char('w') => {
+                self.open_current_source_link();
+            }
+            KeyCode::Char('r') => {
+                self.resolve_current_backtrace();
+            }
+            KeyCode::Char('R') => {
+                self.start_resolve_all();
+            }
+            KeyCode::Char('y') => match self.display_lines.get(self.selected_line) {
+                Some(DisplayLine::ArgumentLine { .. } | DisplayLine::ArgumentField { .. }) => {
+                    self.copy_current_argument(false);
+                }
+                _ => self.copy_current_entry(),
+            },
+            KeyCode::Char('Y') => {
+                self.copy_current_argument(true);
+            }
+            KeyCode::Char('v') if !self.display_lines.is_empty() => {
+                self.show_raw_line = true;
+            }
+            KeyCode::Char('K') if !self.display_lines.is_empty() => {
+                self.show_syscall_help = true;
+            }
+
+            // File switching
+            KeyCode::Char('}') if self.file_count > 1 => {
+                self.pending_file_switch = Some(1);
+            }
+            KeyCode::Char('{') if self.file_count > 1 => {
+                self.pending_file_switch = Some(-1);
+            }
 
             // Navigation
             KeyCode::Up | KeyCode::Char('k') if ctrl => {
@@ -619,11 +1679,48 @@ impl App {
             KeyCode::Down | KeyCode::Char('j') if ctrl => {
                 self.move_next_entry();
             }
+            KeyCode::Char(']') => {
+                self.jump_to_next_matching(true, "failed syscall", |_, entry| {
+                    entry.errno.is_some()
+                });
+            }
+            KeyCode::Char('[') => {
+                self.jump_to_next_matching(false, "failed syscall", |_, entry| {
+                    entry.errno.is_some()
+                });
+            }
+            KeyCode::Char('s') => {
+                self.jump_to_next_matching(true, "signal or exit", |_, entry| {
+                    is_signal_or_exit(entry)
+                });
+            }
+            KeyCode::Char('S') => {
+                self.jump_to_next_matching(false, "signal or exit", |_, entry| {
+                    is_signal_or_exit(entry)
+                });
+            }
+            KeyCode::Char('m') => {
+                self.toggle_bookmark();
+            }
+            KeyCode::Char('z') => {
+                if let Some(line) = self.display_lines.get(self.selected_line) {
+                    let pid = self.entries[line.entry_idx()].pid;
+                    self.toggle_collapsed_pid(pid);
+                }
+            }
+            KeyCode::Char('b') => {
+                let bookmarks = self.bookmarks.clone();
+                self.jump_to_next_matching(true, "bookmark", |idx, _| bookmarks.contains(&idx));
+            }
+            KeyCode::Char('B') => {
+                let bookmarks = self.bookmarks.clone();
+                self.jump_to_next_matching(false, "bookmark", |idx, _| bookmarks.contains(&idx));
+            }
             KeyCode::Up | KeyCode::Char('k') => {
-                self.move_up();
+                self.move_up(count.unwrap_or(1));
             }
             KeyCode::Down | KeyCode::Char('j') => {
-                self.move_down();
+                self.move_down(count.unwrap_or(1));
             }
             KeyCode::PageUp => {
                 self.scroll_page(true, false);
@@ -637,13 +1734,19 @@ impl App {
             KeyCode::Char('d') if event.modifiers.contains(KeyModifiers::CONTROL) => {
                 self.scroll_page(false, true);
             }
+            // `gg` and `G` go to the entry numbered by the count, if there is one
+            KeyCode::Char('g') if !pending_g => {
+                self.pending_g = true;
+                self.pending_count = count;
+            }
+            KeyCode::Char('g') | KeyCode::Char('G') if count.is_some() => {
+                self.jump_to_entry_number(count.unwrap_or_default());
+            }
             KeyCode::Home | KeyCode::Char('g') => {
                 self.selected_line = 0;
             }
-            KeyCode::End | KeyCode::Char('G') => {
-                if !self.display_lines.is_empty() {
-                    self.selected_line = self.display_lines.len() - 1;
-                }
+            KeyCode::End | KeyCode::Char('G') if !self.display_lines.is_empty() => {
+                self.selected_line = self.display_lines.len() - 1;
             }
 
             // Expand/Collapse
@@ -678,20 +1781,19 @@ impl App {
         }
     }
 
-    fn move_up(&mut self) {
+    /// Move the cursor up by `lines`, stopping at the first line
+    fn move_up(&mut self, lines: usize) {
         self.last_collapsed_position = None; // Clear memory on navigation
         self.last_collapsed_scroll = None;
-        if self.selected_line > 0 {
-            self.selected_line -= 1;
-        }
+        self.selected_line = self.selected_line.saturating_sub(lines);
     }
 
-    fn move_down(&mut self) {
+    /// Move the cursor down by `lines`, stopping at the last line
+    fn move_down(&mut self, lines: usize) {
         self.last_collapsed_position = None; // Clear memory on navigation
         self.last_collapsed_scroll = None;
-        if self.selected_line + 1 < self.display_lines.len() {
-            self.selected_line += 1;
-        }
+        let last = self.display_lines.len().saturating_sub(1);
+        self.selected_line = self.selected_line.saturating_add(lines).min(last);
     }
 
     /// Move to the previous visible syscall entry made by the same PID as the currently selected
@@ -732,13 +1834,12 @@ impl App {
             if let Some(line) = self.display_lines.get(pos)
                 && let DisplayLine::SyscallHeader { .. } = line
                 && let Some(entry) = self.entries.get(line.entry_idx())
+                && entry.pid == current_pid
             {
-                if entry.pid == current_pid {
-                    self.last_collapsed_position = None;
-                    self.last_collapsed_scroll = None;
-                    self.selected_line = pos;
-                    return;
-                }
+                self.last_collapsed_position = None;
+                self.last_collapsed_scroll = None;
+                self.selected_line = pos;
+                return;
             }
             pos -= 1;
         }
@@ -782,18 +1883,79 @@ impl App {
             if let Some(line) = self.display_lines.get(pos)
                 && let DisplayLine::SyscallHeader { .. } = line
                 && let Some(entry) = self.entries.get(line.entry_idx())
+                && entry.pid == current_pid
             {
-                if entry.pid == current_pid {
-                    self.last_collapsed_position = None;
-                    self.last_collapsed_scroll = None;
-                    self.selected_line = pos;
-                    return;
-                }
+                self.last_collapsed_position = None;
+                self.last_collapsed_scroll = None;
+                self.selected_line = pos;
+                return;
             }
             pos = pos.saturating_add(1);
         }
     }
 
+    /// Move to the next (or previous) entry header matching `predicate`, given the entry index
+    /// and the entry, wrapping around the list. Entries of hidden syscalls are skipped, even when
+    /// shown.
+    fn jump_to_next_matching(
+        &mut self,
+        forward: bool,
+        what: &str,
+        predicate: impl Fn(usize, &SyscallEntry) -> bool,
+    ) {
+        let len = self.display_lines.len();
+        let is_match = |pos: usize| {
+            matches!(
+                self.display_lines[pos],
+                DisplayLine::SyscallHeader { entry_idx, is_hidden: false, .. }
+                    if predicate(entry_idx, &self.entries[entry_idx])
+            )
+        };
+
+        let found = (1..=len)
+            .map(|step| {
+                if forward {
+                    (self.selected_line + step) % len
+                } else {
+                    (self.selected_line + len * 2 - step) % len
+                }
+            })
+            .find(|&pos| is_match(pos));
+
+        match found {
+            Some(pos) => {
+                self.last_collapsed_position = None;
+                self.last_collapsed_scroll = None;
+                self.selected_line = pos;
+                self.ensure_visible();
+            }
+            None => self.status_message = Some(format!("No {} in view", what)),
+        }
+    }
+
+    /// Move the viewport by a few lines, dragging the cursor along only if it would leave the
+    /// screen
+    fn scroll_viewport(&mut self, up: bool, lines: usize) {
+        let max_scroll = self
+            .display_lines
+            .len()
+            .saturating_sub(self.last_visible_height);
+        // The list may have shrunk since the scroll was last corrected by a draw
+        let scroll_offset = self.scroll_offset.min(max_scroll);
+        self.scroll_offset = if up {
+            scroll_offset.saturating_sub(lines)
+        } else {
+            (scroll_offset + lines).min(max_scroll)
+        };
+
+        let max_visible = (self.scroll_offset + self.last_visible_height)
+            .min(self.display_lines.len())
+            .saturating_sub(1);
+        self.selected_line = self
+            .selected_line
+            .clamp(self.scroll_offset.min(max_visible), max_visible);
+    }
+
     fn scroll_page(&mut self, up: bool, half: bool) {
         if self.display_lines.is_empty() {
             return;
@@ -881,6 +2043,17 @@ impl App {
         }
 
         match &self.display_lines[self.selected_line] {
+            DisplayLine::CollapsedProcess { pid, .. } => {
+                let pid = *pid;
+                self.toggle_collapsed_pid(pid);
+            }
+            DisplayLine::SyscallGroup { entry_idx, .. } => {
+                let name = self.entries[*entry_idx].syscall_name.clone();
+                if !self.expanded_groups.remove(&name) {
+                    self.expanded_groups.insert(name);
+                }
+                self.rebuild_display_lines();
+            }
             DisplayLine::SyscallHeader { entry_idx, .. } => {
                 // Toggle syscall expansion
                 let idx = *entry_idx;
@@ -929,11 +2102,7 @@ impl App {
 
                     self.expanded_backtraces.insert(idx);
                     // Resolve on-demand
-                    if let Some(entry) = self.entries.get_mut(idx)
-                        && !entry.backtrace.is_empty()
-                    {
-                        let _ = self.resolver.resolve_frames(&mut entry.backtrace);
-                    }
+                    self.start_resolve(Some(idx), false);
                     self.rebuild_display_lines();
 
                     // Adjust scroll to show entire expanded item
@@ -946,6 +2115,13 @@ impl App {
                 }
                 self.rebuild_display_lines();
             }
+            DisplayLine::MoreArguments { entry_idx, .. } => {
+                self.all_arguments.insert(*entry_idx);
+                self.rebuild_display_lines();
+            }
+            DisplayLine::Note { entry_idx, .. } => {
+                self.note_prompt = Some((*entry_idx, self.notes[entry_idx].clone()));
+            }
             DisplayLine::ArgumentsHeader { entry_idx, .. } => {
                 // Toggle arguments expansion
                 let idx = *entry_idx;
@@ -991,63 +2167,296 @@ impl App {
                         Some((resolved.file.clone(), resolved.line, resolved.column));
                 }
             }
+            DisplayLine::FdReference {
+                entry_idx, ref_idx, ..
+            } => {
+                let creator_idx = self.fd_links.get(*entry_idx)[*ref_idx].creator_idx;
+                self.jump_to_entry(creator_idx);
+            }
+            DisplayLine::EntryReference { entry_idx, .. } => {
+                let entry = &self.entries[*entry_idx];
+                if let Some(linked_idx) = entry.unfinished_entry_idx.or(entry.resumed_entry_idx) {
+                    self.jump_to_entry(linked_idx);
+                }
+            }
             _ => {
                 // For other line types, do nothing on Enter
             }
         }
     }
 
-    fn expand_current(&mut self) {
-        if self.selected_line >= self.display_lines.len() {
+    /// Resolve the backtrace of the entry under the cursor, even while it is collapsed
+    fn resolve_current_backtrace(&mut self) {
+        let Some(idx) = self
+            .display_lines
+            .get(self.selected_line)
+            .map(DisplayLine::entry_idx)
+        else {
+            return;
+        };
+        if self.entries[idx].backtrace.is_empty() {
+            self.status_message = Some("No backtrace to resolve".to_string());
             return;
         }
+        self.start_resolve(Some(idx), true);
+    }
 
-        let saved_position = self.last_collapsed_position;
+    /// Start resolving the backtraces of all entries on another thread, which can take a while
+    /// on large traces. The footer shows its progress until `poll_resolve` sees it done.
+    fn start_resolve_all(&mut self) {
+        self.start_resolve(None, true);
+    }
 
-        log::debug!(
-            "expand_current: selected_line={}, saved_position={:?}",
-            self.selected_line,
-            saved_position
-        );
+    /// Start resolving the backtrace of an entry, or of all of them, on another thread, as
+    /// loading the binaries and fetching their debug files can take a while. Frames already
+    /// cached are shown right away. While another run is going, this waits for the next key.
+    fn start_resolve(&mut self, entry_idx: Option<usize>, report: bool) {
+        if self.resolving.is_some() {
+            if report {
+                self.status_message = Some("Still resolving backtraces".to_string());
+            }
+            return;
+        }
 
-        match &self.display_lines[self.selected_line] {
-            DisplayLine::SyscallHeader { entry_idx, .. } => {
-                // Expand syscall if not already expanded
-                let idx = *entry_idx;
-                if !self.expanded_items.contains(&idx) {
-                    log::debug!("Expanding syscall {}", idx);
-                    let header_line = self.selected_line;
+        let entries = match entry_idx {
+            Some(idx) => std::slice::from_ref(&self.entries[idx]),
+            None => &self.entries,
+        };
+        let job = self.resolver.job_for(entries);
+        if job.is_empty() {
+            let resolved = job.run(&ResolveProgress::default());
+            self.finish_resolve(resolved, entry_idx, report);
+            return;
+        }
 
-                    // Save current scroll for future collapse (always save before expanding)
-                    log::debug!(
-                        "Saving scroll_offset={} for future collapse",
-                        self.scroll_offset
-                    );
-                    self.last_collapsed_scroll = Some(self.scroll_offset);
+        let progress = Arc::new(ResolveProgress::default());
+        let handle = std::thread::spawn({
+            let progress = Arc::clone(&progress);
+            move || job.run(&progress)
+        });
+        self.resolving = Some(BackgroundResolve {
+            progress,
+            handle,
+            entry_idx,
+            report,
+        });
+    }
 
-                    self.expanded_items.insert(idx);
-                    self.rebuild_display_lines();
+    /// Show the frames resolved on another thread, once it is done
+    pub fn poll_resolve(&mut self) {
+        if !self
+            .resolving
+            .as_ref()
+            .is_some_and(|resolving| resolving.handle.is_finished())
+        {
+            return;
+        }
+        let resolving = self.resolving.take().unwrap();
+        let Ok(resolved) = resolving.handle.join() else {
+            self.status_message = Some("Failed to resolve the backtraces".to_string());
+            return;
+        };
+        self.finish_resolve(resolved, resolving.entry_idx, resolving.report);
+    }
 
-                    // Restore cursor position if we just collapsed this
-                    if let Some(saved_line) = saved_position
-                        && saved_line < self.display_lines.len()
-                    {
-                        log::debug!("Restoring cursor position to {}", saved_line);
-                        self.selected_line = saved_line;
-                    }
+    /// Copy resolved frames into an entry, or all of them, and show them
+    fn finish_resolve(
+        &mut self,
+        resolved: ResolvedAddresses,
+        entry_idx: Option<usize>,
+        report: bool,
+    ) {
+        let entries = match entry_idx {
+            Some(idx) => std::slice::from_mut(&mut self.entries[idx]),
+            None => &mut self.entries,
+        };
+        self.resolver.finish_job(resolved, entries);
 
-                    // Always adjust scroll to show full list when expanding
-                    log::debug!("Adjusting scroll after expansion");
-                    self.adjust_scroll_after_expansion(header_line);
-                    log::debug!(
-                        "After expand_current (syscall), scroll_offset={}",
-                        self.scroll_offset
-                    );
-                }
-            }
-            DisplayLine::ArgumentsHeader { entry_idx, .. } => {
-                // Expand arguments if not already expanded
-                let idx = *entry_idx;
+        if report {
+            let frames = entries.iter().flat_map(|entry| &entry.backtrace);
+            let (resolved, total) = frames.fold((0, 0), |(resolved, total), frame| {
+                (resolved + usize::from(frame.resolved.is_some()), total + 1)
+            });
+            self.status_message = Some(format!("Resolved {} of {} frames", resolved, total));
+        }
+        self.refresh_common_path_prefix();
+        self.rebuild_display_lines();
+    }
+
+    /// Open the web URL of the resolved frame under the cursor, as configured in
+    /// `Config::source_links`
+    fn open_current_source_link(&mut self) {
+        let Some(DisplayLine::BacktraceResolved {
+            entry_idx,
+            frame_idx,
+            resolved_idx,
+            ..
+        }) = self.display_lines.get(self.selected_line)
+        else {
+            self.status_message = Some("Select a resolved backtrace frame to open".to_string());
+            return;
+        };
+
+        let Some(resolved) = self.entries[*entry_idx]
+            .backtrace
+            .get(*frame_idx)
+            .and_then(|frame| frame.resolved.as_ref())
+            .and_then(|resolved_frames| resolved_frames.get(*resolved_idx))
+        else {
+            return;
+        };
+
+        match self.config.source_url(&resolved.file, resolved.line) {
+            Some(url) => self.pending_url_open = Some(url),
+            None => {
+                self.status_message =
+                    Some(format!("No source link configured for {}", resolved.file))
+            }
+        }
+    }
+
+    /// Text of an entry as strace would print it, followed by its subtree as shown when it is
+    /// expanded
+    fn entry_text(&self, entry_idx: usize) -> String {
+        let mut text = format_entry(&self.entries[entry_idx]);
+        if !self.expanded_items.contains(&entry_idx) && !self.notes.contains_key(&entry_idx) {
+            return text;
+        }
+
+        let mut args = None;
+        for line in &self.display_lines {
+            if line.entry_idx() != entry_idx
+                || matches!(
+                    line,
+                    DisplayLine::ArgumentLine { wrap_row: 1.., .. }
+                        | DisplayLine::RawArguments { wrap_row: 1.., .. }
+                )
+            {
+                continue;
+            }
+            if let Some(prefix) = line.tree_prefix() {
+                text.push('\n');
+                text.push_str(&Self::tree_prefix_to_string(prefix));
+                text.push_str(&self.line_text(line, &mut args));
+            }
+        }
+        text
+    }
+
+    /// Copy the entry under the cursor to the clipboard, with its subtree if expanded
+    fn copy_current_entry(&mut self) {
+        let Some(line) = self.display_lines.get(self.selected_line) else {
+            return;
+        };
+        let text = self.entry_text(line.entry_idx());
+        self.copy_to_clipboard(text);
+    }
+
+    /// Copy the argument under the cursor to the clipboard, either whole (`key=value`) or
+    /// just its value
+    fn copy_current_argument(&mut self, value_only: bool) {
+        let (entry_idx, arg_idx, field) = match self.display_lines.get(self.selected_line) {
+            Some(DisplayLine::ArgumentLine {
+                entry_idx, arg_idx, ..
+            }) => (*entry_idx, *arg_idx, None),
+            Some(DisplayLine::ArgumentField {
+                entry_idx,
+                arg_idx,
+                field,
+                ..
+            }) => (*entry_idx, *arg_idx, Some(field.clone())),
+            _ => {
+                self.status_message = Some("Select an argument line to copy".to_string());
+                return;
+            }
+        };
+
+        let args = self.entries[entry_idx].args();
+        let Some(arg) = args
+            .get(arg_idx)
+            .and_then(|arg| arg.get(field.unwrap_or(0..arg.len())))
+        else {
+            return;
+        };
+
+        let text = if value_only { argument_value(arg) } else { arg };
+        self.copy_to_clipboard(text.to_string());
+    }
+
+    /// Put text in the system clipboard, reporting the outcome in the footer
+    fn copy_to_clipboard(&mut self, text: String) {
+        if self.clipboard.is_none() {
+            match arboard::Clipboard::new() {
+                Ok(clipboard) => self.clipboard = Some(clipboard),
+                Err(e) => {
+                    self.status_message = Some(format!("Clipboard unavailable: {}", e));
+                    return;
+                }
+            }
+        }
+
+        let Some(clipboard) = self.clipboard.as_mut() else {
+            return;
+        };
+
+        let len = text.chars().count();
+        self.status_message = Some(match clipboard.set_text(text) {
+            Ok(()) => format!("Copied {} characters", len),
+            Err(e) => format!("Failed to copy: {}", e),
+        });
+    }
+
+    fn expand_current(&mut self) {
+        if self.selected_line >= self.display_lines.len() {
+            return;
+        }
+
+        let saved_position = self.last_collapsed_position;
+
+        log::debug!(
+            "expand_current: selected_line={}, saved_position={:?}",
+            self.selected_line,
+            saved_position
+        );
+
+        match &self.display_lines[self.selected_line] {
+            DisplayLine::SyscallHeader { entry_idx, .. } => {
+                // Expand syscall if not already expanded
+                let idx = *entry_idx;
+                if !self.expanded_items.contains(&idx) {
+                    log::debug!("Expanding syscall {}", idx);
+                    let header_line = self.selected_line;
+
+                    // Save current scroll for future collapse (always save before expanding)
+                    log::debug!(
+                        "Saving scroll_offset={} for future collapse",
+                        self.scroll_offset
+                    );
+                    self.last_collapsed_scroll = Some(self.scroll_offset);
+
+                    self.expanded_items.insert(idx);
+                    self.rebuild_display_lines();
+
+                    // Restore cursor position if we just collapsed this
+                    if let Some(saved_line) = saved_position
+                        && saved_line < self.display_lines.len()
+                    {
+                        log::debug!("Restoring cursor position to {}", saved_line);
+                        self.selected_line = saved_line;
+                    }
+
+                    // Always adjust scroll to show full list when expanding
+                    log::debug!("Adjusting scroll after expansion");
+                    self.adjust_scroll_after_expansion(header_line);
+                    log::debug!(
+                        "After expand_current (syscall), scroll_offset={}",
+                        self.scroll_offset
+                    );
+                }
+            }
+            DisplayLine::ArgumentsHeader { entry_idx, .. } => {
+                // Expand arguments if not already expanded
+                let idx = *entry_idx;
                 if !self.expanded_arguments.contains(&idx) {
                     log::debug!("Expanding arguments {}", idx);
                     let header_line = self.selected_line;
@@ -1095,11 +2504,7 @@ impl App {
 
                     self.expanded_backtraces.insert(idx);
                     // Resolve on-demand
-                    if let Some(entry) = self.entries.get_mut(idx)
-                        && !entry.backtrace.is_empty()
-                    {
-                        let _ = self.resolver.resolve_frames(&mut entry.backtrace);
-                    }
+                    self.start_resolve(Some(idx), false);
                     self.rebuild_display_lines();
 
                     // Restore cursor position if we just collapsed this
@@ -1152,7 +2557,11 @@ impl App {
 
         // Collapse the deepest surrounding fold based on current line type
         match &self.display_lines[self.selected_line] {
-            DisplayLine::ArgumentLine { entry_idx, .. } => {
+            DisplayLine::SyscallGroup { .. } | DisplayLine::CollapsedProcess { .. } => return,
+            DisplayLine::ArgumentLine { entry_idx, .. }
+            | DisplayLine::ArgumentField { entry_idx, .. }
+            | DisplayLine::MoreArguments { entry_idx, .. }
+            | DisplayLine::RawArguments { entry_idx, .. } => {
                 // In an argument line -> collapse arguments
                 let idx = *entry_idx;
                 log::debug!("Collapsing arguments {} from ArgumentLine", idx);
@@ -1227,12 +2636,16 @@ impl App {
                 }
             }
             DisplayLine::SyscallHeader { entry_idx, .. }
+            | DisplayLine::OutputArguments { entry_idx, .. }
             | DisplayLine::ReturnValue { entry_idx, .. }
             | DisplayLine::Error { entry_idx, .. }
             | DisplayLine::Duration { entry_idx, .. }
             | DisplayLine::Signal { entry_idx, .. }
+            | DisplayLine::SignalField { entry_idx, .. }
             | DisplayLine::Exit { entry_idx, .. }
-            | DisplayLine::EntryReference { entry_idx, .. } => {
+            | DisplayLine::EntryReference { entry_idx, .. }
+            | DisplayLine::FdReference { entry_idx, .. }
+            | DisplayLine::Note { entry_idx, .. } => {
                 // On syscall header or other top-level items -> collapse entire syscall
                 let idx = *entry_idx;
                 self.expanded_items.remove(&idx);
@@ -1412,6 +2825,257 @@ impl App {
             .map(|(i, _)| i)
     }
 
+    /// Raise or lower the duration threshold by a factor of ten, from 1µs up to 1s. Lowering
+    /// it below 1µs removes the filter.
+    pub fn step_min_duration(&mut self, increase: bool) {
+        const STEPS: [f64; 7] = [1e-6, 1e-5, 1e-4, 1e-3, 1e-2, 1e-1, 1.0];
+
+        self.min_duration = match (self.min_duration, increase) {
+            (None, true) => Some(STEPS[0]),
+            (None, false) => None,
+            (Some(min), true) => STEPS.into_iter().find(|&step| step > min).or(Some(min)),
+            (Some(min), false) => STEPS.into_iter().rev().find(|&step| step < min),
+        };
+        self.rebuild_display_lines();
+    }
+
+    pub fn handle_duration_prompt_event(&mut self, event: KeyEvent) {
+        let Some(input) = &mut self.duration_prompt else {
+            return;
+        };
+
+        match event.code {
+            KeyCode::Char(c) if !event.modifiers.contains(KeyModifiers::CONTROL) => {
+                input.push(c);
+            }
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Tab => {
+                self.keep_untimed = !self.keep_untimed;
+                self.rebuild_display_lines();
+            }
+            KeyCode::Enter => {
+                let input = self.duration_prompt.take().unwrap_or_default();
+                if input.trim().is_empty() {
+                    self.min_duration = None;
+                } else if let Some(min) = parse_duration_input(&input) {
+                    self.min_duration = Some(min);
+                } else {
+                    self.status_message = Some(format!("Invalid duration: {}", input));
+                    return;
+                }
+                self.rebuild_display_lines();
+                self.ensure_visible();
+            }
+            KeyCode::Esc => {
+                self.duration_prompt = None;
+            }
+            _ => {}
+        }
+    }
+
+    /// Where the session of the trace is saved, if it was read from a file
+    pub fn session_path(&self) -> Option<PathBuf> {
+        Session::path_for(self.file_path.as_deref()?)
+    }
+
+    /// Quit, unless there are notes that won't be saved, as sessions are off or the trace has no
+    /// file to save one for. Those take quitting a second time.
+    fn quit(&mut self) {
+        let saves_session = self.config.sessions && self.session_path().is_some();
+        if !self.notes.is_empty() && !saves_session && !self.quit_warned {
+            self.quit_warned = true;
+            self.status_message =
+                Some("Notes aren't saved without a session: quit again to drop them".to_string());
+            return;
+        }
+        self.should_quit = true;
+    }
+
+    /// Snapshot of the state worth restoring when the trace is opened again
+    pub fn session(&self) -> Session {
+        let line_number = |&idx: &usize| self.entries[idx].line_number;
+        Session {
+            hidden_syscalls: self.hidden_syscalls.clone(),
+            expanded_items: self.expanded_items.iter().map(line_number).collect(),
+            bookmarks: self.bookmarks.iter().map(line_number).collect(),
+            notes: self
+                .notes
+                .iter()
+                .map(|(idx, note)| (line_number(idx), note.clone()))
+                .collect(),
+            search_query: self.search_state.query.clone(),
+            selected_line: self.selected_line,
+            scroll_offset: self.scroll_offset,
+        }
+    }
+
+    /// Bring back a saved session. Entries it names that the trace doesn't have are dropped.
+    pub fn restore_session(&mut self, session: Session) {
+        let entry_at_line: HashMap<usize, usize> = self
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(idx, entry)| (entry.line_number, idx))
+            .collect();
+        let entry_idx = |line_number: usize| entry_at_line.get(&line_number).copied();
+        self.hidden_syscalls = session.hidden_syscalls;
+        self.expanded_items = session
+            .expanded_items
+            .into_iter()
+            .filter_map(entry_idx)
+            .collect();
+        self.bookmarks = session
+            .bookmarks
+            .into_iter()
+            .filter_map(entry_idx)
+            .collect();
+        self.notes = session
+            .notes
+            .into_iter()
+            .filter_map(|(line_number, note)| Some((entry_idx(line_number)?, note)))
+            .collect();
+        self.rebuild_display_lines();
+
+        self.search_state.query = session.search_query;
+        self.update_search_matches_internal(false);
+
+        self.selected_line = session
+            .selected_line
+            .min(self.display_lines.len().saturating_sub(1));
+        self.scroll_offset = session.scroll_offset.min(self.selected_line);
+    }
+
+    /// Whether the entries are listed as they appear in the trace, which the process graph needs
+    pub fn shows_trace_order(&self) -> bool {
+        self.sort_mode == SortMode::Trace && !self.group_by_syscall
+    }
+
+    /// Cluster the entries under a header per syscall name, or list them back in order
+    pub fn toggle_group_by_syscall(&mut self) {
+        self.group_by_syscall = !self.group_by_syscall;
+        let name = self
+            .display_lines
+            .get(self.selected_line)
+            .map(|line| self.entries[line.entry_idx()].syscall_name.clone());
+        self.rebuild_display_lines();
+
+        // Land on the group of the entry, unless it is listed
+        if self.group_by_syscall
+            && let Some(name) = name
+            && !self.expanded_groups.contains(&name)
+            && let Some(line) = self.display_lines.iter().position(|line| {
+                matches!(line, DisplayLine::SyscallGroup { entry_idx, .. }
+                    if self.entries[*entry_idx].syscall_name == name)
+            })
+        {
+            self.selected_line = line;
+        }
+        self.ensure_visible();
+        self.status_message = Some(
+            if self.group_by_syscall {
+                "Grouped by syscall"
+            } else {
+                "Ungrouped"
+            }
+            .to_string(),
+        );
+    }
+
+    /// Fold all the entries of a process into a single line, or unfold them back
+    pub fn toggle_collapsed_pid(&mut self, pid: u32) {
+        if !self.collapsed_pids.remove(&pid) {
+            self.collapsed_pids.insert(pid);
+        }
+        self.rebuild_display_lines();
+
+        // Keep the cursor on the process, as its placeholder may be above the entry it was on
+        if self.collapsed_pids.contains(&pid)
+            && let Some(line) = self.display_lines.iter().position(
+                |line| matches!(line, DisplayLine::CollapsedProcess { pid: p, .. } if *p == pid),
+            )
+        {
+            self.selected_line = line;
+        }
+        self.ensure_visible();
+    }
+
+    /// Bookmark the entry under the cursor, or remove its bookmark
+    pub fn toggle_bookmark(&mut self) {
+        let Some(line) = self.display_lines.get(self.selected_line) else {
+            return;
+        };
+        let entry_idx = line.entry_idx();
+        if !self.bookmarks.remove(&entry_idx) {
+            self.bookmarks.insert(entry_idx);
+        }
+
+        // The first bookmark, or the last one gone, changes the room left for the arguments
+        if self.wraps_arguments() && self.bookmarks.len() <= 1 {
+            self.rebuild_display_lines();
+            self.ensure_visible();
+        }
+    }
+
+    pub fn handle_jump_prompt_event(&mut self, event: KeyEvent) {
+        let Some(input) = &mut self.jump_prompt else {
+            return;
+        };
+
+        match event.code {
+            KeyCode::Char(c) if !event.modifiers.contains(KeyModifiers::CONTROL) => {
+                input.push(c);
+            }
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Enter => {
+                let input = self.jump_prompt.take().unwrap_or_default();
+                match parse_entry_number(input.trim()) {
+                    Some(number) => self.jump_to_entry_number(number),
+                    None => self.status_message = Some(format!("No entry #{}", input.trim())),
+                }
+            }
+            KeyCode::Esc => {
+                self.jump_prompt = None;
+            }
+            _ => {}
+        }
+    }
+
+    /// Edit the note of the entry that was under the cursor when the prompt opened, which
+    /// follow mode may have moved off since. Entering an empty note removes it.
+    pub fn handle_note_prompt_event(&mut self, event: KeyEvent) {
+        let Some((_, input)) = &mut self.note_prompt else {
+            return;
+        };
+
+        match event.code {
+            KeyCode::Char(c) if !event.modifiers.contains(KeyModifiers::CONTROL) => {
+                input.push(c);
+            }
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Enter => {
+                let Some((entry_idx, note)) = self.note_prompt.take() else {
+                    return;
+                };
+                if note.trim().is_empty() {
+                    self.notes.remove(&entry_idx);
+                } else {
+                    self.notes.insert(entry_idx, note.trim().to_string());
+                }
+                self.rebuild_display_lines();
+            }
+            KeyCode::Esc => {
+                self.note_prompt = None;
+            }
+            _ => {}
+        }
+    }
+
     pub fn toggle_show_hidden(&mut self) {
         self.show_hidden = !self.show_hidden;
         self.rebuild_display_lines();
@@ -1440,58 +3104,278 @@ impl App {
         self.rebuild_display_lines();
     }
 
-    pub fn handle_filter_modal_event(&mut self, event: KeyEvent) {
-        // Priority: Modal search mode
-        if self.modal_search_state.active {
-            self.handle_modal_search_event(event);
-            return;
+    pub fn handle_stats_modal_event(&mut self, event: KeyEvent) {
+        // The modal takes 70% of screen height, minus borders and the column header
+        let visible_height = (self.last_visible_height * 70 / 100).saturating_sub(3);
+
+        match event.code {
+            KeyCode::Esc | KeyCode::Char('%') | KeyCode::Char('q') => {
+                self.show_stats_modal = false;
+            }
+            code => {
+                let state = &mut self.stats_modal_state;
+                navigate_list(
+                    &mut state.selected_index,
+                    &mut state.scroll_offset,
+                    state.rows.len(),
+                    visible_height,
+                    code,
+                );
+            }
         }
+    }
 
-        // Get visible height for scroll calculations (estimate based on typical modal size)
-        // The modal takes 70% of screen height, minus 2 for borders
-        let visible_height = (self.last_visible_height * 70 / 100).saturating_sub(2);
+    /// Open the duration histogram of all entries, offering to narrow it to the syscall under
+    /// the cursor
+    fn open_histogram(&mut self) {
+        let state = &mut self.histogram_state;
+        state.syscall = self
+            .display_lines
+            .get(self.selected_line)
+            .map(|line| self.entries[line.entry_idx()].syscall_name.clone())
+            .filter(|name| !name.is_empty());
+        state.filtered = false;
+        state.selected_index = 0;
+        state.scroll_offset = 0;
+        state.buckets = duration_histogram(&self.entries, None);
+        self.show_histogram = true;
+    }
+
+    pub fn handle_histogram_event(&mut self, event: KeyEvent) {
+        // The modal takes 50% of screen height, minus borders and the column header
+        let visible_height = (self.last_visible_height * 50 / 100).saturating_sub(3);
 
         match event.code {
-            KeyCode::Char('/') => {
-                self.start_modal_search();
-            }
-            KeyCode::Char('n') if !self.modal_search_state.query.is_empty() => {
-                self.modal_search_next();
+            KeyCode::Esc | KeyCode::Char('D') | KeyCode::Char('q') => {
+                self.show_histogram = false;
             }
-            KeyCode::Char('N') if !self.modal_search_state.query.is_empty() => {
-                self.modal_search_previous();
+            KeyCode::Tab => {
+                let state = &mut self.histogram_state;
+                if state.syscall.is_some() {
+                    state.filtered = !state.filtered;
+                    let name = state.syscall.as_deref().filter(|_| state.filtered);
+                    state.buckets = duration_histogram(&self.entries, name);
+                }
             }
-            KeyCode::Esc | KeyCode::Char('H') | KeyCode::Char('q') => {
-                self.close_filter_modal();
+            code => {
+                let state = &mut self.histogram_state;
+                navigate_list(
+                    &mut state.selected_index,
+                    &mut state.scroll_offset,
+                    state.buckets.len(),
+                    visible_height,
+                    code,
+                );
             }
-            KeyCode::Up | KeyCode::Char('k') => {
-                if self.filter_modal_state.selected_index > 0 {
-                    self.filter_modal_state.selected_index -= 1;
+        }
+    }
 
-                    // Adjust scroll if needed
-                    if self.filter_modal_state.selected_index
-                        < self.filter_modal_state.scroll_offset
-                    {
-                        self.filter_modal_state.scroll_offset =
-                            self.filter_modal_state.selected_index;
-                    }
-                }
+    pub fn handle_pid_picker_event(&mut self, event: KeyEvent) {
+        // The modal takes 50% of screen height, minus borders and the column header
+        let visible_height = (self.last_visible_height * 50 / 100).saturating_sub(3);
+
+        match event.code {
+            KeyCode::Esc | KeyCode::Char('p') | KeyCode::Char('q') => {
+                self.show_pid_picker = false;
             }
-            KeyCode::Down | KeyCode::Char('j') => {
-                if self.filter_modal_state.selected_index + 1
-                    < self.filter_modal_state.syscall_list.len()
+            KeyCode::Enter => {
+                self.show_pid_picker = false;
+                if let Some(row) = self
+                    .pid_picker_state
+                    .rows
+                    .get(self.pid_picker_state.selected_index)
+                {
+                    let first_entry_idx = row.first_entry_idx;
+                    self.jump_to_entry(first_entry_idx);
+                }
+            }
+            code => {
+                let state = &mut self.pid_picker_state;
+                navigate_list(
+                    &mut state.selected_index,
+                    &mut state.scroll_offset,
+                    state.rows.len(),
+                    visible_height,
+                    code,
+                );
+            }
+        }
+    }
+
+    pub fn handle_process_tree_event(&mut self, event: KeyEvent) {
+        // The panel is as tall as the list, minus borders
+        let visible_height = self.last_visible_height.saturating_sub(2);
+
+        match event.code {
+            KeyCode::Esc | KeyCode::Char('t') | KeyCode::Char('q') => {
+                self.show_process_tree = false;
+            }
+            KeyCode::Char('?') => {
+                self.show_help = true;
+            }
+            // Stays open, so several processes can be visited in turn
+            KeyCode::Enter => {
+                if let Some(row) = self
+                    .process_tree_state
+                    .rows
+                    .get(self.process_tree_state.selected_index)
                 {
-                    self.filter_modal_state.selected_index += 1;
-
-                    // Adjust scroll if needed
-                    let max_visible = self.filter_modal_state.scroll_offset + visible_height;
-                    if self.filter_modal_state.selected_index >= max_visible {
-                        self.filter_modal_state.scroll_offset = self
-                            .filter_modal_state
-                            .selected_index
-                            .saturating_sub(visible_height)
-                            + 1;
+                    let first_entry_idx = row.first_entry_idx;
+                    self.jump_to_entry(first_entry_idx);
+                }
+            }
+            code => {
+                let state = &mut self.process_tree_state;
+                navigate_list(
+                    &mut state.selected_index,
+                    &mut state.scroll_offset,
+                    state.rows.len(),
+                    visible_height,
+                    code,
+                );
+            }
+        }
+    }
+
+    pub fn handle_program_output_event(&mut self, event: KeyEvent) {
+        // The pane takes 70% of screen height, minus borders
+        let visible_height = (self.last_visible_height * 70 / 100).saturating_sub(2);
+
+        match event.code {
+            KeyCode::Esc | KeyCode::Char('O') | KeyCode::Char('q') => {
+                self.show_program_output = false;
+            }
+            code => {
+                let state = &mut self.program_output_state;
+                navigate_list(
+                    &mut state.selected_index,
+                    &mut state.scroll_offset,
+                    self.program_output.len(),
+                    visible_height,
+                    code,
+                );
+            }
+        }
+    }
+
+    pub fn handle_parse_errors_event(&mut self, event: KeyEvent) {
+        // The modal takes 70% of screen height, minus borders
+        let visible_height = (self.last_visible_height * 70 / 100).saturating_sub(2);
+
+        match event.code {
+            KeyCode::Esc | KeyCode::Char('!') | KeyCode::Char('q') => {
+                self.show_parse_errors = false;
+            }
+            KeyCode::Enter => {
+                let Some(error) = self
+                    .parse_errors
+                    .get(self.parse_errors_state.selected_index)
+                else {
+                    return;
+                };
+                // The entry the bad line came after, or the first one when it came before all
+                let entry_idx = self
+                    .entries
+                    .partition_point(|entry| entry.line_number <= error.line_number)
+                    .saturating_sub(1);
+                if entry_idx < self.entries.len() {
+                    self.show_parse_errors = false;
+                    self.jump_to_entry(entry_idx);
+                }
+            }
+            code => {
+                let state = &mut self.parse_errors_state;
+                navigate_list(
+                    &mut state.selected_index,
+                    &mut state.scroll_offset,
+                    self.parse_errors.len(),
+                    visible_height,
+                    code,
+                );
+            }
+        }
+    }
+
+    pub fn handle_errno_filter_event(&mut self, event: KeyEvent) {
+        // The modal takes 50% of screen height, minus borders
+        let visible_height = (self.last_visible_height * 50 / 100).saturating_sub(2);
+
+        match event.code {
+            KeyCode::Esc | KeyCode::Char('E') | KeyCode::Char('q') => {
+                self.show_errno_filter = false;
+            }
+            KeyCode::Char(' ') | KeyCode::Enter => {
+                let state = &self.errno_filter_state;
+                if let Some((code, _)) = state.codes.get(state.selected_index) {
+                    if !self.errno_filter.remove(code) {
+                        self.errno_filter.insert(code.clone());
                     }
+                    self.rebuild_display_lines();
+                }
+            }
+            KeyCode::Char('a') => {
+                self.errno_filter.clear();
+                self.rebuild_display_lines();
+            }
+            code => {
+                let state = &mut self.errno_filter_state;
+                navigate_list(
+                    &mut state.selected_index,
+                    &mut state.scroll_offset,
+                    state.codes.len(),
+                    visible_height,
+                    code,
+                );
+            }
+        }
+    }
+
+    pub fn handle_filter_modal_event(&mut self, event: KeyEvent) {
+        // Priority: Modal search mode
+        if self.modal_search_state.active {
+            self.handle_modal_search_event(event);
+            return;
+        }
+
+        // Get visible height for scroll calculations (estimate based on typical modal size)
+        // The modal takes 70% of screen height, minus 2 for borders
+        let visible_height = (self.last_visible_height * 70 / 100).saturating_sub(2);
+
+        match event.code {
+            KeyCode::Char('/') => {
+                self.start_modal_search();
+            }
+            KeyCode::Char('n') if !self.modal_search_state.query.is_empty() => {
+                self.modal_search_next();
+            }
+            KeyCode::Char('N') if !self.modal_search_state.query.is_empty() => {
+                self.modal_search_previous();
+            }
+            KeyCode::Esc | KeyCode::Char('H') | KeyCode::Char('q') => {
+                self.close_filter_modal();
+            }
+            KeyCode::Up | KeyCode::Char('k') if self.filter_modal_state.selected_index > 0 => {
+                self.filter_modal_state.selected_index -= 1;
+
+                // Adjust scroll if needed
+                if self.filter_modal_state.selected_index < self.filter_modal_state.scroll_offset {
+                    self.filter_modal_state.scroll_offset = self.filter_modal_state.selected_index;
+                }
+            }
+            KeyCode::Down | KeyCode::Char('j')
+                if self.filter_modal_state.selected_index + 1
+                    < self.filter_modal_state.syscall_list.len() =>
+            {
+                self.filter_modal_state.selected_index += 1;
+
+                // Adjust scroll if needed
+                let max_visible = self.filter_modal_state.scroll_offset + visible_height;
+                if self.filter_modal_state.selected_index >= max_visible {
+                    self.filter_modal_state.scroll_offset = self
+                        .filter_modal_state
+                        .selected_index
+                        .saturating_sub(visible_height)
+                        + 1;
                 }
             }
             KeyCode::PageUp => {
@@ -1590,6 +3474,8 @@ impl App {
         self.search_state.query.clear();
         self.search_state.matches.clear();
         self.search_state.current_match_idx = 0;
+        self.search_state.matched_entries = 0;
+        self.search_state.last_hit = None;
     }
 
     pub fn start_modal_search(&mut self) {
@@ -1601,8 +3487,39 @@ impl App {
         self.modal_search_state.current_match_idx = 0;
     }
 
+    #[cfg(test)]
     fn get_line_text(&self, line: &DisplayLine) -> String {
+        self.line_text(line, &mut None)
+    }
+
+    /// The split arguments of an entry, kept in `args` for the following lines of the entry
+    fn entry_args<'a, 'c>(
+        &'a self,
+        entry_idx: usize,
+        args: &'c mut Option<(usize, SyscallArgs<'a>)>,
+    ) -> &'c SyscallArgs<'a> {
+        if args.as_ref().is_some_and(|(idx, _)| *idx != entry_idx) {
+            *args = None;
+        }
+        &args
+            .get_or_insert_with(|| (entry_idx, self.entries[entry_idx].args()))
+            .1
+    }
+
+    /// Text of a line, splitting the arguments of its entry only if `args` doesn't have them
+    /// from the previous line
+    fn line_text<'a>(
+        &'a self,
+        line: &DisplayLine,
+        args: &mut Option<(usize, SyscallArgs<'a>)>,
+    ) -> String {
         match line {
+            DisplayLine::CollapsedProcess { pid, count, .. } => {
+                format!("[{}] {} syscalls collapsed", pid, count)
+            }
+            DisplayLine::SyscallGroup {
+                entry_idx, count, ..
+            } => format_syscall_group(&self.entries[*entry_idx].syscall_name, *count),
             DisplayLine::SyscallHeader { entry_idx, .. } => {
                 let entry = &self.entries[*entry_idx];
                 format!(
@@ -1612,18 +3529,48 @@ impl App {
                     entry.return_value.as_deref().unwrap_or("")
                 )
             }
+            // The first row of a wrapped argument stands for all of it
+            DisplayLine::ArgumentLine { wrap_row: 1.., .. }
+            | DisplayLine::RawArguments { wrap_row: 1.., .. } => String::new(),
+            DisplayLine::RawArguments { entry_idx, .. } => {
+                self.entries[*entry_idx].arguments.clone()
+            }
+            DisplayLine::MoreArguments { count, .. } => format_more_arguments(*count),
+            DisplayLine::Note { entry_idx, .. } => {
+                format!(
+                    "Note: {}",
+                    self.notes.get(entry_idx).map_or("", |note| note)
+                )
+            }
             DisplayLine::ArgumentLine {
                 entry_idx, arg_idx, ..
-            } => {
-                let entry = &self.entries[*entry_idx];
-                let args = split_arguments(&entry.arguments);
-                args.get(*arg_idx).cloned().unwrap_or_default()
-            }
+            } => self
+                .entry_args(*entry_idx, args)
+                .get(*arg_idx)
+                .unwrap_or_default()
+                .to_string(),
+            DisplayLine::ArgumentField {
+                entry_idx,
+                arg_idx,
+                field,
+                ..
+            } => self
+                .entry_args(*entry_idx, args)
+                .get(*arg_idx)
+                .and_then(|arg| arg.get(field.clone()))
+                .unwrap_or_default()
+                .to_string(),
             DisplayLine::ArgumentsHeader { .. } => "Arguments".to_string(),
-            DisplayLine::ReturnValue { entry_idx, .. } => {
-                let entry = &self.entries[*entry_idx];
-                format!("Return: {}", entry.return_value.as_deref().unwrap_or("?"))
+            DisplayLine::OutputArguments { entry_idx, .. } => {
+                format!(
+                    "Output: {}",
+                    self.entries[*entry_idx]
+                        .output_args
+                        .as_deref()
+                        .unwrap_or("")
+                )
             }
+            DisplayLine::ReturnValue { entry_idx, .. } => format_return(&self.entries[*entry_idx]),
             DisplayLine::Error { entry_idx, .. } => {
                 let entry = &self.entries[*entry_idx];
                 if let Some(errno) = &entry.errno {
@@ -1635,15 +3582,35 @@ impl App {
             DisplayLine::Signal { entry_idx, .. } => {
                 let entry = &self.entries[*entry_idx];
                 if let Some(signal) = &entry.signal {
-                    format!("Signal: {}", signal.signal_name)
+                    if signal.stopped {
+                        format!("Stopped by {}", signal.signal_name)
+                    } else {
+                        format!("Signal: {}", signal.signal_name)
+                    }
                 } else {
                     String::new()
                 }
             }
+            DisplayLine::SignalField {
+                entry_idx,
+                field_idx,
+                ..
+            } => {
+                let entry = &self.entries[*entry_idx];
+                entry
+                    .signal
+                    .as_ref()
+                    .and_then(|signal| signal.sorted_fields().get(*field_idx).copied())
+                    .map(|(key, value)| format!("{}={}", key, value))
+                    .unwrap_or_default()
+            }
             DisplayLine::Exit { entry_idx, .. } => {
                 let entry = &self.entries[*entry_idx];
                 if let Some(exit) = &entry.exit_info {
-                    format!("Exit: code={} killed={}", exit.code, exit.killed)
+                    match &exit.signal {
+                        Some(signal) => format!("Exit: killed by {}", signal),
+                        None => format!("Exit: code={} killed={}", exit.code, exit.killed),
+                    }
                 } else {
                     String::new()
                 }
@@ -1658,6 +3625,16 @@ impl App {
                     String::new()
                 }
             }
+            DisplayLine::FdReference {
+                entry_idx, ref_idx, ..
+            } => {
+                let fd_ref = &self.fd_links.get(*entry_idx)[*ref_idx];
+                format!(
+                    "{} created by entry #{}",
+                    fd_ref.inode,
+                    fd_ref.creator_idx + 1
+                )
+            }
             DisplayLine::BacktraceHeader { .. } => "Backtrace".to_string(),
             DisplayLine::BacktraceFrame {
                 entry_idx,
@@ -1706,6 +3683,7 @@ impl App {
             self.search_state.query
         );
         self.search_state.matches.clear();
+        self.search_state.matched_entries = 0;
 
         if self.search_state.query.is_empty() {
             // Clear search match flags
@@ -1720,6 +3698,21 @@ impl App {
                     DisplayLine::ArgumentLine {
                         is_search_match, ..
                     } => *is_search_match = false,
+                    DisplayLine::ArgumentField {
+                        is_search_match, ..
+                    } => *is_search_match = false,
+                    DisplayLine::MoreArguments {
+                        is_search_match, ..
+                    } => *is_search_match = false,
+                    DisplayLine::RawArguments {
+                        is_search_match, ..
+                    } => *is_search_match = false,
+                    DisplayLine::Note {
+                        is_search_match, ..
+                    } => *is_search_match = false,
+                    DisplayLine::OutputArguments {
+                        is_search_match, ..
+                    } => *is_search_match = false,
                     DisplayLine::ReturnValue {
                         is_search_match, ..
                     } => *is_search_match = false,
@@ -1732,12 +3725,18 @@ impl App {
                     DisplayLine::Signal {
                         is_search_match, ..
                     } => *is_search_match = false,
+                    DisplayLine::SignalField {
+                        is_search_match, ..
+                    } => *is_search_match = false,
                     DisplayLine::Exit {
                         is_search_match, ..
                     } => *is_search_match = false,
                     DisplayLine::EntryReference {
                         is_search_match, ..
                     } => *is_search_match = false,
+                    DisplayLine::FdReference {
+                        is_search_match, ..
+                    } => *is_search_match = false,
                     DisplayLine::BacktraceHeader {
                         is_search_match, ..
                     } => *is_search_match = false,
@@ -1747,6 +3746,12 @@ impl App {
                     DisplayLine::BacktraceResolved {
                         is_search_match, ..
                     } => *is_search_match = false,
+                    DisplayLine::SyscallGroup {
+                        is_search_match, ..
+                    } => *is_search_match = false,
+                    DisplayLine::CollapsedProcess {
+                        is_search_match, ..
+                    } => *is_search_match = false,
                 }
             }
             return;
@@ -1756,9 +3761,13 @@ impl App {
 
         // First pass: collect match information
         let mut matches_and_texts: Vec<(usize, bool)> = Vec::new();
+        let mut args = None;
         for (idx, line) in self.display_lines.iter().enumerate() {
-            let text = self.get_line_text(line);
-            let is_match = text.to_lowercase().contains(&query_lower);
+            let is_match = self.search_state.scope.includes(line)
+                && self
+                    .line_text(line, &mut args)
+                    .to_lowercase()
+                    .contains(&query_lower);
             matches_and_texts.push((idx, is_match));
         }
 
@@ -1774,6 +3783,21 @@ impl App {
                 DisplayLine::ArgumentLine {
                     is_search_match, ..
                 } => *is_search_match = is_match,
+                DisplayLine::ArgumentField {
+                    is_search_match, ..
+                } => *is_search_match = is_match,
+                DisplayLine::MoreArguments {
+                    is_search_match, ..
+                } => *is_search_match = is_match,
+                DisplayLine::RawArguments {
+                    is_search_match, ..
+                } => *is_search_match = is_match,
+                DisplayLine::Note {
+                    is_search_match, ..
+                } => *is_search_match = is_match,
+                DisplayLine::OutputArguments {
+                    is_search_match, ..
+                } => *is_search_match = is_match,
                 DisplayLine::ReturnValue {
                     is_search_match, ..
                 } => *is_search_match = is_match,
@@ -1786,12 +3810,18 @@ impl App {
                 DisplayLine::Signal {
                     is_search_match, ..
                 } => *is_search_match = is_match,
+                DisplayLine::SignalField {
+                    is_search_match, ..
+                } => *is_search_match = is_match,
                 DisplayLine::Exit {
                     is_search_match, ..
                 } => *is_search_match = is_match,
                 DisplayLine::EntryReference {
                     is_search_match, ..
                 } => *is_search_match = is_match,
+                DisplayLine::FdReference {
+                    is_search_match, ..
+                } => *is_search_match = is_match,
                 DisplayLine::BacktraceHeader {
                     is_search_match, ..
                 } => *is_search_match = is_match,
@@ -1801,6 +3831,12 @@ impl App {
                 DisplayLine::BacktraceResolved {
                     is_search_match, ..
                 } => *is_search_match = is_match,
+                DisplayLine::SyscallGroup {
+                    is_search_match, ..
+                } => *is_search_match = is_match,
+                DisplayLine::CollapsedProcess {
+                    is_search_match, ..
+                } => *is_search_match = is_match,
             }
 
             if is_match {
@@ -1808,6 +3844,24 @@ impl App {
             }
         }
 
+        // Lines of the same entry are contiguous, so counting entry changes counts entries
+        let mut last_entry = None;
+        self.search_state.matched_entries = 0;
+        for &idx in &self.search_state.matches {
+            let entry_idx = self.display_lines[idx].entry_idx();
+            if last_entry != Some(entry_idx) {
+                self.search_state.matched_entries += 1;
+                last_entry = Some(entry_idx);
+            }
+        }
+
+        if !self.search_state.matches.is_empty() {
+            self.search_state.last_hit = Some((
+                self.search_state.query.clone(),
+                self.search_state.matches.len(),
+            ));
+        }
+
         // Update current_match_idx to point to nearest match
         if !self.search_state.matches.is_empty() {
             // Find first match at or after current position
@@ -1881,6 +3935,29 @@ impl App {
         self.ensure_visible();
     }
 
+    /// Jump to an entry by the number shown with `#`, which starts at 1
+    fn jump_to_entry_number(&mut self, number: usize) {
+        if (1..=self.entries.len()).contains(&number) {
+            self.jump_to_entry(number - 1);
+        } else {
+            self.status_message = Some(format!("No entry #{}", number));
+        }
+    }
+
+    /// Move the cursor to an entry's header line, if filters don't hide it
+    fn jump_to_entry(&mut self, entry_idx: usize) -> bool {
+        let Some(line) = self.display_lines.iter().position(
+            |line| matches!(line, DisplayLine::SyscallHeader { entry_idx: i, .. } if *i == entry_idx),
+        ) else {
+            self.status_message = Some(format!("Entry #{} is hidden by filters", entry_idx + 1));
+            return false;
+        };
+
+        self.selected_line = line;
+        self.ensure_visible();
+        true
+    }
+
     fn ensure_visible(&mut self) {
         if self.selected_line < self.scroll_offset {
             self.scroll_offset = self.selected_line;
@@ -1899,6 +3976,10 @@ impl App {
                 self.search_state.query.pop();
                 self.update_search_matches();
             }
+            KeyCode::Tab => {
+                self.search_state.scope = self.search_state.scope.next();
+                self.update_search_matches();
+            }
             KeyCode::Enter => {
                 // Accept search, stay at current position
                 self.search_state.active = false;
@@ -2050,62 +4131,1753 @@ impl App {
     }
 }
 
-/// Split arguments by comma, handling nested structures
-pub fn split_arguments(args: &str) -> Vec<String> {
-    let mut result = Vec::new();
-    let mut current = String::new();
+/// Longest common directory prefix (ending in '/') shared by all paths. Returns an empty string
+/// if there are no paths or they only share the root directory.
+pub fn common_path_prefix<'a>(paths: impl IntoIterator<Item = &'a str>) -> String {
+    let mut prefix: Option<&str> = None;
+
+    for path in paths {
+        let Some(current) = prefix else {
+            prefix = Some(path);
+            continue;
+        };
+
+        let mut common = current
+            .bytes()
+            .zip(path.bytes())
+            .take_while(|(a, b)| a == b)
+            .count();
+        while !current.is_char_boundary(common) {
+            common -= 1;
+        }
+        prefix = Some(&current[..common]);
+    }
+
+    // Only strip whole directory components
+    match prefix.and_then(|p| p.rfind('/').map(|pos| &p[..=pos])) {
+        Some(dir) if dir != "/" => dir.to_string(),
+        _ => String::new(),
+    }
+}
+
+/// Move a modal list selection with the usual navigation keys, scrolling to keep it in view
+fn navigate_list(
+    selected_index: &mut usize,
+    scroll_offset: &mut usize,
+    len: usize,
+    visible_height: usize,
+    code: KeyCode,
+) {
+    let max_index = len.saturating_sub(1);
+
+    match code {
+        KeyCode::Up | KeyCode::Char('k') => {
+            *selected_index = selected_index.saturating_sub(1);
+        }
+        KeyCode::Down | KeyCode::Char('j') => {
+            *selected_index = (*selected_index + 1).min(max_index);
+        }
+        KeyCode::PageUp => {
+            *selected_index = selected_index.saturating_sub(visible_height);
+        }
+        KeyCode::PageDown => {
+            *selected_index = (*selected_index + visible_height).min(max_index);
+        }
+        KeyCode::Home | KeyCode::Char('g') => {
+            *selected_index = 0;
+        }
+        KeyCode::End | KeyCode::Char('G') => {
+            *selected_index = max_index;
+        }
+        _ => return,
+    }
+
+    if *selected_index < *scroll_offset {
+        *scroll_offset = *selected_index;
+    } else if *selected_index >= *scroll_offset + visible_height {
+        *scroll_offset = (*selected_index + 1).saturating_sub(visible_height);
+    }
+}
+
+/// Entry number typed in the jump prompt, in decimal or `0x`-prefixed hex
+fn parse_entry_number(input: &str) -> Option<usize> {
+    match input
+        .strip_prefix("0x")
+        .or_else(|| input.strip_prefix("0X"))
+    {
+        Some(hex) => usize::from_str_radix(hex, 16).ok(),
+        None => input.parse().ok(),
+    }
+}
+
+/// Text of the line standing for the arguments past the cap
+pub fn format_more_arguments(count: usize) -> String {
+    format!("(+{} more, Enter to show all)", count)
+}
+
+/// Text of the header of a syscall's group, e.g. `openat (42 calls)`
+pub fn format_syscall_group(name: &str, count: usize) -> String {
+    format!(
+        "{} ({} call{})",
+        name,
+        count,
+        if count == 1 { "" } else { "s" }
+    )
+}
+
+fn is_signal_or_exit(entry: &SyscallEntry) -> bool {
+    entry.signal.is_some() || entry.exit_info.is_some()
+}
+
+/// Parse a duration typed by the user into seconds, e.g. `1ms`, `250us`, `0.5s` or `2` (seconds)
+pub fn parse_duration_input(input: &str) -> Option<f64> {
+    let input = input.trim();
+    let split = input
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(input.len());
+    let (number, unit) = input.split_at(split);
+
+    let per_second = match unit.trim() {
+        "" | "s" => 1.0,
+        "ms" => 1e3,
+        "us" | "µs" => 1e6,
+        "ns" => 1e9,
+        _ => return None,
+    };
+    let value: f64 = number.parse().ok()?;
+    Some(value / per_second)
+}
+
+/// Format a duration in seconds with the unit that fits its magnitude (ns/µs/ms/s), keeping
+/// three significant digits, e.g. `0.000001` -> `1µs`, `0.0123` -> `12.3ms`
+pub fn format_duration(secs: f64) -> String {
+    const UNITS: [(f64, &str); 3] = [(1e9, "ns"), (1e6, "µs"), (1e3, "ms")];
+    // The unit is picked after rounding, so that 999.7µs reads 1ms and not 1000µs
+    let (value, unit) = UNITS
+        .iter()
+        .map(|&(per_second, unit)| (secs * per_second, unit))
+        .find(|(value, _)| *value < 999.5)
+        .unwrap_or((secs, "s"));
+
+    let decimals = if value < 10.0 {
+        2
+    } else if value < 100.0 {
+        1
+    } else {
+        0
+    };
+
+    let mut number = format!("{:.*}", decimals, value);
+    if number.contains('.') {
+        number = number
+            .trim_end_matches('0')
+            .trim_end_matches('.')
+            .to_string();
+    }
+    format!("{}{}", number, unit)
+}
+
+/// Format a byte count in the binary unit that fits it, with one decimal, e.g. `1536` ->
+/// `1.5KiB`
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{}B", bytes)
+    } else {
+        let number = format!("{:.1}", value);
+        format!("{}{}", number.trim_end_matches(".0"), UNITS[unit])
+    }
+}
+
+/// Return line of an expanded entry, with the fd path and annotation strace decoded, e.g.
+/// `Return: 0 (Timeout)`
+pub fn format_return(entry: &SyscallEntry) -> String {
+    let mut text = format!("Return: {}", entry.return_value.as_deref().unwrap_or("?"));
+    if let Some(path) = &entry.return_fd_path {
+        text.push_str(&format!("<{}>", path));
+    }
+    if let Some(annotation) = &entry.return_annotation {
+        text.push_str(&format!(" ({})", annotation));
+    }
+    text
+}
+
+/// Reconstruct the strace line of an entry, e.g. `123 10:00:00 openat(AT_FDCWD, "x", O_RDONLY)
+/// = -1 ENOENT (No such file or directory) <0.000010>`
+pub fn format_entry(entry: &SyscallEntry) -> String {
+    let mut line = format!("{} ", entry.pid);
+    if !entry.timestamp.is_empty() {
+        line.push_str(&entry.timestamp);
+        line.push(' ');
+    }
+
+    if let Some(signal) = &entry.signal {
+        line.push_str(&format!("--- {} ---", signal.details));
+        return line;
+    }
+    if let Some(exit) = &entry.exit_info {
+        line.push_str(&match &exit.signal {
+            Some(signal) => format!("+++ killed by {} +++", signal),
+            None => format!("+++ exited with {} +++", exit.code),
+        });
+        return line;
+    }
+
+    // Resumed arguments keep their closing parenthesis. Unfinished ones lost the trailing comma
+    // there may have been, and output arguments where they were, so this isn't exact for them.
+    let output = entry
+        .output_args
+        .as_ref()
+        .map_or(String::new(), |output| format!(" => {}", output));
+    if entry.is_resumed {
+        line.push_str(&format!(
+            "<... {} resumed>{}{}",
+            entry.syscall_name, output, entry.arguments
+        ));
+    } else {
+        line.push_str(&format!(
+            "{}({}{}",
+            entry.syscall_name, entry.arguments, output
+        ));
+        if entry.is_unfinished {
+            line.push_str(" <unfinished ...>");
+            return line;
+        }
+        line.push(')');
+    }
+
+    if let Some(ret) = &entry.return_value {
+        line.push_str(&format!(" = {}", ret));
+        if let Some(path) = &entry.return_fd_path {
+            line.push_str(&format!("<{}>", path));
+        }
+    }
+    if let Some(errno) = &entry.errno {
+        line.push_str(&format!(" {} ({})", errno.code, errno.message));
+    } else if let Some(annotation) = &entry.return_annotation {
+        line.push_str(&format!(" ({})", annotation));
+    }
+    if let Some(duration) = entry.duration {
+        line.push_str(&format!(" <{:.6}>", duration));
+    }
+    line
+}
+
+/// Value of a `key=value` argument or struct field, or the whole argument if it isn't one
+pub fn argument_value(arg: &str) -> &str {
+    match arg.split_once('=') {
+        Some((key, value))
+            if !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') =>
+        {
+            value
+        }
+        _ => arg,
+    }
+}
+
+/// Members of a struct argument like `{st_mode=S_IFREG|0644, st_size=1234}` (or a `key={...}`
+/// field), as byte ranges into `arg`. None if it isn't a struct.
+pub fn parse_struct_argument(arg: &str) -> Option<Vec<Range<usize>>> {
+    let value = argument_value(arg);
+    let inner = value.strip_prefix('{')?.strip_suffix('}')?;
+    let inner_start = arg.len() - value.len() + 1;
+
+    let members = SyscallArgs::new(inner);
+    Some(
+        (0..members.len())
+            .filter_map(|idx| members.range(idx))
+            .map(|range| inner_start + range.start..inner_start + range.end)
+            .collect(),
+    )
+}
+
+/// Flags of an argument like `O_RDONLY|O_CLOEXEC` (or a `key=A|B` field), as byte ranges into
+/// `arg`. Only splits on `|` outside strings and brackets. None if there is a single flag.
+pub fn parse_flags_argument(arg: &str) -> Option<Vec<Range<usize>>> {
+    let value = argument_value(arg);
+    let value_start = arg.len() - value.len();
+
+    let mut flags = Vec::new();
+    let mut start = 0;
     let mut depth = 0; // Track nesting depth for (), {}, []
     let mut in_string = false;
     let mut escape_next = false;
-
-    for ch in args.chars() {
+    for (idx, ch) in value.char_indices() {
         if escape_next {
-            current.push(ch);
             escape_next = false;
             continue;
         }
-
         match ch {
-            '\\' => {
-                escape_next = true;
-                current.push(ch);
-            }
-            '"' => {
-                in_string = !in_string;
-                current.push(ch);
-            }
-            '(' | '{' | '[' if !in_string => {
-                depth += 1;
-                current.push(ch);
-            }
-            ')' | '}' | ']' if !in_string => {
-                depth -= 1;
-                current.push(ch);
-            }
-            ',' if !in_string && depth == 0 => {
-                // Split point
-                let trimmed = current.trim().to_string();
-                if !trimmed.is_empty() {
-                    result.push(trimmed);
-                }
-                current.clear();
-            }
-            _ => {
-                current.push(ch);
+            '\\' => escape_next = true,
+            '"' => in_string = !in_string,
+            '(' | '{' | '[' if !in_string => depth += 1,
+            ')' | '}' | ']' if !in_string => depth -= 1,
+            '|' if !in_string && depth == 0 => {
+                flags.push(value_start + start..value_start + idx);
+                start = idx + 1;
             }
+            _ => {}
         }
     }
-
-    // Don't forget the last argument
-    let trimmed = current.trim().to_string();
-    if !trimmed.is_empty() {
-        result.push(trimmed);
+    if flags.is_empty() {
+        return None;
     }
+    flags.push(value_start + start..arg.len());
+    Some(flags)
+}
 
-    // If we couldn't parse any arguments, return the whole string
-    if result.is_empty() && !args.trim().is_empty() {
-        result.push(args.trim().to_string());
-    }
+/// Struct members or flags an argument breaks down into, if any
+pub fn argument_members(arg: &str) -> Option<Vec<Range<usize>>> {
+    parse_struct_argument(arg).or_else(|| parse_flags_argument(arg))
+}
 
-    result
+/// Add a line per member of the struct or flag at `arg[range]`, and nest further into members
+/// that break down themselves, while the tree has room for another level
+fn push_argument_fields(
+    lines: &mut Vec<DisplayLine>,
+    entry_idx: usize,
+    arg_idx: usize,
+    arg: &str,
+    range: Range<usize>,
+    parent_prefix: &TreePrefix,
+    parent_is_last: bool,
+) {
+    let nested_base = App::build_nested_prefix(parent_prefix, parent_is_last);
+    if !nested_base.contains(&TreeElement::Null) {
+        return;
+    }
+    let Some(members) = argument_members(&arg[range.clone()]) else {
+        return;
+    };
+
+    for (member_idx, member) in members.iter().enumerate() {
+        let is_last = member_idx == members.len() - 1;
+        let prefix = App::build_tree_prefix(&nested_base, is_last);
+        let field = range.start + member.start..range.start + member.end;
+
+        lines.push(DisplayLine::ArgumentField {
+            entry_idx,
+            arg_idx,
+            field: field.clone(),
+            tree_prefix: prefix,
+            is_search_match: false,
+        });
+        push_argument_fields(lines, entry_idx, arg_idx, arg, field, &prefix, is_last);
+    }
+}
+
+/// Columns left for an argument's text after its tree prefix, keeping a margin of one
+pub fn argument_width(prefix: &TreePrefix, width: usize) -> usize {
+    let prefix_len = App::tree_prefix_to_string(prefix).chars().count();
+    width.saturating_sub(prefix_len + 1).max(1)
+}
+
+/// Split text into rows of at most `width` characters
+pub fn wrap_chunks(text: &str, width: usize) -> Vec<&str> {
+    let mut chunks = Vec::new();
+    let mut rest = text;
+    while !rest.is_empty() {
+        let end = rest
+            .char_indices()
+            .nth(width)
+            .map_or(rest.len(), |(idx, _)| idx);
+        let (chunk, tail) = rest.split_at(end);
+        chunks.push(chunk);
+        rest = tail;
+    }
+    if chunks.is_empty() {
+        chunks.push("");
+    }
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tui::keymap::Keymap;
+
+    fn app_from_trace(trace: &str) -> App {
+        let entries = crate::parser::StraceParser::new()
+            .parse_lines(trace.lines().map(str::to_string), false)
+            .unwrap();
+        let summary = SummaryStats::from_entries(&entries);
+        App::new(entries, summary, None, Config::default())
+    }
+
+    #[test]
+    fn test_min_duration_filter() {
+        let mut app = app_from_trace(
+            "1 10:00:00 read(3, \"\", 1) = 0 <0.000002>\n\
+             1 10:00:00 write(1, \"a\", 1) = 1 <0.000500>\n\
+             1 10:00:00 fsync(1) = 0 <0.020000>\n\
+             1 10:00:00 close(3) = 0\n",
+        );
+        assert_eq!(app.display_lines.len(), 4);
+
+        app.step_min_duration(true); // 1µs, drops the entry without timing
+        assert_eq!(app.display_lines.len(), 3);
+
+        app.step_min_duration(true);
+        app.step_min_duration(true);
+        app.step_min_duration(true); // 1ms
+        assert_eq!(app.min_duration, Some(1e-3));
+        assert_eq!(app.display_lines.len(), 1);
+
+        for _ in 0..4 {
+            app.step_min_duration(false);
+        }
+        assert_eq!(app.min_duration, None);
+        assert_eq!(app.display_lines.len(), 4);
+    }
+
+    #[test]
+    fn test_jump_to_failed_syscall() {
+        let mut app = app_from_trace(
+            "1 10:00:00 open(\"/a\", O_RDONLY) = -1 ENOENT (No such file or directory)\n\
+             1 10:00:00 read(3, \"\", 1) = 0\n\
+             1 10:00:00 access(\"/b\", F_OK) = -1 EACCES (Permission denied)\n\
+             1 10:00:00 close(3) = 0\n\
+             1 10:00:00 close(4) = -1 EBADF (Bad file descriptor)\n",
+        );
+        let entry = |app: &App| app.display_lines[app.selected_line].entry_idx();
+
+        app.handle_event(KeyEvent::from(KeyCode::Char(']')));
+        assert_eq!(entry(&app), 2);
+        app.handle_event(KeyEvent::from(KeyCode::Char(']')));
+        assert_eq!(entry(&app), 4);
+        app.handle_event(KeyEvent::from(KeyCode::Char(']')));
+        assert_eq!(entry(&app), 0); // Wrapped around
+        app.handle_event(KeyEvent::from(KeyCode::Char('[')));
+        assert_eq!(entry(&app), 4);
+
+        // Hidden syscalls are skipped, even when shown
+        app.hidden_syscalls.insert("access".to_string());
+        app.toggle_show_hidden();
+        app.selected_line = 0;
+        app.handle_event(KeyEvent::from(KeyCode::Char(']')));
+        assert_eq!(entry(&app), 4);
+
+        // Nothing to jump to keeps the cursor and says so
+        app.hidden_syscalls
+            .extend(["open".to_string(), "close".to_string()]);
+        app.rebuild_display_lines();
+        app.selected_line = 1;
+        app.handle_event(KeyEvent::from(KeyCode::Char(']')));
+        assert_eq!(app.selected_line, 1);
+        assert!(app.status_message.is_some());
+    }
+
+    #[test]
+    fn test_jump_to_signal_or_exit() {
+        let mut app = app_from_trace(
+            "12345 10:20:30 write(1, \"hello\\n\", 6) = 6\n\
+             12345 10:20:31 --- SIGINT {si_signo=SIGINT, si_code=SI_USER, si_pid=123, si_uid=1000} ---\n\
+             12345 10:20:32 close(1) = 0\n\
+             12345 10:20:33 +++ exited with 0 +++\n",
+        );
+        let entry = |app: &App| app.display_lines[app.selected_line].entry_idx();
+
+        app.handle_event(KeyEvent::from(KeyCode::Char('s')));
+        assert_eq!(entry(&app), 1);
+        assert_eq!(
+            app.entries[1].signal.as_ref().unwrap().signal_name,
+            "SIGINT"
+        );
+        app.handle_event(KeyEvent::from(KeyCode::Char('s')));
+        assert_eq!(entry(&app), 3);
+        assert!(app.entries[3].exit_info.is_some());
+        app.handle_event(KeyEvent::from(KeyCode::Char('S')));
+        assert_eq!(entry(&app), 1);
+        app.handle_event(KeyEvent::from(KeyCode::Char('S')));
+        assert_eq!(entry(&app), 3); // Wrapped around
+    }
+
+    #[test]
+    fn test_jump_between_unfinished_and_resumed() {
+        let mut app = app_from_trace(
+            "1 10:00:00 read(3,  <unfinished ...>\n\
+             2 10:00:00 close(4) = 0\n\
+             1 10:00:01 <... read resumed>\"x\", 1) = 1\n",
+        );
+        assert_eq!(app.entries[0].resumed_entry_idx, Some(2));
+        assert_eq!(app.entries[2].unfinished_entry_idx, Some(0));
+
+        let reference_line = |app: &App| {
+            app.display_lines
+                .iter()
+                .position(|line| matches!(line, DisplayLine::EntryReference { .. }))
+                .unwrap()
+        };
+        let header_of = |app: &App, idx: usize| {
+            app.display_lines.iter().position(
+                |line| matches!(line, DisplayLine::SyscallHeader { entry_idx, .. } if *entry_idx == idx),
+            )
+        };
+
+        // From the unfinished call to its resumption
+        app.toggle_current_line();
+        app.selected_line = reference_line(&app);
+        app.toggle_current_line();
+        assert_eq!(Some(app.selected_line), header_of(&app, 2));
+
+        // And back
+        app.collapse_all();
+        app.selected_line = header_of(&app, 2).unwrap();
+        app.toggle_current_line();
+        app.selected_line = reference_line(&app);
+        app.toggle_current_line();
+        assert_eq!(Some(app.selected_line), header_of(&app, 0));
+    }
+
+    #[test]
+    fn test_sort_by_duration() {
+        let mut app = app_from_trace(
+            "1 10:00:00 read(3, \"\", 1) = 0 <0.000200>\n\
+             1 10:00:00 --- SIGCHLD {si_signo=SIGCHLD} ---\n\
+             1 10:00:00 fsync(1) = 0 <0.020000>\n\
+             1 10:00:00 write(1, \"a\", 1) = 1 <0.000500>\n",
+        );
+        let order = |app: &App| -> Vec<usize> {
+            app.display_lines
+                .iter()
+                .filter(|line| matches!(line, DisplayLine::SyscallHeader { .. }))
+                .map(DisplayLine::entry_idx)
+                .collect()
+        };
+        assert_eq!(order(&app), [0, 1, 2, 3]);
+
+        // The signal has no duration and keeps its place
+        app.handle_event(KeyEvent::from(KeyCode::Char('o')));
+        assert_eq!(app.sort_mode, SortMode::DurationDesc);
+        assert_eq!(order(&app), [2, 1, 3, 0]);
+
+        app.handle_event(KeyEvent::from(KeyCode::Char('o')));
+        assert_eq!(order(&app), [0, 1, 3, 2]);
+
+        // Expanding a sorted entry shows its own details
+        app.selected_line = 2;
+        app.toggle_current_line();
+        let children: Vec<_> = app.display_lines[3..]
+            .iter()
+            .take_while(|line| !matches!(line, DisplayLine::SyscallHeader { .. }))
+            .collect();
+        assert!(!children.is_empty());
+        assert!(children.iter().all(|line| line.entry_idx() == 3));
+        assert!(matches!(
+            app.display_lines[3 + children.len()],
+            DisplayLine::SyscallHeader { entry_idx: 2, .. }
+        ));
+
+        app.handle_event(KeyEvent::from(KeyCode::Char('o')));
+        assert_eq!(app.sort_mode, SortMode::Trace);
+        assert!(app.expanded_items.contains(&3));
+        assert_eq!(app.display_lines[app.selected_line].entry_idx(), 3);
+    }
+
+    #[test]
+    fn test_errno_filter() {
+        let mut app = app_from_trace(
+            "1 10:00:00 open(\"/a\", O_RDONLY) = -1 ENOENT (No such file or directory)\n\
+             1 10:00:00 open(\"/b\", O_RDONLY) = -1 EACCES (Permission denied)\n\
+             1 10:00:00 stat(\"/c\", 0x7ffd) = -1 ENOENT (No such file or directory)\n\
+             1 10:00:00 open(\"/d\", O_RDONLY) = 3\n",
+        );
+        let shown = |app: &App| -> Vec<usize> {
+            app.display_lines
+                .iter()
+                .map(DisplayLine::entry_idx)
+                .collect()
+        };
+        assert_eq!(
+            app.errno_filter_state.codes,
+            [("EACCES".to_string(), 1), ("ENOENT".to_string(), 2)]
+        );
+
+        // Select ENOENT, the second code
+        app.handle_event(KeyEvent::from(KeyCode::Char('E')));
+        app.handle_event(KeyEvent::from(KeyCode::Down));
+        app.handle_event(KeyEvent::from(KeyCode::Enter));
+        assert_eq!(shown(&app), [0, 2]);
+
+        // Composes with hiding syscalls
+        app.hidden_syscalls.insert("stat".to_string());
+        app.rebuild_display_lines();
+        assert_eq!(shown(&app), [0]);
+
+        app.handle_event(KeyEvent::from(KeyCode::Char('a')));
+        assert_eq!(shown(&app), [0, 1, 3]);
+    }
+
+    #[test]
+    fn test_duration_prompt() {
+        let mut app = app_from_trace(
+            "1 10:00:00 read(3, \"\", 1) = 0 <0.000002>\n\
+             1 10:00:00 write(1, \"a\", 1) = 1 <0.000500>\n\
+             1 10:00:00 fsync(1) = 0 <0.020000>\n\
+             1 10:00:00 --- SIGCHLD {si_signo=SIGCHLD} ---\n\
+             1 10:00:00 nanosleep({tv_sec=1, tv_nsec=0}, NULL) = 0 <1.000100>\n",
+        );
+        let shown = |app: &App| -> Vec<usize> {
+            app.display_lines
+                .iter()
+                .map(DisplayLine::entry_idx)
+                .collect()
+        };
+        let type_threshold = |app: &mut App, text: &str| {
+            app.handle_event(KeyEvent::from(KeyCode::Char('>')));
+            for c in text.chars() {
+                app.handle_event(KeyEvent::from(KeyCode::Char(c)));
+            }
+            app.handle_event(KeyEvent::from(KeyCode::Enter));
+        };
+
+        type_threshold(&mut app, "1ms");
+        assert_eq!(app.min_duration, Some(1e-3));
+        assert_eq!(shown(&app), [2, 4]);
+
+        type_threshold(&mut app, "400us");
+        assert_eq!(shown(&app), [1, 2, 4]);
+
+        // Entries without a duration can be kept
+        app.handle_event(KeyEvent::from(KeyCode::Char('>')));
+        app.handle_event(KeyEvent::from(KeyCode::Tab));
+        app.handle_event(KeyEvent::from(KeyCode::Esc));
+        assert!(app.keep_untimed);
+        assert_eq!(shown(&app), [1, 2, 3, 4]);
+
+        // A bad value keeps the threshold, and an empty one clears it
+        type_threshold(&mut app, "fast");
+        assert_eq!(app.min_duration, Some(4e-4));
+        assert!(app.status_message.is_some());
+        type_threshold(&mut app, "");
+        assert_eq!(app.min_duration, None);
+        assert_eq!(shown(&app), [0, 1, 2, 3, 4]);
+
+        assert_eq!(parse_duration_input("0.5s"), Some(0.5));
+        assert_eq!(parse_duration_input("2"), Some(2.0));
+        assert_eq!(parse_duration_input("10 ns"), Some(1e-8));
+        assert_eq!(parse_duration_input("1h"), None);
+    }
+
+    #[test]
+    fn test_search_scope() {
+        let mut app = app_from_trace(
+            "1 10:00:00 openat(AT_FDCWD, \"/usr/lib/libfoo.so\", O_RDONLY) = 3\n\
+             \x20> /usr/lib/libfoo.so(foo_open+0x14) [0x10e53e]\n\
+             1 10:00:00 read(3, \"\", 1) = 0\n\
+             \x20> /usr/lib/libfoo.so(foo_read+0x14) [0x10e600]\n",
+        );
+        app.expanded_items.extend([0, 1]);
+        app.expanded_arguments.extend([0, 1]);
+        app.expanded_backtraces.extend([0, 1]);
+        app.rebuild_display_lines();
+
+        let matched = |app: &mut App| -> Vec<&'static str> {
+            app.update_search_matches();
+            app.search_state
+                .matches
+                .iter()
+                .map(|&idx| match app.display_lines[idx] {
+                    DisplayLine::SyscallHeader { .. } => "header",
+                    DisplayLine::ArgumentLine { .. } => "argument",
+                    DisplayLine::BacktraceFrame { .. } => "frame",
+                    _ => "other",
+                })
+                .collect()
+        };
+
+        app.start_search();
+        for c in "libfoo".chars() {
+            app.handle_event(KeyEvent::from(KeyCode::Char(c)));
+        }
+        assert_eq!(matched(&mut app), ["header", "argument", "frame", "frame"]);
+
+        app.handle_event(KeyEvent::from(KeyCode::Tab));
+        assert_eq!(app.search_state.scope, SearchScope::Arguments);
+        assert_eq!(matched(&mut app), ["header", "argument"]);
+
+        app.handle_event(KeyEvent::from(KeyCode::Tab));
+        assert_eq!(matched(&mut app), ["frame", "frame"]);
+    }
+
+    #[test]
+    fn test_mouse_click_expands_header() {
+        let mut app = app_from_trace(
+            "1 10:00:00 getpid() = 1\n\
+             1 10:00:00 openat(AT_FDCWD, \"/etc/passwd\", O_RDONLY) = 3\n\
+             1 10:00:00 close(3) = 0\n",
+        );
+        app.update_list_area(Rect::new(0, 2, 80, 10));
+
+        let click = |column, row| MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        };
+
+        // Second line of the list, below the two rows of header
+        app.handle_mouse_event(click(5, 3));
+        assert_eq!(app.selected_line, 1);
+        assert!(app.expanded_items.contains(&1));
+        assert!(matches!(
+            app.display_lines[2],
+            DisplayLine::ArgumentsHeader { entry_idx: 1, .. }
+        ));
+
+        // Clicking outside the list does nothing
+        app.handle_mouse_event(click(5, 0));
+        assert_eq!(app.selected_line, 1);
+
+        // Clicking the header again collapses it
+        app.handle_mouse_event(click(5, 3));
+        assert!(!app.expanded_items.contains(&1));
+    }
+
+    #[test]
+    fn test_mouse_scroll_after_list_shrinks() {
+        let trace = "1 10:00:00 getpid() = 1\n".repeat(30) + "1 10:00:00 close(3) = 0\n";
+        let mut app = app_from_trace(&trace);
+        app.update_list_area(Rect::new(0, 0, 80, 5));
+
+        // Hiding getpid leaves a single line, and the scroll is past it until a draw corrects it
+        app.hidden_syscalls.insert("getpid".to_string());
+        app.rebuild_display_lines();
+        app.scroll_offset = 24;
+        let scroll = |up| MouseEvent {
+            kind: if up {
+                MouseEventKind::ScrollUp
+            } else {
+                MouseEventKind::ScrollDown
+            },
+            column: 5,
+            row: 2,
+            modifiers: KeyModifiers::NONE,
+        };
+        app.handle_mouse_event(scroll(true));
+        assert_eq!((app.scroll_offset, app.selected_line), (0, 0));
+        app.handle_mouse_event(scroll(false));
+        assert_eq!((app.scroll_offset, app.selected_line), (0, 0));
+    }
+
+    #[test]
+    fn test_wrap_long_argument() {
+        let path = "/very/long/path/".repeat(8);
+        let mut app = app_from_trace(&format!(
+            "1 10:00:00 openat(AT_FDCWD, \"{}\", O_RDONLY) = 3\n",
+            path
+        ));
+        app.expanded_items.insert(0);
+        app.expanded_arguments.insert(0);
+        app.update_list_area(Rect::new(0, 0, 40, 20));
+        app.rebuild_display_lines();
+
+        let rows_of = |app: &App, arg: usize| {
+            app.display_lines
+                .iter()
+                .filter(|line| {
+                    matches!(line, DisplayLine::ArgumentLine { arg_idx, .. } if *arg_idx == arg)
+                })
+                .count()
+        };
+        assert_eq!(rows_of(&app, 1), 1);
+
+        app.handle_event(KeyEvent::from(KeyCode::Char('W')));
+        let rows = rows_of(&app, 1);
+        assert!(rows > 1, "expected the path to wrap, got {} rows", rows);
+        assert_eq!(rows_of(&app, 0), 1);
+        assert_eq!(rows_of(&app, 2), 1);
+
+        // A narrower list wraps into more rows
+        app.update_list_area(Rect::new(0, 0, 30, 20));
+        assert!(rows_of(&app, 1) > rows);
+
+        app.handle_event(KeyEvent::from(KeyCode::Char('W')));
+        assert_eq!(rows_of(&app, 1), 1);
+    }
+
+    #[test]
+    fn test_wrap_chunks() {
+        assert_eq!(wrap_chunks("abcdefg", 3), ["abc", "def", "g"]);
+        assert_eq!(wrap_chunks("äöü", 2), ["äö", "ü"]);
+        assert_eq!(wrap_chunks("", 2), [""]);
+    }
+
+    #[test]
+    fn test_parse_struct_argument() {
+        let arg = "{st_mode=S_IFREG|0644, st_size=1234, st_atime={tv_sec=1, tv_nsec=2}, ...}";
+        let members: Vec<&str> = parse_struct_argument(arg)
+            .unwrap()
+            .into_iter()
+            .map(|range| &arg[range])
+            .collect();
+        assert_eq!(
+            members,
+            [
+                "st_mode=S_IFREG|0644",
+                "st_size=1234",
+                "st_atime={tv_sec=1, tv_nsec=2}",
+                "..."
+            ]
+        );
+
+        // A `key={...}` field is a struct too
+        let field = "st_atime={tv_sec=1, tv_nsec=2}";
+        let members: Vec<&str> = parse_struct_argument(field)
+            .unwrap()
+            .into_iter()
+            .map(|range| &field[range])
+            .collect();
+        assert_eq!(members, ["tv_sec=1", "tv_nsec=2"]);
+
+        assert!(parse_struct_argument("\"/etc/passwd\"").is_none());
+        assert!(parse_struct_argument("[{a=1}]").is_none());
+    }
+
+    #[test]
+    fn test_parse_flags_argument() {
+        let flags = |arg: &'static str| -> Option<Vec<&'static str>> {
+            parse_flags_argument(arg).map(|ranges| ranges.into_iter().map(|r| &arg[r]).collect())
+        };
+
+        assert_eq!(
+            flags("O_RDONLY|O_CLOEXEC|O_NONBLOCK").unwrap(),
+            ["O_RDONLY", "O_CLOEXEC", "O_NONBLOCK"]
+        );
+        assert_eq!(flags("st_mode=S_IFREG|0644").unwrap(), ["S_IFREG", "0644"]);
+        assert_eq!(flags("O_RDONLY"), None);
+
+        // Not inside strings or brackets
+        assert_eq!(flags("\"a|b\""), None);
+        assert_eq!(flags("{a=1|2}"), None);
+        assert_eq!(flags("[SIGINT|SIGTERM]"), None);
+        assert_eq!(flags("\"a|\\\"|b\"|X").unwrap(), ["\"a|\\\"|b\"", "X"]);
+    }
+
+    #[test]
+    fn test_flags_argument_fields() {
+        let mut app =
+            app_from_trace("1 10:00:00 openat(AT_FDCWD, \"x|y\", O_RDONLY|O_CLOEXEC) = 3\n");
+        app.expanded_items.insert(0);
+        app.expanded_arguments.insert(0);
+        app.rebuild_display_lines();
+
+        let children: Vec<(usize, String)> = app
+            .display_lines
+            .iter()
+            .filter_map(|line| match line {
+                DisplayLine::ArgumentField { arg_idx, .. } => {
+                    Some((*arg_idx, app.get_line_text(line)))
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            children,
+            [(2, "O_RDONLY".to_string()), (2, "O_CLOEXEC".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_struct_argument_fields() {
+        let mut app = app_from_trace(
+            "1 10:00:00 fstat(3, {st_mode=S_IFREG|0644, st_size=1234, \
+             st_atim={tv_sec=1, tv_nsec={a=1}}, ...}) = 0\n",
+        );
+        app.expanded_items.insert(0);
+        app.expanded_arguments.insert(0);
+        app.rebuild_display_lines();
+
+        let fields: Vec<(String, String)> = app
+            .display_lines
+            .iter()
+            .filter(|line| matches!(line, DisplayLine::ArgumentField { .. }))
+            .map(|line| {
+                let DisplayLine::ArgumentField { tree_prefix, .. } = line else {
+                    unreachable!()
+                };
+                (
+                    App::tree_prefix_to_string(tree_prefix),
+                    app.get_line_text(line),
+                )
+            })
+            .collect();
+
+        let texts: Vec<&str> = fields.iter().map(|(_, text)| text.as_str()).collect();
+        assert_eq!(
+            texts,
+            [
+                "st_mode=S_IFREG|0644",
+                "S_IFREG",
+                "0644",
+                "st_size=1234",
+                "st_atim={tv_sec=1, tv_nsec={a=1}}",
+                "tv_sec=1",
+                "tv_nsec={a=1}", // The tree is full, so this one isn't expanded
+                "...",
+            ]
+        );
+
+        // Members nest one level deeper than their struct, and the last one closes the branch
+        assert_eq!(fields[0].0, "  │     ├─ ");
+        assert_eq!(fields[5].0, "  │     │  ├─ ");
+        assert_eq!(fields[7].0, "  │     └─ ");
+
+        // The struct's own line comes before its members
+        let arg_line = app
+            .display_lines
+            .iter()
+            .position(|line| matches!(line, DisplayLine::ArgumentLine { arg_idx: 1, .. }))
+            .unwrap();
+        assert!(matches!(
+            app.display_lines[arg_line + 1],
+            DisplayLine::ArgumentField { arg_idx: 1, .. }
+        ));
+    }
+
+    #[test]
+    fn test_process_tree_jump() {
+        let mut app = app_from_trace(
+            "100 10:20:30 clone(child_stack=NULL, flags=SIGCHLD) = 200\n\
+             100 10:20:30 getpid() = 100\n\
+             200 10:20:30 getpid() = 200\n\
+             200 10:20:31 exit_group(0) = ?\n",
+        );
+        app.handle_event(KeyEvent::from(KeyCode::End));
+        app.handle_event(KeyEvent::from(KeyCode::Char('t')));
+        assert!(app.show_process_tree);
+
+        // Keys move within the panel, not the list
+        app.handle_event(KeyEvent::from(KeyCode::Down));
+        assert_eq!(app.process_tree_state.rows[1].pid, 200);
+        assert_eq!(app.selected_line, 3);
+
+        // The child first shows up as the clone's return value
+        app.handle_event(KeyEvent::from(KeyCode::Enter));
+        assert!(app.show_process_tree);
+        assert_eq!(app.selected_line, 0);
+
+        app.handle_event(KeyEvent::from(KeyCode::Esc));
+        assert!(!app.show_process_tree);
+    }
+
+    #[test]
+    fn test_format_entry() {
+        let trace = "123 10:00:00 openat(AT_FDCWD, \"x\", O_RDONLY) = -1 ENOENT (No such file or directory) <0.000010>\n\
+                     123 10:00:00 fcntl(3, F_GETFL) = 0x8002 (flags O_RDWR|O_LARGEFILE)\n\
+                     123 10:00:00 ioctl(3, SIOCGIFNAME, {ifr_index=2} => {ifr_name=\"eth0\"}) = 0\n\
+                     123 10:00:00 futex(0x7f00, FUTEX_WAIT_PRIVATE, 0, NULL <unfinished ...>\n\
+                     123 10:00:00 <... futex resumed>) = 0\n\
+                     123 10:00:00 --- SIGCHLD {si_signo=SIGCHLD, si_code=CLD_EXITED} ---\n\
+                     123 10:00:00 +++ exited with 0 +++\n";
+        let app = app_from_trace(trace);
+        let lines: Vec<String> = app.entries.iter().map(format_entry).collect();
+        assert_eq!(lines, trace.lines().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_copy_entry_text() {
+        let mut app = app_from_trace(
+            "123 10:00:00 openat(AT_FDCWD, \"x\", O_RDONLY) = 3\n\
+             123 10:00:00 close(3) = 0\n",
+        );
+        assert_eq!(
+            app.entry_text(0),
+            "123 10:00:00 openat(AT_FDCWD, \"x\", O_RDONLY) = 3"
+        );
+
+        // Expanded, the subtree comes along as shown
+        app.handle_event(KeyEvent::from(KeyCode::Enter));
+        let text = app.entry_text(0);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(
+            lines[0],
+            "123 10:00:00 openat(AT_FDCWD, \"x\", O_RDONLY) = 3"
+        );
+        assert!(lines[1..].iter().any(|line| line.ends_with("Return: 3")));
+        assert_eq!(app.entry_text(1), "123 10:00:00 close(3) = 0");
+    }
+
+    #[test]
+    fn test_follow_keeps_cursor_at_bottom() {
+        use std::io::Write;
+
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        let mut app = app_from_trace("");
+        app.follow = Some(Follow {
+            source: crate::tui::LineSource::File(
+                crate::tui::FileTail::open(file.path().to_str().unwrap()).unwrap(),
+            ),
+            parser: crate::parser::StraceParser::new(),
+            merge_resumed: false,
+        });
+        let mut append = |app: &mut App, text: &str| {
+            file.write_all(text.as_bytes()).unwrap();
+            file.flush().unwrap();
+            app.follow_refreshed = None;
+            app.poll_follow();
+        };
+
+        append(
+            &mut app,
+            "1 10:00:00 open(\"a\", O_RDONLY) = 3\n1 10:00:00 read(3, \"\", 1) = 0\n",
+        );
+        assert_eq!(app.entries.len(), 2);
+        assert_eq!(app.summary.total_syscalls, 2);
+        assert_eq!(app.selected_line, 1);
+
+        append(&mut app, "1 10:00:00 close(3) = 0\n");
+        assert_eq!(app.selected_line, 2);
+
+        // Once scrolled up, new entries don't move the cursor
+        app.handle_event(KeyEvent::from(KeyCode::Char('k')));
+        append(&mut app, "1 10:00:00 exit_group(0) = ?\n");
+        assert_eq!(app.display_lines.len(), 4);
+        assert_eq!(app.selected_line, 1);
+
+        // Right after a slow refresh, new entries are parsed but shown by a later poll
+        app.follow_refreshed = Some((Instant::now(), Duration::from_secs(60)));
+        file.write_all(b"1 10:00:01 getpid() = 1\n").unwrap();
+        file.flush().unwrap();
+        app.poll_follow();
+        assert_eq!(app.entries.len(), 5);
+        assert_eq!(app.display_lines.len(), 4);
+        app.follow_refreshed = None;
+        app.poll_follow();
+        assert_eq!(app.display_lines.len(), 5);
+        assert_eq!(app.summary.total_syscalls, 5);
+    }
+
+    #[test]
+    fn test_jump_to_entry_number() {
+        let mut app = app_from_trace(
+            "1 10:00:00 open(\"a\", O_RDONLY) = 3\n\
+             1 10:00:00 futex(0x1, FUTEX_WAIT, 0 <unfinished ...>\n\
+             2 10:00:00 close(3) = 0\n\
+             1 10:00:01 <... futex resumed>) = 0\n",
+        );
+        let type_keys = |app: &mut App, keys: &str| {
+            for c in keys.chars() {
+                app.handle_event(KeyEvent::from(KeyCode::Char(c)));
+            }
+        };
+
+        // The resumed half names the unfinished one by its number
+        app.selected_line = app.display_lines.len() - 1;
+        app.toggle_current_line();
+        let reference = app
+            .display_lines
+            .iter()
+            .map(|line| app.get_line_text(line))
+            .find(|text| text.starts_with("Resumed from entry #"))
+            .unwrap();
+        let number = reference.trim_start_matches("Resumed from entry #");
+
+        type_keys(&mut app, ":");
+        type_keys(&mut app, number);
+        app.handle_event(KeyEvent::from(KeyCode::Enter));
+        assert!(app.jump_prompt.is_none());
+        assert!(matches!(
+            app.display_lines[app.selected_line],
+            DisplayLine::SyscallHeader { entry_idx: 1, .. }
+        ));
+
+        type_keys(&mut app, ":3");
+        app.handle_event(KeyEvent::from(KeyCode::Enter));
+        assert!(matches!(
+            app.display_lines[app.selected_line],
+            DisplayLine::SyscallHeader { entry_idx: 2, .. }
+        ));
+
+        type_keys(&mut app, ":9");
+        app.handle_event(KeyEvent::from(KeyCode::Enter));
+        assert_eq!(app.status_message.as_deref(), Some("No entry #9"));
+
+        // Numbers take the digits of the last entry, and a space
+        assert_eq!(app.entry_number_width(), 0);
+        type_keys(&mut app, "#");
+        assert_eq!(app.entry_number_width(), 2);
+    }
+
+    #[test]
+    fn test_bookmarks() {
+        let mut app = app_from_trace(
+            "1 10:00:00 open(\"a\", O_RDONLY) = 3\n\
+             1 10:00:00 read(3, \"\", 1) = 0\n\
+             1 10:00:00 write(1, \"a\", 1) = 1\n\
+             1 10:00:00 close(3) = 0\n",
+        );
+        let entry = |app: &App| app.display_lines[app.selected_line].entry_idx();
+        let press = |app: &mut App, c: char| app.handle_event(KeyEvent::from(KeyCode::Char(c)));
+
+        press(&mut app, 'b');
+        assert!(app.status_message.is_some());
+
+        // Bookmark from a line under the header, then another entry
+        app.selected_line = 1;
+        app.toggle_current_line();
+        app.selected_line = 2;
+        press(&mut app, 'm');
+        app.selected_line = app.display_lines.len() - 1;
+        press(&mut app, 'm');
+        assert_eq!(app.bookmarks, HashSet::from([1, 3]));
+
+        // They are kept by entry, across rebuilds that move the lines
+        app.collapse_all();
+        app.hidden_syscalls.insert("write".to_string());
+        app.rebuild_display_lines();
+        app.selected_line = 0;
+        press(&mut app, 'b');
+        assert_eq!(entry(&app), 1);
+        press(&mut app, 'b');
+        assert_eq!(entry(&app), 3);
+        press(&mut app, 'b');
+        assert_eq!(entry(&app), 1); // Wrapped around
+        press(&mut app, 'B');
+        assert_eq!(entry(&app), 3);
+
+        // Toggling again removes the bookmark
+        press(&mut app, 'm');
+        assert_eq!(app.bookmarks, HashSet::from([1]));
+        press(&mut app, 'b');
+        assert_eq!(entry(&app), 1);
+    }
+
+    #[test]
+    fn test_collapse_process() {
+        let mut app = app_from_trace(
+            "1 10:00:00 open(\"a\", O_RDONLY) = 3\n\
+             2 10:00:00 read(3, \"\", 1) = 0\n\
+             1 10:00:00 write(1, \"a\", 1) = 1\n\
+             2 10:00:00 close(3) = 0\n\
+             2 10:00:00 close(4) = 0\n",
+        );
+        let press = |app: &mut App, c: char| app.handle_event(KeyEvent::from(KeyCode::Char(c)));
+
+        // Folding from the last entry of the process puts the cursor on its placeholder, which
+        // stands where its first entry was
+        app.selected_line = 4;
+        press(&mut app, 'z');
+        assert_eq!(app.display_lines.len(), 3);
+        assert!(matches!(
+            app.display_lines[1],
+            DisplayLine::CollapsedProcess {
+                entry_idx: 1,
+                pid: 2,
+                count: 3,
+                ..
+            }
+        ));
+        assert_eq!(app.selected_line, 1);
+        assert_eq!(
+            app.get_line_text(&app.display_lines[1]),
+            "[2] 3 syscalls collapsed"
+        );
+
+        // Filtered entries aren't counted
+        app.hidden_syscalls.insert("close".to_string());
+        app.rebuild_display_lines();
+        assert!(matches!(
+            app.display_lines[1],
+            DisplayLine::CollapsedProcess { count: 1, .. }
+        ));
+        app.hidden_syscalls.clear();
+
+        // Enter on the placeholder brings the entries back
+        app.selected_line = 1;
+        app.toggle_current_line();
+        assert!(app.collapsed_pids.is_empty());
+        let headers: Vec<usize> = app
+            .display_lines
+            .iter()
+            .map(DisplayLine::entry_idx)
+            .collect();
+        assert_eq!(headers, [0, 1, 2, 3, 4]);
+        assert_eq!(app.display_lines[app.selected_line].entry_idx(), 1);
+    }
+
+    #[test]
+    fn test_histogram_of_syscall_under_cursor() {
+        let mut app = app_from_trace(
+            "1 10:00:00 read(3, \"\", 1) = 0 <0.000005>\n\
+             1 10:00:00 write(1, \"a\", 1) = 1 <0.002000>\n\
+             1 10:00:00 read(3, \"\", 1) = 0 <0.000500>\n",
+        );
+        let total = |app: &App| -> usize {
+            app.histogram_state
+                .buckets
+                .iter()
+                .map(|bucket| bucket.count)
+                .sum()
+        };
+
+        app.handle_event(KeyEvent::from(KeyCode::Char('D')));
+        assert!(app.show_histogram);
+        assert_eq!(total(&app), 3);
+
+        app.handle_event(KeyEvent::from(KeyCode::Tab));
+        assert_eq!(app.histogram_state.syscall.as_deref(), Some("read"));
+        assert_eq!(total(&app), 2);
+        app.handle_event(KeyEvent::from(KeyCode::Tab));
+        assert_eq!(total(&app), 3);
+
+        app.handle_event(KeyEvent::from(KeyCode::Esc));
+        assert!(!app.show_histogram);
+    }
+
+    #[test]
+    fn test_restore_session() {
+        let trace = "1 10:00:00 open(\"/etc/passwd\", O_RDONLY) = 3\n\
+                     1 10:00:00 read(3, \"\", 1) = 0\n\
+                     1 10:00:00 close(3) = 0\n";
+        let mut app = app_from_trace(trace);
+        app.hidden_syscalls.insert("read".to_string());
+        app.expanded_items.insert(2);
+        app.notes.insert(2, "closes the fd of the open".to_string());
+        app.rebuild_display_lines();
+        app.bookmarks.insert(0);
+        app.search_state.query = "passwd".to_string();
+        app.selected_line = 2;
+        let session = app.session();
+        assert_eq!(session.notes[&3], "closes the fd of the open");
+        assert_eq!(session.bookmarks, HashSet::from([1]));
+
+        let mut restored = app_from_trace(trace);
+        restored.restore_session(session.clone());
+        assert_eq!(restored.session(), session);
+        assert_eq!(restored.display_lines.len(), app.display_lines.len());
+        assert_eq!(restored.search_state.matches, [0]);
+
+        // A session from another trace can't select lines or entries past the end
+        let mut short = app_from_trace("1 10:00:00 close(3) = 0\n");
+        short.restore_session(session);
+        assert!(short.expanded_items.is_empty());
+        assert!(short.bookmarks.contains(&0));
+        assert!(short.notes.is_empty());
+        assert_eq!(short.selected_line, 0);
+    }
+
+    #[test]
+    fn test_restore_session_merged_differently() {
+        let trace = "1 10:00:00 read(3, <unfinished ...>\n\
+                     2 10:00:00 write(1, \"x\", 1) = 1\n\
+                     1 10:00:00 <... read resumed>\"\", 1) = 0\n\
+                     1 10:00:00 close(3) = 0\n";
+        let mut app = app_from_trace(trace);
+        app.bookmarks.insert(3);
+        app.expanded_items.insert(1);
+        app.notes.insert(3, "after the read".to_string());
+        let session = app.session();
+
+        // Merged, the resumed line is no entry of its own, so the others move
+        let entries = crate::parser::StraceParser::new()
+            .parse_lines(trace.lines().map(str::to_string), true)
+            .unwrap();
+        let summary = SummaryStats::from_entries(&entries);
+        let mut merged = App::new(entries, summary, None, Config::default());
+        merged.restore_session(session.clone());
+        assert_eq!(merged.bookmarks, HashSet::from([2]));
+        assert_eq!(merged.expanded_items, HashSet::from([1]));
+        assert_eq!(merged.notes[&2], "after the read");
+        assert_eq!(merged.entries[2].syscall_name, "close");
+        assert_eq!(merged.session(), session);
+    }
+
+    #[test]
+    fn test_resolve_current_backtrace() {
+        // The test binary has line info, and an address that resolves is in its .text
+        let exe = std::env::current_exe().unwrap();
+        let exe = exe.to_str().unwrap();
+        let text = addr2line::Loader::new(exe)
+            .unwrap()
+            .get_section_range(b".text")
+            .unwrap();
+        let mut resolver = Addr2LineResolver::new();
+        let address = (text.begin..text.end)
+            .step_by(0x10)
+            .map(|address| format!("{:#x}", address))
+            .find(|address| {
+                let mut frame = crate::parser::BacktraceFrame {
+                    binary: exe.to_string(),
+                    function: None,
+                    offset: None,
+                    address: address.clone(),
+                    resolved: None,
+                };
+                resolver.resolve_frame(&mut frame).unwrap();
+                frame.resolved.is_some()
+            })
+            .unwrap();
+
+        let mut app = app_from_trace(&format!(
+            "1 10:00:00 getpid() = 1\n\
+             1 10:00:00 write(1, \"x\", 1) = 1\n > {}() [{}]\n",
+            exe, address
+        ));
+        app.handle_event(KeyEvent::from(KeyCode::Char('r')));
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some("No backtrace to resolve")
+        );
+
+        app.selected_line = 1;
+        app.handle_event(KeyEvent::from(KeyCode::Char('r')));
+        while app.resolving.is_some() {
+            std::thread::sleep(Duration::from_millis(10));
+            app.poll_resolve();
+        }
+        assert!(app.entries[1].backtrace[0].resolved.is_some());
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some("Resolved 1 of 1 frames")
+        );
+
+        // Expanded, the resolved frames are shown instead of the raw one
+        app.expanded_items.insert(1);
+        app.expanded_backtraces.insert(1);
+        app.rebuild_display_lines();
+        assert!(
+            app.display_lines
+                .iter()
+                .any(|line| matches!(line, DisplayLine::BacktraceResolved { .. }))
+        );
+    }
+
+    #[test]
+    fn test_return_annotation_line() {
+        let mut app = app_from_trace(
+            "1 10:00:00 poll([{fd=3, events=POLLIN}], 1, 100) = 0 (Timeout)\n\
+             1 10:00:00 socket(AF_NETLINK, SOCK_RAW, NETLINK_ROUTE) = 3 (NETLINK_ROUTE)\n",
+        );
+        app.toggle_current_line();
+        app.selected_line = app.display_lines.len() - 1;
+        app.toggle_current_line();
+
+        let returns: Vec<String> = app
+            .display_lines
+            .iter()
+            .filter(|line| matches!(line, DisplayLine::ReturnValue { .. }))
+            .map(|line| app.get_line_text(line))
+            .collect();
+        assert_eq!(
+            returns,
+            ["Return: 0 (Timeout)", "Return: 3 (NETLINK_ROUTE)"]
+        );
+    }
+
+    #[test]
+    fn test_vim_counts() {
+        let trace: String = (0..30)
+            .map(|i| format!("1 10:00:00 getpid() = {}\n", i))
+            .collect();
+        let mut app = app_from_trace(&trace);
+        let press = |app: &mut App, c: char| app.handle_event(KeyEvent::from(KeyCode::Char(c)));
+
+        press(&mut app, '1');
+        press(&mut app, '0');
+        press(&mut app, 'j');
+        assert_eq!(app.selected_line, 10);
+        assert_eq!(app.pending_count, None);
+
+        press(&mut app, '3');
+        press(&mut app, 'k');
+        assert_eq!(app.selected_line, 7);
+        press(&mut app, 'j');
+        assert_eq!(app.selected_line, 8);
+
+        // A count before G or gg is an entry number
+        press(&mut app, '2');
+        press(&mut app, '5');
+        press(&mut app, 'G');
+        assert_eq!(app.selected_line, 24);
+        press(&mut app, '2');
+        press(&mut app, 'g');
+        press(&mut app, 'g');
+        assert_eq!(app.selected_line, 1);
+
+        press(&mut app, 'G');
+        assert_eq!(app.selected_line, 29);
+        press(&mut app, 'g');
+        assert_eq!(app.selected_line, 29);
+        press(&mut app, 'g');
+        assert_eq!(app.selected_line, 0);
+
+        // A huge count stops at the last line at once
+        for c in "99999999999999999999999".chars() {
+            press(&mut app, c);
+        }
+        press(&mut app, 'j');
+        assert_eq!(app.selected_line, 29);
+        for c in "99999999999".chars() {
+            press(&mut app, c);
+        }
+        press(&mut app, 'k');
+        assert_eq!(app.selected_line, 0);
+
+        // Digits typed in a search are text, not counts
+        press(&mut app, '/');
+        press(&mut app, '4');
+        assert_eq!(app.search_state.query, "4");
+        assert_eq!(app.pending_count, None);
+    }
+
+    #[test]
+    fn test_keymap_replaces_default_keys() {
+        let mut app = app_from_trace("1 10:00:00 getpid() = 1\n");
+        app.config.keys = Keymap::parse("quit = \"Q\"").unwrap();
+
+        app.handle_event(KeyEvent::from(KeyCode::Char('q')));
+        assert!(!app.should_quit);
+        app.handle_event(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL));
+        assert!(!app.should_quit);
+
+        app.handle_event(KeyEvent::new(KeyCode::Char('Q'), KeyModifiers::SHIFT));
+        assert!(app.should_quit);
+    }
+
+    #[test]
+    fn test_quit_warns_about_unsaved_notes() {
+        // Read from no file, there is no session to save the note in
+        let mut app = app_from_trace("1 10:00:00 getpid() = 1\n");
+        app.notes.insert(0, "why?".to_string());
+
+        app.handle_event(KeyEvent::from(KeyCode::Char('q')));
+        assert!(!app.should_quit);
+        assert!(app.status_message.as_ref().unwrap().contains("Notes"));
+        app.handle_event(KeyEvent::from(KeyCode::Char('q')));
+        assert!(app.should_quit);
+    }
+
+    #[test]
+    fn test_parse_errors_modal() {
+        let trace = "1 10:00:00 getpid() = 1\n\
+                     1 10:00:00 close(1) = 0\n >\n\
+                     1 10:00:00 getppid() = 0\n";
+        let mut parser = crate::parser::StraceParser::new();
+        let entries = parser
+            .parse_lines(trace.lines().map(str::to_string), false)
+            .unwrap();
+        let summary = SummaryStats::from_entries(&entries);
+        let mut app = App::new(entries, summary, None, Config::default());
+        app.parse_errors = parser.take_errors();
+
+        app.handle_event(KeyEvent::from(KeyCode::Char('!')));
+        assert!(app.show_parse_errors);
+        assert_eq!(app.parse_errors.len(), 1);
+        assert_eq!(app.parse_errors[0].line_number, 3);
+        assert_eq!(app.parse_errors[0].raw_line.as_deref(), Some(" >"));
+
+        // Enter goes to the entry the bad line followed
+        app.handle_event(KeyEvent::from(KeyCode::Enter));
+        assert!(!app.show_parse_errors);
+        assert_eq!(app.selected_line, 1);
+
+        let mut app = app_from_trace("1 10:00:00 getpid() = 1\n");
+        app.handle_event(KeyEvent::from(KeyCode::Char('!')));
+        assert!(!app.show_parse_errors);
+    }
+
+    #[test]
+    fn test_max_arguments() {
+        let args: Vec<String> = (0..50).map(|i| i.to_string()).collect();
+        let mut app = app_from_trace(&format!(
+            "1 10:00:00 io_uring_enter({}) = 0\n",
+            args.join(", ")
+        ));
+        app.toggle_current_line();
+        app.selected_line = 1;
+        app.toggle_current_line();
+
+        let count_arguments = |app: &App| {
+            app.display_lines
+                .iter()
+                .filter(|line| matches!(line, DisplayLine::ArgumentLine { .. }))
+                .count()
+        };
+        assert_eq!(count_arguments(&app), 20);
+        let more = app
+            .display_lines
+            .iter()
+            .position(|line| matches!(line, DisplayLine::MoreArguments { count: 30, .. }))
+            .unwrap();
+        assert_eq!(more, 2 + 20);
+        assert_eq!(
+            app.get_line_text(&app.display_lines[more]),
+            "(+30 more, Enter to show all)"
+        );
+
+        app.selected_line = more;
+        app.toggle_current_line();
+        assert_eq!(count_arguments(&app), 50);
+        assert!(
+            !app.display_lines
+                .iter()
+                .any(|line| matches!(line, DisplayLine::MoreArguments { .. }))
+        );
+    }
+
+    #[test]
+    fn test_no_backtraces_fast_path() {
+        let trace = "1 10:00:00 openat(AT_FDCWD, \"/etc/passwd\", O_RDONLY) = 3 <0.000010>\n \
+                     > /usr/lib/libc.so.6(open64+0x5b) [0x10e5d4]\n\
+                     1 10:00:01 read(3, \"root\", 4) = 4\n \
+                     > /usr/lib/libc.so.6(read+0x14) [0x10e6d4]\n\
+                     1 10:00:02 close(3) = -1 EBADF (Bad file descriptor)\n";
+        let without_frames: String = trace
+            .lines()
+            .filter(|line| !line.trim_start().starts_with('>'))
+            .map(|line| format!("{}\n", line))
+            .collect();
+
+        let mut fast = app_from_trace(&without_frames);
+        assert!(!fast.has_backtraces);
+        fast.handle_event(KeyEvent::from(KeyCode::Char('e')));
+
+        let mut app = app_from_trace(trace);
+        assert!(app.has_backtraces);
+        app.handle_event(KeyEvent::from(KeyCode::Char('e')));
+        let has_frames = |app: &App| {
+            app.display_lines
+                .iter()
+                .any(|line| matches!(line, DisplayLine::BacktraceHeader { .. }))
+        };
+        assert!(has_frames(&app));
+
+        // Hiding the frames of a trace that has them shows what the skipped path does
+        app.handle_event(KeyEvent::from(KeyCode::Char('F')));
+        assert!(!has_frames(&app));
+        assert_eq!(
+            format!("{:?}", app.display_lines),
+            format!("{:?}", fast.display_lines)
+        );
+    }
+
+    #[test]
+    fn test_notes() {
+        let mut app = app_from_trace(
+            "1 10:00:00 close(3) = 0\n\
+             1 10:00:01 close(3) = -1 EBADF (Bad file descriptor)\n",
+        );
+        app.selected_line = 1;
+        app.handle_event(KeyEvent::from(KeyCode::Char('a')));
+        for c in "double close".chars() {
+            app.handle_event(KeyEvent::from(KeyCode::Char(c)));
+        }
+        app.handle_event(KeyEvent::from(KeyCode::Enter));
+        assert!(app.note_prompt.is_none());
+        assert_eq!(app.notes[&1], "double close");
+
+        // Shown under the entry while collapsed, and first among its items when expanded
+        let note_line = |app: &App| {
+            app.display_lines
+                .iter()
+                .position(|line| matches!(line, DisplayLine::Note { entry_idx: 1, .. }))
+        };
+        assert_eq!(note_line(&app), Some(2));
+        assert_eq!(
+            app.get_line_text(&app.display_lines[2]),
+            "Note: double close"
+        );
+        app.expanded_items.insert(1);
+        app.rebuild_display_lines();
+        assert_eq!(note_line(&app), Some(2));
+        assert!(app.entry_text(1).contains("Note: double close"));
+
+        // Editing it to nothing removes it
+        app.selected_line = 2;
+        app.handle_event(KeyEvent::from(KeyCode::Enter));
+        assert_eq!(app.note_prompt, Some((1, "double close".to_string())));
+        app.note_prompt = Some((1, String::new()));
+        app.handle_event(KeyEvent::from(KeyCode::Enter));
+        assert!(app.notes.is_empty());
+        assert_eq!(note_line(&app), None);
+
+        // The note goes to the entry the prompt was opened on, even if the cursor moved since
+        app.selected_line = 0;
+        app.handle_event(KeyEvent::from(KeyCode::Char('a')));
+        app.selected_line = 1;
+        app.handle_event(KeyEvent::from(KeyCode::Char('x')));
+        app.handle_event(KeyEvent::from(KeyCode::Enter));
+        assert_eq!(app.notes.keys().collect::<Vec<_>>(), [&0]);
+    }
+
+    #[test]
+    fn test_jump_prompt_hex() {
+        let trace: String = (0..20)
+            .map(|i| format!("1 10:00:00 close({}) = 0\n", i))
+            .collect();
+        let mut app = app_from_trace(&trace);
+
+        for c in ":0x10".chars() {
+            app.handle_event(KeyEvent::from(KeyCode::Char(c)));
+        }
+        app.handle_event(KeyEvent::from(KeyCode::Enter));
+        assert!(matches!(
+            app.display_lines[app.selected_line],
+            DisplayLine::SyscallHeader { entry_idx: 15, .. }
+        ));
+
+        assert_eq!(parse_entry_number("0X1f"), Some(31));
+        assert_eq!(parse_entry_number("16"), Some(16));
+        assert_eq!(parse_entry_number("0xg"), None);
+    }
+
+    #[test]
+    fn test_raw_arguments() {
+        let mut app = app_from_trace(
+            "1 10:00:00 ioctl(1, TCGETS, {c_iflag=ICRNL|IXON, c_cc=\"\\x03\\x1c\"}) = 0\n",
+        );
+        app.toggle_current_line();
+        app.selected_line = 1;
+        app.toggle_current_line();
+        app.handle_event(KeyEvent::from(KeyCode::Char('V')));
+
+        let raw_lines: Vec<&DisplayLine> = app
+            .display_lines
+            .iter()
+            .filter(|line| matches!(line, DisplayLine::RawArguments { .. }))
+            .collect();
+        assert_eq!(raw_lines.len(), 1);
+        assert_eq!(
+            app.get_line_text(raw_lines[0]),
+            "1, TCGETS, {c_iflag=ICRNL|IXON, c_cc=\"\\x03\\x1c\"}"
+        );
+        assert!(
+            !app.display_lines
+                .iter()
+                .any(|line| matches!(line, DisplayLine::ArgumentLine { .. }))
+        );
+
+        // Long ones wrap over several rows
+        app.update_list_area(Rect::new(0, 0, 30, 20));
+        let rows = app
+            .display_lines
+            .iter()
+            .filter(|line| matches!(line, DisplayLine::RawArguments { .. }))
+            .count();
+        assert!(rows > 1);
+
+        app.handle_event(KeyEvent::from(KeyCode::Char('V')));
+        assert!(
+            app.display_lines
+                .iter()
+                .any(|line| matches!(line, DisplayLine::ArgumentLine { .. }))
+        );
+    }
+
+    #[test]
+    fn test_group_by_syscall() {
+        let mut app = app_from_trace(
+            "1 10:00:00 read(3, \"a\", 1) = 1\n\
+             1 10:00:00 openat(AT_FDCWD, \"/a\", O_RDONLY) = 3\n\
+             1 10:00:00 read(3, \"b\", 1) = 1\n\
+             1 10:00:00 close(3) = 0\n\
+             1 10:00:00 openat(AT_FDCWD, \"/b\", O_RDONLY) = 3\n\
+             1 10:00:00 read(3, \"\", 1) = 0\n",
+        );
+        let texts = |app: &App| -> Vec<String> {
+            app.display_lines
+                .iter()
+                .map(|line| app.get_line_text(line))
+                .collect()
+        };
+
+        app.handle_event(KeyEvent::from(KeyCode::Char('Z')));
+        assert_eq!(
+            texts(&app),
+            ["close (1 call)", "openat (2 calls)", "read (3 calls)"]
+        );
+
+        // Expanding a group lists its entries under it, in trace order
+        app.selected_line = 2;
+        app.handle_event(KeyEvent::from(KeyCode::Enter));
+        let listed: Vec<usize> = app.display_lines[3..]
+            .iter()
+            .map(|line| line.entry_idx())
+            .collect();
+        assert_eq!(listed, [0, 2, 5]);
+
+        // The counts only take the entries left by the filters
+        app.hidden_syscalls.insert("close".to_string());
+        app.errno_filter.insert("ENOENT".to_string());
+        app.rebuild_display_lines();
+        assert!(app.display_lines.is_empty());
+        app.errno_filter.clear();
+        app.rebuild_display_lines();
+        assert_eq!(texts(&app)[..2], ["openat (2 calls)", "read (3 calls)"]);
+
+        app.handle_event(KeyEvent::from(KeyCode::Char('Z')));
+        assert!(!app.group_by_syscall);
+        assert_eq!(app.display_lines.len(), 5);
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(0.000001), "1µs");
+        assert_eq!(format_duration(0.000123), "123µs");
+        assert_eq!(format_duration(0.0000005), "500ns");
+        assert_eq!(format_duration(0.0123), "12.3ms");
+        assert_eq!(format_duration(0.00105), "1.05ms");
+        assert_eq!(format_duration(2.5), "2.5s");
+        assert_eq!(format_duration(0.0), "0ns");
+        assert_eq!(format_duration(0.000000004), "4ns");
+        assert_eq!(format_duration(0.000004), "4µs");
+        assert_eq!(format_duration(0.00123), "1.23ms");
+        assert_eq!(format_duration(0.999), "999ms");
+        assert_eq!(format_duration(125.0), "125s");
+        assert_eq!(format_duration(3600.0), "3600s");
+
+        // Rounding up to the next unit
+        assert_eq!(format_duration(0.0000009996), "1µs");
+        assert_eq!(format_duration(0.0009997), "1ms");
+        assert_eq!(format_duration(0.99996), "1s");
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(0), "0B");
+        assert_eq!(format_bytes(1023), "1023B");
+        assert_eq!(format_bytes(1536), "1.5KiB");
+        assert_eq!(format_bytes(4 * 1024 * 1024), "4MiB");
+    }
+
+    #[test]
+    fn test_argument_value() {
+        assert_eq!(argument_value("st_size=4096"), "4096");
+        assert_eq!(
+            argument_value("sa_flags=SA_RESTART|SA_SIGINFO"),
+            "SA_RESTART|SA_SIGINFO"
+        );
+        assert_eq!(argument_value("\"a=b\""), "\"a=b\"");
+        assert_eq!(
+            argument_value("{st_mode=S_IFREG|0644, st_size=12}"),
+            "{st_mode=S_IFREG|0644, st_size=12}"
+        );
+        assert_eq!(argument_value("O_RDONLY"), "O_RDONLY");
+    }
+
+    #[test]
+    fn test_common_path_prefix() {
+        let paths = [
+            "/home/user/build/src/main.rs",
+            "/home/user/build/src/parser/mod.rs",
+            "/home/user/build/target/debug/app",
+        ];
+        assert_eq!(common_path_prefix(paths), "/home/user/build/");
+
+        // Prefix stops at a directory boundary, not mid-component
+        let paths = ["/home/user/app1", "/home/user/app2"];
+        assert_eq!(common_path_prefix(paths), "/home/user/");
+
+        // Only the root in common, or no paths at all
+        assert_eq!(common_path_prefix(["/usr/lib/libc.so.6", "/home/x"]), "");
+        assert_eq!(common_path_prefix([]), "");
+    }
 }
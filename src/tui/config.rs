@@ -0,0 +1,196 @@
+use super::keymap::Keymap;
+use super::syscall_colors::SyscallColors;
+use super::theme::ThemeName;
+use crate::parser::ResolverOptions;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// User configuration for the TUI, loaded from `config.toml` in the strace-tui config directory.
+/// Every field is optional, so a missing or partial file falls back to the defaults.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Mappings from local source roots to web URL templates
+    pub source_links: Vec<SourceLink>,
+
+    /// Whether the process graph starts visible (toggled with `|`)
+    pub show_graph: bool,
+
+    /// Capture the mouse. Disabling it keeps the terminal's native text selection.
+    pub mouse: bool,
+
+    /// Fetch debug files missing locally from the servers in `DEBUGINFOD_URLS`, same as
+    /// `--debuginfod`
+    pub debuginfod: bool,
+
+    /// Keep the resolved frames on disk across runs, unless `--no-resolve-cache` is given
+    pub resolve_cache: bool,
+
+    /// Palette for a dark or light background. Guessed from the terminal when not set.
+    pub theme: Option<ThemeName>,
+
+    /// Save the expanded entries, filters, bookmarks and position of each trace file on quit,
+    /// and restore them when it's opened again unchanged
+    pub sessions: bool,
+
+    /// Arguments listed when an entry's arguments are expanded, before a line to show the rest.
+    /// 0 lists them all.
+    pub max_arguments: usize,
+
+    /// Syscall colors, loaded from their own `colors.toml`
+    #[serde(skip)]
+    pub colors: SyscallColors,
+
+    /// Key bindings, loaded from their own `keys.toml`
+    #[serde(skip)]
+    pub keys: Keymap,
+
+    /// How backtraces are resolved, from the command line along with `debuginfod`
+    #[serde(skip)]
+    pub resolver: ResolverOptions,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            source_links: Vec::new(),
+            show_graph: true,
+            mouse: true,
+            debuginfod: false,
+            resolve_cache: true,
+            theme: None,
+            sessions: true,
+            max_arguments: 20,
+            colors: SyscallColors::default(),
+            keys: Keymap::default(),
+            resolver: ResolverOptions::default(),
+        }
+    }
+}
+
+/// Maps resolved source files under `root` to a web URL, for opening frames in a browser
+#[derive(Debug, Clone, Deserialize)]
+pub struct SourceLink {
+    /// Local source root, e.g. "/home/build/foo"
+    pub root: String,
+
+    /// URL template with `{rev}`, `{path}` and `{line}` placeholders, e.g.
+    /// "https://github.com/org/foo/blob/{rev}/{path}#L{line}"
+    pub url: String,
+
+    /// Revision substituted for `{rev}`
+    #[serde(default = "default_rev")]
+    pub rev: String,
+}
+
+fn default_rev() -> String {
+    "HEAD".to_string()
+}
+
+impl Config {
+    /// Directory holding the config files (e.g. `~/.config/strace-tui`)
+    pub fn dir() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("strace-tui"))
+    }
+
+    /// Load the config files, falling back to defaults for those that don't exist. A malformed
+    /// file is reported on stderr and ignored.
+    pub fn load() -> Self {
+        let Some(dir) = Self::dir() else {
+            return Self::default();
+        };
+
+        let mut config: Self = load_file(&dir.join("config.toml"), Self::parse);
+        config.colors = load_file(&dir.join("colors.toml"), SyscallColors::parse);
+        config.keys = load_file(&dir.join("keys.toml"), Keymap::parse);
+        config
+    }
+
+    pub fn parse(content: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(content)
+    }
+
+    /// Build the web URL for a source location, using the longest matching source root
+    pub fn source_url(&self, file: &str, line: u32) -> Option<String> {
+        let (link, path) = self
+            .source_links
+            .iter()
+            .filter_map(|link| {
+                let root = link.root.trim_end_matches('/');
+                let path = file.strip_prefix(root)?.strip_prefix('/')?;
+                Some((link, path))
+            })
+            .max_by_key(|(link, _)| link.root.len())?;
+
+        Some(
+            link.url
+                .replace("{rev}", &link.rev)
+                .replace("{path}", path)
+                .replace("{line}", &line.to_string()),
+        )
+    }
+}
+
+fn load_file<T: Default>(path: &Path, parse: fn(&str) -> Result<T, toml::de::Error>) -> T {
+    let Ok(content) = std::fs::read_to_string(path) else {
+        return T::default();
+    };
+
+    match parse(&content) {
+        Ok(value) => value,
+        Err(err) => {
+            eprintln!("Ignoring invalid config {}: {}", path.display(), err);
+            T::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_url() {
+        let config = Config::parse(
+            r#"
+            [[source_links]]
+            root = "/home/build/foo"
+            url = "https://github.com/org/foo/blob/{rev}/{path}#L{line}"
+            rev = "v1.2"
+
+            [[source_links]]
+            root = "/home/build/foo/vendor/bar/"
+            url = "https://gitlab.com/org/bar/-/blob/{rev}/{path}#L{line}"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.source_url("/home/build/foo/src/main.rs", 42),
+            Some("https://github.com/org/foo/blob/v1.2/src/main.rs#L42".to_string())
+        );
+
+        // Longest root wins
+        assert_eq!(
+            config.source_url("/home/build/foo/vendor/bar/lib.rs", 7),
+            Some("https://gitlab.com/org/bar/-/blob/HEAD/lib.rs#L7".to_string())
+        );
+
+        // Only matches whole path components
+        assert_eq!(config.source_url("/home/build/foobar/main.rs", 1), None);
+        assert_eq!(config.source_url("/usr/src/libc.c", 1), None);
+    }
+
+    #[test]
+    fn test_empty_config() {
+        let config = Config::parse("").unwrap();
+        assert!(config.source_links.is_empty());
+        assert!(config.show_graph);
+
+        assert!(config.resolve_cache);
+
+        let config = Config::parse("show_graph = false\nresolve_cache = false").unwrap();
+        assert!(!config.show_graph);
+        assert!(!config.resolve_cache);
+    }
+}
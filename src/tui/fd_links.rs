@@ -0,0 +1,134 @@
+use crate::parser::SyscallEntry;
+use std::collections::HashMap;
+
+/// A socket/pipe inode referenced by an entry, and the entry that created it
+#[derive(Debug, Clone, PartialEq)]
+pub struct FdReference {
+    pub inode: String, // e.g. "socket:[12345]"
+    pub creator_idx: usize,
+}
+
+/// Cross-references between fds decoded by `strace -y` (`write(5<socket:[12345]>, ...)`) and
+/// the syscall that created the socket or pipe behind them.
+#[derive(Debug, Default)]
+pub struct FdLinks {
+    /// References of each entry, keyed by entry index. Entries that create an inode don't
+    /// reference themselves.
+    pub references: HashMap<usize, Vec<FdReference>>,
+}
+
+impl FdLinks {
+    fn creates_through_arguments(syscall_name: &str) -> bool {
+        matches!(syscall_name, "pipe" | "pipe2" | "socketpair")
+    }
+
+    pub fn build(entries: &[SyscallEntry]) -> Self {
+        // First pass: find the creator of each inode. Most syscalls return the new fd, but
+        // pipe() and socketpair() fill an fd array argument instead.
+        let mut creators: HashMap<&str, usize> = HashMap::new();
+        for (idx, entry) in entries.iter().enumerate() {
+            if let Some(path) = &entry.return_fd_path
+                && is_inode(path)
+            {
+                creators.entry(path).or_insert(idx);
+            }
+
+            if Self::creates_through_arguments(&entry.syscall_name) {
+                for inode in inodes_in(entry) {
+                    creators.entry(inode).or_insert(idx);
+                }
+            }
+        }
+
+        // Second pass: link every decorated fd argument to its creator
+        let mut references: HashMap<usize, Vec<FdReference>> = HashMap::new();
+        for (idx, entry) in entries.iter().enumerate() {
+            for inode in inodes_in(entry) {
+                let Some(&creator_idx) = creators.get(inode) else {
+                    continue;
+                };
+                if creator_idx == idx {
+                    continue;
+                }
+
+                let entry_refs = references.entry(idx).or_default();
+                if !entry_refs.iter().any(|r| r.inode == inode) {
+                    entry_refs.push(FdReference {
+                        inode: inode.to_string(),
+                        creator_idx,
+                    });
+                }
+            }
+        }
+
+        FdLinks { references }
+    }
+
+    pub fn get(&self, entry_idx: usize) -> &[FdReference] {
+        self.references
+            .get(&entry_idx)
+            .map(Vec::as_slice)
+            .unwrap_or(&[])
+    }
+}
+
+/// Whether a decoded fd path names a socket or pipe inode, like `socket:[12345]`
+fn is_inode(path: &str) -> bool {
+    let Some((kind, rest)) = path.split_once(":[") else {
+        return false;
+    };
+    matches!(kind, "socket" | "pipe")
+        && rest
+            .strip_suffix(']')
+            .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// Socket/pipe inodes decorating the fd arguments of an entry, e.g. `socket:[12345]` out of
+/// `5<socket:[12345]>`
+fn inodes_in(entry: &SyscallEntry) -> impl Iterator<Item = &str> {
+    entry
+        .fd_paths
+        .iter()
+        .map(|(_, path)| path.as_str())
+        .filter(|path| is_inode(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::StraceParser;
+
+    #[test]
+    fn test_fd_links() {
+        let entries = StraceParser::new()
+            .parse_lines(
+                "1 10:00:00 socket(AF_UNIX, SOCK_STREAM, 0) = 3<socket:[100]>\n\
+                 1 10:00:00 pipe2([4<pipe:[200]>, 5<pipe:[200]>], O_CLOEXEC) = 0\n\
+                 1 10:00:00 write(5<pipe:[200]>, \"x\", 1) = 1\n\
+                 1 10:00:00 sendto(3<socket:[100]>, \"x\", 1, 0, NULL, 0) = 1\n\
+                 1 10:00:00 read(6<socket:[999]>, \"x\", 1) = 1\n\
+                 1 10:00:00 read(7</etc/passwd>, \"x\", 1) = 1\n"
+                    .lines()
+                    .map(str::to_string),
+                false,
+            )
+            .unwrap();
+
+        let links = FdLinks::build(&entries);
+
+        assert!(links.get(0).is_empty());
+        assert!(links.get(1).is_empty()); // The pipe doesn't reference itself
+        assert_eq!(
+            links.get(2),
+            &[FdReference {
+                inode: "pipe:[200]".to_string(),
+                creator_idx: 1
+            }]
+        );
+        assert_eq!(links.get(3)[0].creator_idx, 0);
+
+        // Unknown creator and regular files aren't linked
+        assert!(links.get(4).is_empty());
+        assert!(links.get(5).is_empty());
+    }
+}
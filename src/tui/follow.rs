@@ -0,0 +1,198 @@
+use crate::parser::StraceParser;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+
+/// A trace that is still being written, whose new lines are added to the app as they show up
+pub struct Follow {
+    pub source: LineSource,
+    /// Kept across reads, as it holds the unfinished calls still waiting for their resumption
+    pub parser: StraceParser,
+    pub merge_resumed: bool,
+}
+
+/// Where the lines of a followed trace come from
+pub enum LineSource {
+    File(FileTail),
+    Pipe(PipeFeed),
+}
+
+impl LineSource {
+    /// Complete lines that arrived since the last call, without their newline
+    pub fn read_lines(&mut self) -> io::Result<Vec<String>> {
+        match self {
+            LineSource::File(tail) => tail.read_lines(),
+            LineSource::Pipe(feed) => Ok(feed.read_lines()),
+        }
+    }
+
+    /// Whether no more lines will come. A file may always grow.
+    pub fn is_closed(&self) -> bool {
+        match self {
+            LineSource::File(_) => false,
+            LineSource::Pipe(feed) => feed.closed,
+        }
+    }
+}
+
+/// Reads the lines appended to a file since the last read, like `tail -f`
+pub struct FileTail {
+    reader: BufReader<File>,
+    partial: Vec<u8>, // Start of a line whose newline wasn't written yet
+}
+
+impl FileTail {
+    /// Tail a file from its start
+    pub fn open(path: &str) -> io::Result<Self> {
+        Ok(Self {
+            reader: BufReader::new(File::open(path)?),
+            partial: Vec::new(),
+        })
+    }
+
+    /// Complete lines written since the last call, without their newline. A line still being
+    /// written is held back until it is complete.
+    pub fn read_lines(&mut self) -> io::Result<Vec<String>> {
+        let mut lines = Vec::new();
+        loop {
+            // At the end of the file, this reads nothing now and more once the file grows
+            if self.reader.read_until(b'\n', &mut self.partial)? == 0 {
+                return Ok(lines);
+            }
+            if self.partial.ends_with(b"\n") {
+                lines.push(line_text(&self.partial));
+                self.partial.clear();
+            }
+        }
+    }
+}
+
+/// Lines of a stream, like the output of a running strace, read on a background thread as
+/// reading a pipe blocks until something is written to it
+pub struct PipeFeed {
+    lines: Receiver<String>,
+    closed: bool,
+}
+
+impl PipeFeed {
+    pub fn spawn<R: Read + Send + 'static>(reader: R) -> Self {
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            let mut reader = BufReader::new(reader);
+            let mut line = Vec::new();
+            // Stop at the end of the stream, or once nobody is listening
+            while reader
+                .read_until(b'\n', &mut line)
+                .is_ok_and(|read| read > 0)
+            {
+                if sender.send(line_text(&line)).is_err() {
+                    break;
+                }
+                line.clear();
+            }
+        });
+
+        Self {
+            lines,
+            closed: false,
+        }
+    }
+
+    /// Lines received since the last call
+    pub fn read_lines(&mut self) -> Vec<String> {
+        let mut lines = Vec::new();
+        loop {
+            match self.lines.try_recv() {
+                Ok(line) => lines.push(line),
+                Err(TryRecvError::Empty) => return lines,
+                Err(TryRecvError::Disconnected) => {
+                    self.closed = true;
+                    return lines;
+                }
+            }
+        }
+    }
+}
+
+/// Text of a line without its line ending. Invalid UTF-8, which strace escapes but the traced
+/// program may print, is replaced rather than failing the whole read.
+fn line_text(line: &[u8]) -> String {
+    let line = line.strip_suffix(b"\n").unwrap_or(line);
+    let line = line.strip_suffix(b"\r").unwrap_or(line);
+    String::from_utf8_lossy(line).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_pipe_feed_until_closed() {
+        let trace = "1 10:00:00 read(3, \"\", 1) = 0\n \
+                     > /usr/lib/libc.so.6(read+0x14) [0x10e53e]\n\
+                     1 10:00:00 close(3) = 0";
+        let mut feed = PipeFeed::spawn(trace.as_bytes());
+
+        let mut lines = Vec::new();
+        while !feed.closed {
+            lines.extend(feed.read_lines());
+            thread::yield_now();
+        }
+        // The last line counts even without a newline, as the stream ended
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[2], "1 10:00:00 close(3) = 0");
+    }
+
+    #[test]
+    fn test_tail_parses_incrementally() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        let mut tail = FileTail::open(file.path().to_str().unwrap()).unwrap();
+        let mut parser = StraceParser::new();
+        let mut entries = Vec::new();
+        let mut poll = |tail: &mut FileTail, entries: &mut Vec<_>| {
+            parser.parse_more(tail.read_lines().unwrap(), false, entries);
+        };
+
+        poll(&mut tail, &mut entries);
+        assert!(entries.is_empty());
+
+        // The second line is still being written
+        write!(
+            file,
+            "1 10:00:00 openat(AT_FDCWD, \"a\", O_RDONLY) = 3\n1 10:00:00 futex(0x1, FUTEX_WAIT"
+        )
+        .unwrap();
+        file.flush().unwrap();
+        poll(&mut tail, &mut entries);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].syscall_name, "openat");
+
+        write!(
+            file,
+            ", 0 <unfinished ...>\n \
+             > /usr/lib/libc.so.6(syscall+0x1d) [0x11f8ad]\n"
+        )
+        .unwrap();
+        file.flush().unwrap();
+        poll(&mut tail, &mut entries);
+        assert_eq!(entries.len(), 2);
+        assert!(entries[1].is_unfinished);
+        assert_eq!(entries[1].backtrace.len(), 1);
+
+        // More backtrace for the last entry, then its resumption in a later read
+        write!(
+            file,
+            " > /usr/lib/libc.so.6(__libc_start_main+0x80) [0x27680]\n\
+             1 10:00:01 <... futex resumed>) = 0\n"
+        )
+        .unwrap();
+        file.flush().unwrap();
+        poll(&mut tail, &mut entries);
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[1].backtrace.len(), 2);
+        assert_eq!(entries[1].resumed_entry_idx, Some(2));
+        assert_eq!(entries[2].unfinished_entry_idx, Some(1));
+    }
+}
@@ -0,0 +1,323 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::{Deserialize, Deserializer};
+use std::collections::HashMap;
+
+/// Something a key does in the main view
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Quit,
+    Help,
+    HideSyscall,
+    FilterSyscalls,
+    ShowHidden,
+    ErrnoFilter,
+    Stats,
+    Histogram,
+    PidPicker,
+    ProcessTree,
+    ProgramOutput,
+    ParseErrors,
+    MinDurationUp,
+    MinDurationDown,
+    MinDuration,
+    StripPathPrefix,
+    ToggleGraph,
+    ToggleBacktraces,
+    Sort,
+    GroupBySyscall,
+    ToggleDeltas,
+    ToggleTimestamps,
+    TogglePidTags,
+    WrapArguments,
+    RawArguments,
+    EntryNumbers,
+    JumpToEntry,
+    OpenSourceLink,
+    ResolveBacktrace,
+    ResolveAll,
+    Copy,
+    CopyArgument,
+    RawLine,
+    SyscallHelp,
+    NextFile,
+    PrevFile,
+    NextEntry,
+    PrevEntry,
+    NextError,
+    PrevError,
+    NextSignal,
+    PrevSignal,
+    Bookmark,
+    Note,
+    CollapsePid,
+    NextBookmark,
+    PrevBookmark,
+    MoveUp,
+    MoveDown,
+    PageUp,
+    PageDown,
+    HalfPageUp,
+    HalfPageDown,
+    Top,
+    Bottom,
+    ToggleFold,
+    Collapse,
+    Expand,
+    ExpandAll,
+    CollapseAll,
+    Search,
+    SearchNext,
+    SearchPrev,
+}
+
+impl Action {
+    /// Built-in keys of the action. The first is the one `handle_event` matches on.
+    fn default_keys(self) -> &'static [KeyBinding] {
+        use KeyBinding as K;
+        match self {
+            Action::Quit => const { &[K::char('q'), K::char('Q'), K::ctrl('c')] },
+            Action::Help => const { &[K::char('?')] },
+            Action::HideSyscall => const { &[K::char('h')] },
+            Action::FilterSyscalls => const { &[K::char('H')] },
+            Action::ShowHidden => const { &[K::char('.')] },
+            Action::ErrnoFilter => const { &[K::char('E')] },
+            Action::Stats => const { &[K::char('%')] },
+            Action::Histogram => const { &[K::char('D')] },
+            Action::PidPicker => const { &[K::char('p')] },
+            Action::ProcessTree => const { &[K::char('t')] },
+            Action::ProgramOutput => const { &[K::char('O')] },
+            Action::ParseErrors => const { &[K::char('!')] },
+            Action::MinDurationUp => const { &[K::char('+')] },
+            Action::MinDurationDown => const { &[K::char('-')] },
+            Action::MinDuration => const { &[K::char('>')] },
+            Action::StripPathPrefix => const { &[K::char('f')] },
+            Action::ToggleGraph => const { &[K::char('|')] },
+            Action::ToggleBacktraces => const { &[K::char('F')] },
+            Action::Sort => const { &[K::char('o')] },
+            Action::GroupBySyscall => const { &[K::char('Z')] },
+            Action::ToggleDeltas => const { &[K::char('i')] },
+            Action::ToggleTimestamps => const { &[K::char('T')] },
+            Action::TogglePidTags => const { &[K::char('I')] },
+            Action::WrapArguments => const { &[K::char('W')] },
+            Action::RawArguments => const { &[K::char('V')] },
+            Action::EntryNumbers => const { &[K::char('#')] },
+            Action::JumpToEntry => const { &[K::char(':')] },
+            Action::OpenSourceLink => const { &[K::char('w')] },
+            Action::ResolveBacktrace => const { &[K::char('r')] },
+            Action::ResolveAll => const { &[K::char('R')] },
+            Action::Copy => const { &[K::char('y')] },
+            Action::CopyArgument => const { &[K::char('Y')] },
+            Action::RawLine => const { &[K::char('v')] },
+            Action::SyscallHelp => const { &[K::char('K')] },
+            Action::NextFile => const { &[K::char('}')] },
+            Action::PrevFile => const { &[K::char('{')] },
+            Action::NextEntry => const { &[K::ctrl('j'), K::ctrl_key(KeyCode::Down)] },
+            Action::PrevEntry => const { &[K::ctrl('k'), K::ctrl_key(KeyCode::Up)] },
+            Action::NextError => const { &[K::char(']')] },
+            Action::PrevError => const { &[K::char('[')] },
+            Action::NextSignal => const { &[K::char('s')] },
+            Action::PrevSignal => const { &[K::char('S')] },
+            Action::Bookmark => const { &[K::char('m')] },
+            Action::Note => const { &[K::char('a')] },
+            Action::CollapsePid => const { &[K::char('z')] },
+            Action::NextBookmark => const { &[K::char('b')] },
+            Action::PrevBookmark => const { &[K::char('B')] },
+            Action::MoveUp => const { &[K::char('k'), K::key(KeyCode::Up)] },
+            Action::MoveDown => const { &[K::char('j'), K::key(KeyCode::Down)] },
+            Action::PageUp => const { &[K::key(KeyCode::PageUp)] },
+            Action::PageDown => const { &[K::key(KeyCode::PageDown)] },
+            Action::HalfPageUp => const { &[K::ctrl('u')] },
+            Action::HalfPageDown => const { &[K::ctrl('d')] },
+            // Bound keys act as Home, since `g` alone waits for a second `g`
+            Action::Top => const { &[K::key(KeyCode::Home), K::char('g')] },
+            Action::Bottom => const { &[K::char('G'), K::key(KeyCode::End)] },
+            Action::ToggleFold => const { &[K::key(KeyCode::Enter), K::char(' ')] },
+            Action::Collapse => const { &[K::key(KeyCode::Left)] },
+            Action::Expand => const { &[K::key(KeyCode::Right)] },
+            Action::ExpandAll => const { &[K::char('e')] },
+            Action::CollapseAll => const { &[K::char('c')] },
+            Action::Search => const { &[K::char('/')] },
+            Action::SearchNext => const { &[K::char('n')] },
+            Action::SearchPrev => const { &[K::char('N')] },
+        }
+    }
+}
+
+/// A key with or without Ctrl, written like `x`, `ctrl-u`, `enter` or `pagedown`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyBinding {
+    code: KeyCode,
+    ctrl: bool,
+}
+
+impl KeyBinding {
+    const fn key(code: KeyCode) -> Self {
+        Self { code, ctrl: false }
+    }
+
+    const fn ctrl_key(code: KeyCode) -> Self {
+        Self { code, ctrl: true }
+    }
+
+    const fn char(c: char) -> Self {
+        Self::key(KeyCode::Char(c))
+    }
+
+    const fn ctrl(c: char) -> Self {
+        Self::ctrl_key(KeyCode::Char(c))
+    }
+
+    fn matches(&self, event: &KeyEvent) -> bool {
+        self.code == event.code && self.ctrl == event.modifiers.contains(KeyModifiers::CONTROL)
+    }
+
+    fn to_event(self) -> KeyEvent {
+        let modifiers = if self.ctrl {
+            KeyModifiers::CONTROL
+        } else {
+            KeyModifiers::NONE
+        };
+        KeyEvent::new(self.code, modifiers)
+    }
+
+    fn parse(spec: &str) -> Option<Self> {
+        let (ctrl, key) = match spec.strip_prefix("ctrl-") {
+            Some(key) if !key.is_empty() => (true, key),
+            _ => (false, spec),
+        };
+
+        let mut chars = key.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match key.to_ascii_lowercase().as_str() {
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "enter" => KeyCode::Enter,
+                "space" => KeyCode::Char(' '),
+                "tab" => KeyCode::Tab,
+                "backspace" => KeyCode::Backspace,
+                "delete" => KeyCode::Delete,
+                _ => return None,
+            },
+        };
+        Some(Self { code, ctrl })
+    }
+}
+
+/// Keys replacing the built-in ones, from `keys.toml` in the config directory:
+///
+/// ```toml
+/// hide_syscall = "x"
+/// quit = ["Q", "ctrl-c"]
+/// ```
+///
+/// An action given keys loses its built-in ones. Actions not listed keep theirs.
+#[derive(Debug, Clone, Default)]
+pub struct Keymap {
+    bindings: HashMap<Action, Vec<KeyBinding>>,
+}
+
+impl Keymap {
+    pub fn parse(content: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(content)
+    }
+
+    /// Turn a key press into the built-in key of the action it's bound to. Returns `None` for
+    /// a built-in key of an action that was given other keys.
+    pub fn translate(&self, event: KeyEvent) -> Option<KeyEvent> {
+        if let Some(action) = self
+            .bindings
+            .iter()
+            .find(|(_, keys)| keys.iter().any(|key| key.matches(&event)))
+            .map(|(action, _)| *action)
+        {
+            return Some(action.default_keys()[0].to_event());
+        }
+
+        let rebound = self
+            .bindings
+            .keys()
+            .any(|action| action.default_keys().iter().any(|key| key.matches(&event)));
+        (!rebound).then_some(event)
+    }
+}
+
+impl<'de> Deserialize<'de> for Keymap {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Keys {
+            One(String),
+            Many(Vec<String>),
+        }
+
+        let bindings = HashMap::<Action, Keys>::deserialize(deserializer)?
+            .into_iter()
+            .map(|(action, keys)| {
+                let specs = match keys {
+                    Keys::One(spec) => vec![spec],
+                    Keys::Many(specs) => specs,
+                };
+                let keys = specs
+                    .iter()
+                    .map(|spec| {
+                        KeyBinding::parse(spec).ok_or_else(|| {
+                            serde::de::Error::custom(format!("invalid key \"{}\"", spec))
+                        })
+                    })
+                    .collect::<Result<_, _>>()?;
+                Ok((action, keys))
+            })
+            .collect::<Result<_, D::Error>>()?;
+        Ok(Self { bindings })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keymap() {
+        let keymap = Keymap::parse(
+            r#"
+            hide_syscall = "x"
+            half_page_down = ["ctrl-f", "pagedown"]
+            "#,
+        )
+        .unwrap();
+        let press = |code| {
+            keymap
+                .translate(KeyEvent::from(code))
+                .map(|event| event.code)
+        };
+
+        assert_eq!(press(KeyCode::Char('x')), Some(KeyCode::Char('h')));
+        assert_eq!(press(KeyCode::Char('h')), None);
+        // Unmapped keys keep working, even those of other actions
+        assert_eq!(press(KeyCode::Char('c')), Some(KeyCode::Char('c')));
+        assert_eq!(press(KeyCode::PageDown), Some(KeyCode::Char('d')));
+
+        let event = keymap
+            .translate(KeyEvent::new(KeyCode::Char('f'), KeyModifiers::CONTROL))
+            .unwrap();
+        assert_eq!(
+            event,
+            KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL)
+        );
+        assert_eq!(
+            keymap.translate(KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL)),
+            None
+        );
+
+        assert!(Keymap::parse("hide_syscall = \"nope\"").is_err());
+        assert!(Keymap::parse("fold_everything = \"x\"").is_err());
+    }
+}
@@ -1,12 +1,26 @@
 mod app;
+mod config;
+mod fd_links;
+mod follow;
+mod keymap;
 mod process_graph;
+mod session;
+mod stats;
 mod syscall_colors;
+mod theme;
 mod ui;
 
 pub use app::App;
-
+pub use config::Config;
+pub use follow::{FileTail, Follow, LineSource, PipeFeed};
+pub use process_graph::process_tree;
+pub use session::Session;
+pub use stats::summary_table;
+pub use theme::{Theme, ThemeName};
+
+use crate::parser::Addr2LineResolver;
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyEvent, KeyEventKind},
+    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyEventKind},
     execute,
     terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
 };
@@ -15,11 +29,18 @@ use std::fs::{self, OpenOptions};
 use std::io::{self, Write};
 use std::time::Duration;
 
-pub fn run_tui(
-    entries: Vec<crate::parser::SyscallEntry>,
-    summary: crate::parser::SummaryStats,
-    file_path: Option<String>,
-) -> io::Result<()> {
+/// A parsed trace file to show in the TUI
+pub struct TraceFile {
+    pub entries: Vec<crate::parser::SyscallEntry>,
+    pub summary: crate::parser::SummaryStats,
+    pub file_path: Option<String>,
+    pub program_output: Vec<(usize, String)>, // (line number, text) of non-strace lines
+    pub errors: Vec<crate::parser::ParseErrorInfo>, // Lines that failed to parse
+    pub truncated_at: Option<usize>,          // Entry cap parsing stopped at, if it did
+    pub follow: Option<Follow>,               // Keep reading what is added to the file
+}
+
+pub fn run_tui(traces: Vec<TraceFile>, config: Config) -> io::Result<()> {
     // Initialize logging to file only if RUST_LOG is set
     if std::env::var("RUST_LOG").is_ok() {
         // Get the cache directory (or state directory on Linux)
@@ -49,25 +70,63 @@ pub fn run_tui(
     }
 
     // Setup terminal
+    let mouse = config.mouse;
     enable_raw_mode()?;
     let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    execute!(stdout, EnterAlternateScreen)?;
+    if mouse {
+        execute!(stdout, EnableMouseCapture)?;
+    }
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    // Create app
-    let mut app = App::new(entries, summary, file_path);
+    // Create one app per file, so each keeps its own cursor, filters and search
+    let file_count = traces.len();
+    let mut apps: Vec<App> = traces
+        .into_iter()
+        .enumerate()
+        .map(|(file_index, trace)| {
+            let mut app = App::new(
+                trace.entries,
+                trace.summary,
+                trace.file_path,
+                config.clone(),
+            );
+            app.resolver = Addr2LineResolver::from_options(&config.resolver);
+            app.program_output = trace.program_output;
+            app.parse_errors = trace.errors;
+            app.truncated_at = trace.truncated_at;
+            app.follow = trace.follow;
+            app.file_index = file_index;
+            app.file_count = file_count;
+            if config.sessions
+                && let Some(session) = app.session_path().and_then(|path| Session::load(&path))
+            {
+                app.restore_session(session);
+            }
+            app
+        })
+        .collect();
 
     // Run the main loop
-    let res = run_app(&mut terminal, &mut app);
+    let res = run_app(&mut terminal, &mut apps, mouse);
+
+    if config.sessions {
+        for app in &apps {
+            if let Some(path) = app.session_path()
+                && let Err(err) = app.session().save(&path)
+            {
+                log::warn!("Failed to save the session of {}: {}", path.display(), err);
+            }
+        }
+    }
 
     // Restore terminal
     disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    if mouse {
+        execute!(terminal.backend_mut(), DisableMouseCapture)?;
+    }
     terminal.show_cursor()?;
 
     res
@@ -75,17 +134,25 @@ pub fn run_tui(
 
 fn run_app<B: ratatui::backend::Backend + io::Write>(
     terminal: &mut Terminal<B>,
-    app: &mut App,
+    apps: &mut [App],
+    mouse: bool,
 ) -> Result<(), B::Error>
 where
     B::Error: From<std::io::Error>,
 {
+    let mut active = 0;
+
     loop {
+        let app = &mut apps[active];
+        app.poll_follow();
+        app.poll_resolve();
         let app_ref = &mut *app;
         terminal.draw(move |f| ui::draw(f, app_ref))?;
 
-        if let Some(event) = get_event()? {
-            app.handle_event(event);
+        match get_event()? {
+            Some(Event::Key(key)) => app.handle_event(key),
+            Some(Event::Mouse(mouse)) => app.handle_mouse_event(mouse),
+            _ => {}
         }
 
         if app.should_quit {
@@ -96,11 +163,10 @@ where
         if let Some((file, line, column)) = app.pending_editor_open.take() {
             // Suspend the TUI - proper cleanup
             disable_raw_mode()?;
-            execute!(
-                terminal.backend_mut(),
-                LeaveAlternateScreen,
-                DisableMouseCapture
-            )?;
+            execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+            if mouse {
+                execute!(terminal.backend_mut(), DisableMouseCapture)?;
+            }
             terminal.show_cursor()?;
 
             // Flush the terminal to ensure all commands are executed
@@ -117,31 +183,69 @@ where
 
             // Resume the TUI
             enable_raw_mode()?;
-            execute!(
-                terminal.backend_mut(),
-                EnterAlternateScreen,
-                EnableMouseCapture
-            )?;
+            execute!(terminal.backend_mut(), EnterAlternateScreen)?;
+            if mouse {
+                execute!(terminal.backend_mut(), EnableMouseCapture)?;
+            }
             terminal.hide_cursor()?;
 
             // Force a full redraw
             terminal.clear()?;
         }
+
+        // Check if we need to open a URL in the browser
+        if let Some(url) = app.pending_url_open.take()
+            && let Err(e) = open_url(&url)
+        {
+            app.status_message = Some(e);
+        }
+
+        // Check if we need to switch to another file
+        if let Some(offset) = app.pending_file_switch.take() {
+            active = (active as isize + offset).rem_euclid(apps.len() as isize) as usize;
+        }
     }
 }
 
-pub fn get_event() -> io::Result<Option<KeyEvent>> {
-    if event::poll(Duration::from_millis(100))?
-        && let Event::Key(key) = event::read()?
-    {
-        // Only process key press events, not release
-        if key.kind == KeyEventKind::Press {
-            return Ok(Some(key));
+/// Next key press or mouse event, if one arrives soon
+pub fn get_event() -> io::Result<Option<Event>> {
+    if event::poll(Duration::from_millis(100))? {
+        match event::read()? {
+            // Only process key press events, not release
+            Event::Key(key) if key.kind == KeyEventKind::Press => return Ok(Some(Event::Key(key))),
+            Event::Mouse(mouse) => return Ok(Some(Event::Mouse(mouse))),
+            _ => {}
         }
     }
     Ok(None)
 }
 
+/// Open a URL in the default browser, without waiting for it
+fn open_url(url: &str) -> Result<(), String> {
+    use std::process::{Command, Stdio};
+
+    let opener = if cfg!(target_os = "macos") {
+        "open"
+    } else {
+        "xdg-open"
+    };
+
+    log::debug!("Opening URL with {}: {}", opener, url);
+
+    // Detach from the terminal so the browser doesn't draw over the TUI
+    Command::new(opener)
+        .arg(url)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map(|mut child| {
+            // Reap the opener once it exits, without blocking the UI
+            std::thread::spawn(move || child.wait());
+        })
+        .map_err(|e| format!("Failed to run {}: {}", opener, e))
+}
+
 /// Open editor in foreground (blocking)
 fn open_editor_foreground(file: &str, line: u32, column: Option<u32>) -> Result<(), String> {
     use std::env;
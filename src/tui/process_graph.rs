@@ -1,40 +1,43 @@
-use crate::parser::SyscallEntry;
+use super::stats::pid_summaries;
+use super::theme::Theme;
+use crate::parser::{ProcessNode, SyscallEntry};
 use ratatui::style::Color;
-use std::collections::HashMap;
-
-const GRAPH_COLORS: &[Color] = &[
-    Color::Blue,
-    Color::Green,
-    Color::Yellow,
-    Color::Magenta,
-    Color::Cyan,
-    Color::LightBlue,
-    Color::LightGreen,
-    Color::LightMagenta,
-];
+use std::collections::{HashMap, HashSet};
 
 #[derive(Debug)]
 pub struct ProcessInfo {
     pub _pid: u32,
     pub column: usize,
-    pub color: Color,
+    pub color_index: usize, // Index in the PID colors of the theme, see Theme::pid_color
     pub first_entry_idx: usize,
     pub last_entry_idx: usize,
-    pub _parent_pid: Option<u32>,
+    pub parent_pid: Option<u32>, // Process that forked this one, if it is in the trace
 }
 
-#[derive(Debug)]
+/// A row of the process tree panel
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcessTreeRow {
+    pub pid: u32,
+    pub depth: usize, // 0 for processes whose parent isn't in the trace
+    pub first_entry_idx: usize,
+}
+
+/// The processes of a trace in tree order, with their parents and children, for the JSON output
+pub fn process_tree(entries: &[SyscallEntry]) -> Vec<ProcessNode> {
+    ProcessGraph::build(entries).nodes(entries)
+}
+
+#[derive(Debug, Default)]
 pub struct ProcessGraph {
     pub processes: HashMap<u32, ProcessInfo>,
     pub max_columns: usize,
     pub enabled: bool, // Hide graph if only one process
+    /// Children returned by fork/clone that never show up in the trace, which happens when
+    /// strace ran without `-f`
+    pub untraced_children: usize,
 }
 
 impl ProcessGraph {
-    fn is_fork_syscall(syscall_name: &str) -> bool {
-        matches!(syscall_name, "fork" | "vfork" | "clone" | "clone3")
-    }
-
     fn is_wait_syscall(syscall_name: &str) -> bool {
         matches!(syscall_name, "wait4" | "waitid" | "waitpid")
     }
@@ -53,12 +56,9 @@ impl ProcessGraph {
             pid_first_seen.entry(pid).or_insert(idx);
             pid_last_seen.insert(pid, idx);
 
-            // Detect fork syscalls
-            if Self::is_fork_syscall(&entry.syscall_name)
-                && let Some(ref ret) = entry.return_value
-                // Try to parse return value as child PID
-                && let Ok(child_pid) = ret.trim().parse::<u32>()
-                && child_pid > 0
+            // Detect fork syscalls, at the first half of those that were unfinished
+            if let Some(child_pid) = entry.child_pid
+                && !(entry.is_resumed && entry.unfinished_entry_idx.is_some())
             {
                 fork_relationships.push((idx, pid, child_pid));
                 pid_first_seen.entry(child_pid).or_insert(idx);
@@ -120,46 +120,124 @@ impl ProcessGraph {
                 ProcessInfo {
                     _pid: pid,
                     column,
-                    color: GRAPH_COLORS[index % GRAPH_COLORS.len()],
+                    color_index: index,
                     first_entry_idx: idx,
                     last_entry_idx: pid_last_seen.get(&pid).cloned().unwrap_or(idx),
-                    _parent_pid: parent_pid,
+                    parent_pid,
                 },
             );
         }
 
         let enabled = max_columns > 1; // Hide graph if only one process
 
+        let traced_pids: HashSet<u32> = entries.iter().map(|entry| entry.pid).collect();
+        let untraced_children = fork_relationships
+            .iter()
+            .filter(|(_, _, child)| !traced_pids.contains(child))
+            .count();
+
         ProcessGraph {
             processes,
             max_columns,
             enabled,
+            untraced_children,
         }
     }
 
-    pub fn get_color(&self, pid: u32) -> Color {
+    /// The process hierarchy, each parent followed by its children, in order of first appearance
+    pub fn tree(&self) -> Vec<ProcessTreeRow> {
+        let mut children: HashMap<u32, Vec<&ProcessInfo>> = HashMap::new();
+        let mut roots = Vec::new();
+        for info in self.processes.values() {
+            match info.parent_pid {
+                Some(parent) if self.processes.contains_key(&parent) => {
+                    children.entry(parent).or_default().push(info)
+                }
+                _ => roots.push(info),
+            }
+        }
+        roots.sort_by_key(|info| info.first_entry_idx);
+        for siblings in children.values_mut() {
+            siblings.sort_by_key(|info| info.first_entry_idx);
+        }
+
+        // Depth-first, with a stack in reverse so the first child comes out first. The visited
+        // set guards against loops from reused PIDs.
+        let mut rows = Vec::new();
+        let mut visited = HashSet::new();
+        let mut stack: Vec<(&ProcessInfo, usize)> =
+            roots.into_iter().rev().map(|info| (info, 0)).collect();
+        while let Some((info, depth)) = stack.pop() {
+            if !visited.insert(info._pid) {
+                continue;
+            }
+            rows.push(ProcessTreeRow {
+                pid: info._pid,
+                depth,
+                first_entry_idx: info.first_entry_idx,
+            });
+            if let Some(siblings) = children.get(&info._pid) {
+                stack.extend(siblings.iter().rev().map(|child| (*child, depth + 1)));
+            }
+        }
+        rows
+    }
+
+    /// The processes of the tree, in its order, with their children and programs
+    fn nodes(&self, entries: &[SyscallEntry]) -> Vec<ProcessNode> {
+        let programs: HashMap<u32, String> = pid_summaries(entries)
+            .into_iter()
+            .filter_map(|summary| Some((summary.pid, summary.command?)))
+            .collect();
+
+        self.tree()
+            .into_iter()
+            .filter_map(|row| self.processes.get(&row.pid))
+            .map(|info| {
+                let mut children: Vec<&ProcessInfo> = self
+                    .processes
+                    .values()
+                    .filter(|child| child.parent_pid == Some(info._pid))
+                    .collect();
+                children.sort_by_key(|child| child.first_entry_idx);
+
+                ProcessNode {
+                    pid: info._pid,
+                    parent_pid: info.parent_pid,
+                    children: children.iter().map(|child| child._pid).collect(),
+                    first_entry_idx: info.first_entry_idx,
+                    last_entry_idx: info.last_entry_idx,
+                    program: programs.get(&info._pid).cloned(),
+                }
+            })
+            .collect()
+    }
+
+    pub fn get_color(&self, pid: u32, theme: &Theme) -> Color {
         self.processes
             .get(&pid)
-            .map(|info| info.color)
-            .unwrap_or(Color::White)
+            .map(|info| theme.pid_color(info.color_index))
+            .unwrap_or(theme.text)
     }
 
-    pub fn get_color_for_column(&self, column: usize, entry_idx: usize) -> Color {
-        self.processes
+    pub fn get_color_for_column(&self, column: usize, entry_idx: usize, theme: &Theme) -> Color {
+        let index = self
+            .processes
             .values()
             .find(|info| {
                 info.column == column
                     && entry_idx >= info.first_entry_idx
                     && entry_idx <= info.last_entry_idx
             })
-            .map(|info| info.color)
-            .unwrap_or(GRAPH_COLORS[column % GRAPH_COLORS.len()])
+            .map_or(column, |info| info.color_index);
+        theme.pid_color(index)
     }
 
     pub fn render_graph_for_entry(
         &self,
         entry_idx: usize,
         entries: &[SyscallEntry],
+        theme: &Theme,
     ) -> Vec<(char, Color)> {
         if !self.enabled {
             return Vec::new();
@@ -173,27 +251,8 @@ impl ProcessGraph {
         let pid = entry.pid;
         let mut graph = Vec::new();
 
-        // Check if this is a fork
-        let is_fork = Self::is_fork_syscall(&entry.syscall_name) && !entry.is_resumed;
-        let child_pid = if is_fork {
-            if entry.is_unfinished {
-                entry.resumed_entry_idx.and_then(|resumed_idx| {
-                    entries
-                        .get(resumed_idx)
-                        .and_then(|resumed_entry| resumed_entry.return_value.as_ref())
-                        .and_then(|ret| ret.trim().parse::<u32>().ok())
-                        .filter(|&child| child > 0)
-                })
-            } else {
-                entry
-                    .return_value
-                    .as_ref()
-                    .and_then(|ret| ret.trim().parse::<u32>().ok())
-                    .filter(|&child| child > 0)
-            }
-        } else {
-            None
-        };
+        // Check if this is a fork, drawn at the first half of those that were unfinished
+        let child_pid = entry.child_pid.filter(|_| !entry.is_resumed);
 
         // Check if this is a wait that completes
         let is_wait = Self::is_wait_syscall(&entry.syscall_name);
@@ -221,7 +280,7 @@ impl ProcessGraph {
 
         // Build graph with colored characters column by column
         for col in 0..self.max_columns {
-            let col_color = self.get_color_for_column(col, entry_idx);
+            let col_color = self.get_color_for_column(col, entry_idx, theme);
             if let Some(child) = child_pid {
                 let child_column = self.processes.get(&child).map(|p| p.column).unwrap_or(0);
 
@@ -300,3 +359,59 @@ impl ProcessGraph {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::StraceParser;
+
+    fn parse(trace: &str) -> Vec<SyscallEntry> {
+        StraceParser::new()
+            .parse_lines(trace.lines().map(str::to_string), false)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_process_tree() {
+        // 100 forks 200 and 400, and 200 forks 300
+        let entries = parse(
+            "100 10:20:30 clone(child_stack=NULL, flags=SIGCHLD) = 200\n\
+             200 10:20:30 clone(child_stack=NULL, flags=SIGCHLD) = 300\n\
+             300 10:20:30 exit_group(0) = ?\n\
+             100 10:20:31 fork() = 400\n\
+             400 10:20:31 exit_group(0) = ?\n\
+             200 10:20:31 exit_group(0) = ?\n",
+        );
+        let tree: Vec<(u32, usize)> = ProcessGraph::build(&entries)
+            .tree()
+            .into_iter()
+            .map(|row| (row.pid, row.depth))
+            .collect();
+        assert_eq!(tree, [(100, 0), (200, 1), (300, 2), (400, 1)]);
+    }
+
+    #[test]
+    fn test_untraced_children() {
+        // Without -f, the child returned by clone never shows up
+        let entries = parse(
+            "10:20:30 clone(child_stack=NULL, flags=SIGCHLD) = 4321\n\
+             10:20:31 wait4(-1, NULL, 0, NULL) = 4321\n",
+        );
+        assert_eq!(ProcessGraph::build(&entries).untraced_children, 1);
+
+        let entries = parse(
+            "1234 10:20:30 clone(child_stack=NULL, flags=SIGCHLD) = 4321\n\
+             4321 10:20:30 exit_group(0) = ?\n\
+             1234 10:20:31 wait4(-1, NULL, 0, NULL) = 4321\n",
+        );
+        assert_eq!(ProcessGraph::build(&entries).untraced_children, 0);
+
+        // Both halves of an unfinished clone3 know the child, which counts once
+        let entries = parse(
+            "1234 10:20:30 clone3({flags=CLONE_VM, exit_signal=SIGCHLD}, 88 <unfinished ...>\n\
+             1234 10:20:30 <... clone3 resumed> => {parent_tid=[4321]}, 88) = 4321\n",
+        );
+        assert_eq!(entries[0].child_pid, Some(4321));
+        assert_eq!(ProcessGraph::build(&entries).untraced_children, 1);
+    }
+}
@@ -0,0 +1,178 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// What was being looked at in a trace, saved on quit and restored when the same trace is
+/// opened again. Entries are named by their line number, which unlike their index doesn't
+/// depend on how the trace was parsed, e.g. with `--merge-resumed`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Session {
+    pub hidden_syscalls: HashSet<String>,
+    pub expanded_items: HashSet<usize>,
+    pub bookmarks: HashSet<usize>,
+    pub notes: HashMap<usize, String>,
+    pub search_query: String,
+    pub selected_line: usize,
+    pub scroll_offset: usize,
+}
+
+impl Session {
+    /// Sessions kept, as one is saved for every trace file opened
+    const MAX_SAVED: usize = 200;
+
+    /// Where the session of a trace file is kept (e.g.
+    /// `~/.local/state/strace-tui/<hash>.json`). The file size is part of the key, as line
+    /// numbers mean nothing once the trace is rewritten.
+    pub fn path_for(trace_path: &str) -> Option<PathBuf> {
+        let size = std::fs::metadata(trace_path).ok()?.len();
+        let trace_path = std::fs::canonicalize(trace_path).ok()?;
+        let dir = dirs::state_dir().or_else(dirs::cache_dir)?;
+        Some(
+            dir.join("strace-tui")
+                .join(format!("{:016x}.json", session_key(&trace_path, size))),
+        )
+    }
+
+    /// Load a saved session, if there is a readable one
+    pub fn load(path: &Path) -> Option<Self> {
+        let data = std::fs::read(path).ok()?;
+        serde_json::from_slice(&data).ok()
+    }
+
+    /// Save through a temporary file, so that quitting midway never leaves half of it. Past
+    /// [`MAX_SAVED`](Self::MAX_SAVED) sessions in its directory, the ones saved longest ago are
+    /// removed, unless they have notes, which are kept nowhere else.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let partial = path.with_extension("json.part");
+        std::fs::write(&partial, serde_json::to_vec(self)?)?;
+        std::fs::rename(partial, path)?;
+
+        if let Some(dir) = path.parent() {
+            prune_sessions(dir, Self::MAX_SAVED)?;
+        }
+        Ok(())
+    }
+}
+
+/// Remove the sessions in `dir` saved longest ago, keeping `keep` of them and any with notes.
+/// Other files, like the resolver cache when both are in the cache directory, are left alone.
+fn prune_sessions(dir: &Path, keep: usize) -> io::Result<()> {
+    let mut sessions: Vec<(SystemTime, PathBuf)> = std::fs::read_dir(dir)?
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let path = entry.path();
+            let stem = path.file_stem()?.to_str()?;
+            let is_session = path.extension()? == "json"
+                && stem.len() == 16
+                && stem.bytes().all(|b| b.is_ascii_hexdigit());
+            is_session.then_some(())?;
+            Some((entry.metadata().ok()?.modified().ok()?, path))
+        })
+        .collect();
+    if sessions.len() <= keep {
+        return Ok(());
+    }
+
+    sessions.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+    for (_, path) in &sessions[keep..] {
+        if Session::load(path).is_some_and(|session| !session.notes.is_empty()) {
+            continue;
+        }
+        std::fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+/// FNV-1a of the path and size, which unlike the std hasher is the same on every build
+fn session_key(trace_path: &Path, size: u64) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in trace_path
+        .as_os_str()
+        .as_encoded_bytes()
+        .iter()
+        .chain(&size.to_le_bytes())
+    {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_round_trip() {
+        let session = Session {
+            hidden_syscalls: HashSet::from(["mmap".to_string(), "brk".to_string()]),
+            expanded_items: HashSet::from([0, 7]),
+            bookmarks: HashSet::from([3]),
+            notes: HashMap::from([(3, "double close?".to_string())]),
+            search_query: "/etc".to_string(),
+            selected_line: 12,
+            scroll_offset: 4,
+        };
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("session.json");
+        session.save(&path).unwrap();
+        assert_eq!(Session::load(&path), Some(session));
+
+        // Fields missing from an older file fall back to their defaults
+        std::fs::write(&path, r#"{"selected_line": 2}"#).unwrap();
+        let loaded = Session::load(&path).unwrap();
+        assert_eq!(loaded.selected_line, 2);
+        assert!(loaded.bookmarks.is_empty());
+
+        assert_ne!(
+            session_key(Path::new("/tmp/a.txt"), 10),
+            session_key(Path::new("/tmp/a.txt"), 11)
+        );
+    }
+
+    #[test]
+    fn test_prune_sessions() {
+        let dir = tempfile::tempdir().unwrap();
+        let saved_at = |name: &str, secs: u64, session: &Session| {
+            let path = dir.path().join(name);
+            std::fs::write(&path, serde_json::to_vec(session).unwrap()).unwrap();
+            let file = std::fs::File::options().write(true).open(&path).unwrap();
+            file.set_modified(SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(secs))
+                .unwrap();
+        };
+        let plain = Session::default();
+        saved_at("000000000000000a.json", 1, &plain);
+        saved_at("000000000000000b.json", 3, &plain);
+        saved_at("000000000000000c.json", 2, &plain);
+        saved_at("resolved.json", 0, &plain);
+        // The oldest, but its notes are kept nowhere else
+        let noted = Session {
+            notes: HashMap::from([(1, "keep me".to_string())]),
+            ..Session::default()
+        };
+        saved_at("000000000000000d.json", 0, &noted);
+
+        prune_sessions(dir.path(), 2).unwrap();
+        let mut left: Vec<_> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        left.sort();
+        assert_eq!(
+            left,
+            [
+                "000000000000000b.json",
+                "000000000000000c.json",
+                "000000000000000d.json",
+                "resolved.json"
+            ]
+        );
+    }
+}
@@ -0,0 +1,424 @@
+use crate::parser::{SyscallEntry, Timestamp};
+use std::collections::HashMap;
+
+/// Aggregated numbers for one syscall name, like a row of `strace -c`
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyscallStats {
+    pub name: String,
+    pub calls: usize,
+    pub errors: usize,
+    pub total_time: f64,   // Sum of durations, in seconds
+    pub percent_time: f64, // Share of the time spent in all syscalls, 0-100
+}
+
+impl SyscallStats {
+    /// Mean duration of a call, in seconds
+    pub fn average_time(&self) -> f64 {
+        if self.calls == 0 {
+            0.0
+        } else {
+            self.total_time / self.calls as f64
+        }
+    }
+}
+
+/// Entries whose duration is below `upper` and at least the previous bucket's bound
+#[derive(Debug, Clone, PartialEq)]
+pub struct DurationBucket {
+    pub upper: f64, // In seconds, infinite for the last bucket
+    pub count: usize,
+}
+
+/// Activity of one process or thread, for picking it from a list
+#[derive(Debug, Clone, PartialEq)]
+pub struct PidSummary {
+    pub pid: u32,
+    pub calls: usize,
+    pub failed: usize,
+    pub total_time: f64, // Sum of syscall durations, in seconds
+    pub first_entry_idx: usize,
+    pub last_entry_idx: usize,
+    pub command: Option<String>, // Program of the last successful execve, if any
+}
+
+impl PidSummary {
+    /// Seconds between the first and last entry of the process, if they have timestamps
+    pub fn lifetime(&self, entries: &[SyscallEntry]) -> Option<f64> {
+        let first = entries[self.first_entry_idx].time?;
+        let last = entries[self.last_entry_idx].time?;
+        Some(seconds_between(&first, &last))
+    }
+}
+
+/// Seconds from `first` to `last`, assuming less than a day apart
+fn seconds_between(first: &Timestamp, last: &Timestamp) -> f64 {
+    // Wrap around midnight
+    let span = last.as_secs() - first.as_secs();
+    if span < 0.0 { span + 86400.0 } else { span }
+}
+
+/// Seconds since the previous timestamped entry of the same PID, per entry. None for entries
+/// without a timestamp and for the first one of each PID.
+pub fn pid_deltas(entries: &[SyscallEntry]) -> Vec<Option<f64>> {
+    let mut previous: HashMap<u32, Timestamp> = HashMap::new();
+    entries
+        .iter()
+        .map(|entry| {
+            let time = entry.time?;
+            previous
+                .insert(entry.pid, time)
+                .map(|prev| seconds_between(&prev, &time))
+        })
+        .collect()
+}
+
+/// Summarize each PID, in order of first appearance
+pub fn pid_summaries(entries: &[SyscallEntry]) -> Vec<PidSummary> {
+    let mut rows: Vec<PidSummary> = Vec::new();
+    let mut row_of_pid: HashMap<u32, usize> = HashMap::new();
+
+    for (idx, entry) in entries.iter().enumerate() {
+        let row_idx = *row_of_pid.entry(entry.pid).or_insert_with(|| {
+            rows.push(PidSummary {
+                pid: entry.pid,
+                calls: 0,
+                failed: 0,
+                total_time: 0.0,
+                first_entry_idx: idx,
+                last_entry_idx: idx,
+                command: None,
+            });
+            rows.len() - 1
+        });
+        let row = &mut rows[row_idx];
+        row.last_entry_idx = idx;
+        row.total_time += entry.duration.unwrap_or(0.0);
+
+        if entry.signal.is_none() && entry.exit_info.is_none() && !entry.is_resumed {
+            row.calls += 1;
+        }
+        if entry.errno.is_some() {
+            row.failed += 1;
+        }
+
+        // The program path is the first, quoted, argument of execve
+        if entry.syscall_name == "execve"
+            && entry.errno.is_none()
+            && let Some(path) = entry
+                .arguments
+                .strip_prefix('"')
+                .and_then(|args| args.split('"').next())
+        {
+            row.command = Some(path.to_string());
+        }
+    }
+
+    rows
+}
+
+/// Aggregate entries by syscall name, sorted by total time (then calls) descending.
+///
+/// Signals and exits are not syscalls and are skipped. An unfinished/resumed pair is counted
+/// as a single call, with its duration taken from whichever half has it.
+pub fn syscall_stats(entries: &[SyscallEntry]) -> Vec<SyscallStats> {
+    let mut by_name: HashMap<&str, SyscallStats> = HashMap::new();
+
+    for entry in entries {
+        if entry.signal.is_some() || entry.exit_info.is_some() || entry.syscall_name.is_empty() {
+            continue;
+        }
+
+        let stats = by_name
+            .entry(&entry.syscall_name)
+            .or_insert_with(|| SyscallStats {
+                name: entry.syscall_name.clone(),
+                calls: 0,
+                errors: 0,
+                total_time: 0.0,
+                percent_time: 0.0,
+            });
+
+        if !entry.is_resumed {
+            stats.calls += 1;
+        }
+        if entry.errno.is_some() {
+            stats.errors += 1;
+        }
+        stats.total_time += entry.duration.unwrap_or(0.0);
+    }
+
+    let grand_total: f64 = by_name.values().map(|stats| stats.total_time).sum();
+
+    let mut rows: Vec<SyscallStats> = by_name.into_values().collect();
+    for row in &mut rows {
+        if grand_total > 0.0 {
+            row.percent_time = row.total_time / grand_total * 100.0;
+        }
+    }
+
+    rows.sort_by(|a, b| {
+        b.total_time
+            .total_cmp(&a.total_time)
+            .then(b.calls.cmp(&a.calls))
+            .then(a.name.cmp(&b.name))
+    });
+    rows
+}
+
+/// Render the syscall stats as the table `strace -c` prints, with a total row at the end
+pub fn summary_table(entries: &[SyscallEntry]) -> String {
+    const SEPARATOR: &str = "------ ----------- ----------- --------- --------- ----------------\n";
+
+    let rows = syscall_stats(entries);
+    let row = |percent: f64, seconds: f64, calls: usize, errors: usize, name: &str| {
+        let usecs_per_call = if calls == 0 {
+            0.0
+        } else {
+            seconds * 1e6 / calls as f64
+        };
+        // Like strace, no errors leaves the column blank
+        let errors = if errors == 0 {
+            String::new()
+        } else {
+            errors.to_string()
+        };
+        format!(
+            "{:>6.2} {:>11.6} {:>11.0} {:>9} {:>9} {}\n",
+            percent, seconds, usecs_per_call, calls, errors, name
+        )
+    };
+
+    let mut table = format!(
+        "{:>6} {:>11} {:>11} {:>9} {:>9} {}\n",
+        "% time", "seconds", "usecs/call", "calls", "errors", "syscall"
+    );
+    table.push_str(SEPARATOR);
+    for stats in &rows {
+        table.push_str(&row(
+            stats.percent_time,
+            stats.total_time,
+            stats.calls,
+            stats.errors,
+            &stats.name,
+        ));
+    }
+    table.push_str(SEPARATOR);
+
+    let total_time = rows.iter().map(|stats| stats.total_time).sum();
+    table.push_str(&row(
+        100.0,
+        total_time,
+        rows.iter().map(|stats| stats.calls).sum(),
+        rows.iter().map(|stats| stats.errors).sum(),
+        "total",
+    ));
+    table
+}
+
+/// Count the entries with a duration in buckets a power of ten apart, from under 1µs to 10s
+/// and more. Only entries of the syscall `name` are counted, when given.
+pub fn duration_histogram(entries: &[SyscallEntry], name: Option<&str>) -> Vec<DurationBucket> {
+    let mut buckets: Vec<DurationBucket> = (-6..=1)
+        .map(|exponent| 10f64.powi(exponent))
+        .chain([f64::INFINITY])
+        .map(|upper| DurationBucket { upper, count: 0 })
+        .collect();
+
+    for entry in entries {
+        if name.is_some_and(|name| entry.syscall_name != name) {
+            continue;
+        }
+        if let Some(duration) = entry.duration
+            && let Some(bucket) = buckets.iter_mut().find(|bucket| duration < bucket.upper)
+        {
+            bucket.count += 1;
+        }
+    }
+
+    buckets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::StraceParser;
+
+    #[test]
+    fn test_duration_histogram() {
+        let entries = StraceParser::new()
+            .parse_lines(
+                "1 10:00:00 getpid() = 1 <0.000000>\n\
+                 1 10:00:00 read(3, \"\", 1) = 0 <0.000005>\n\
+                 1 10:00:00 read(3, \"\", 1) = 0 <0.000010>\n\
+                 1 10:00:00 write(1, \"a\", 1) = 1 <0.000999>\n\
+                 1 10:00:00 fsync(1) = 0 <0.020000>\n\
+                 1 10:00:00 read(3, \"\", 1) = 0 <12.500000>\n\
+                 1 10:00:00 close(3) = 0\n\
+                 1 10:00:00 --- SIGCHLD {si_signo=SIGCHLD} ---\n"
+                    .lines()
+                    .map(str::to_string),
+                false,
+            )
+            .unwrap();
+
+        let counts = |name| -> Vec<usize> {
+            duration_histogram(&entries, name)
+                .iter()
+                .map(|bucket| bucket.count)
+                .collect()
+        };
+        // <1µs, <10µs, <100µs, <1ms, <10ms, <100ms, <1s, <10s, the rest
+        assert_eq!(counts(None), [1, 1, 1, 1, 0, 1, 0, 0, 1]);
+        assert_eq!(counts(Some("read")), [0, 1, 1, 0, 0, 0, 0, 0, 1]);
+        assert_eq!(duration_histogram(&entries, None)[3].upper, 1e-3);
+    }
+
+    #[test]
+    fn test_pid_summaries() {
+        let entries = StraceParser::new()
+            .parse_lines(
+                "10 10:00:00.000000 clone(child_stack=NULL, flags=SIGCHLD) = 11 <0.000100>\n\
+                 11 10:00:00.100000 execve(\"/bin/ls\", [\"ls\"], 0x7ffd /* 3 vars */) = 0 <0.000200>\n\
+                 11 10:00:00.200000 access(\"/x\", F_OK) = -1 ENOENT (No such file or directory)\n\
+                 11 10:00:01.600000 exit_group(0) = ?\n\
+                 10 10:00:02.000000 wait4(-1, NULL, 0, NULL) = 11\n"
+                    .lines()
+                    .map(str::to_string),
+                false,
+            )
+            .unwrap();
+
+        let rows = pid_summaries(&entries);
+        assert_eq!(rows.len(), 2);
+
+        assert_eq!(
+            (rows[0].pid, rows[0].calls, rows[0].first_entry_idx),
+            (10, 2, 0)
+        );
+        assert_eq!(rows[0].command, None);
+        assert!((rows[0].lifetime(&entries).unwrap() - 2.0).abs() < 1e-9);
+
+        assert_eq!(
+            (rows[1].pid, rows[1].calls, rows[1].first_entry_idx),
+            (11, 3, 1)
+        );
+        assert_eq!(rows[1].failed, 1);
+        assert!((rows[1].total_time - 0.0002).abs() < 1e-9);
+        assert!((rows[1].lifetime(&entries).unwrap() - 1.5).abs() < 1e-9);
+        assert_eq!(rows[1].command.as_deref(), Some("/bin/ls"));
+    }
+
+    #[test]
+    fn test_pid_deltas() {
+        let entries = StraceParser::new()
+            .parse_lines(
+                "10 10:00:00.000000 read(3, \"\", 1) = 0\n\
+                 11 10:00:00.100000 getpid() = 11\n\
+                 10 10:00:00.452000 write(1, \"a\", 1) = 1\n\
+                 11 getppid() = 10\n\
+                 11 10:00:01.000000 close(3) = 0\n\
+                 10 23:59:59.500000 close(4) = 0\n"
+                    .lines()
+                    .map(str::to_string),
+                false,
+            )
+            .unwrap();
+
+        let deltas = pid_deltas(&entries);
+        assert_eq!(deltas.len(), 6);
+        assert_eq!(deltas[0], None); // First of PID 10
+        assert_eq!(deltas[1], None); // First of PID 11
+        assert!((deltas[2].unwrap() - 0.452).abs() < 1e-9);
+        assert_eq!(deltas[3], None); // No timestamp
+        assert!((deltas[4].unwrap() - 0.9).abs() < 1e-9); // Since the last timestamped one
+        assert!((deltas[5].unwrap() - 50399.048).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_percent_time() {
+        let entries = StraceParser::new()
+            .parse_lines(
+                "1 10:00:00 read(3, \"a\", 1) = 1 <0.000300>\n\
+                 1 10:00:00 read(3, \"a\", 1) = 1 <0.000300>\n\
+                 1 10:00:00 write(1, \"a\", 1) = 1 <0.000400>\n\
+                 1 10:00:00 --- SIGCHLD {si_signo=SIGCHLD} ---\n"
+                    .lines()
+                    .map(str::to_string),
+                false,
+            )
+            .unwrap();
+
+        let stats = syscall_stats(&entries);
+        assert_eq!(stats.len(), 2);
+
+        assert_eq!(stats[0].name, "read");
+        assert_eq!(stats[0].calls, 2);
+        assert!((stats[0].percent_time - 60.0).abs() < 1e-9);
+
+        assert_eq!(stats[1].name, "write");
+        assert!((stats[1].percent_time - 40.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_errors_and_average() {
+        let entries = StraceParser::new()
+            .parse_lines(
+                "1 10:00:00 openat(AT_FDCWD, \"/a\", O_RDONLY) = 3 <0.000100>\n\
+                 1 10:00:00 openat(AT_FDCWD, \"/b\", O_RDONLY) = -1 ENOENT (No such file or directory) <0.000050>\n\
+                 1 10:00:00 openat(AT_FDCWD, \"/c\", O_RDONLY) = -1 EACCES (Permission denied) <0.000150>\n\
+                 2 10:00:00 read(3,  <unfinished ...>\n\
+                 1 10:00:00 close(3) = 0 <0.000010>\n\
+                 2 10:00:01 <... read resumed>\"\", 1) = -1 EINTR (Interrupted system call) <0.001000>\n"
+                    .lines()
+                    .map(str::to_string),
+                false,
+            )
+            .unwrap();
+
+        let stats = syscall_stats(&entries);
+        let row = |name: &str| stats.iter().find(|row| row.name == name).unwrap();
+
+        // Sorted by total time
+        let names: Vec<_> = stats.iter().map(|row| row.name.as_str()).collect();
+        assert_eq!(names, ["read", "openat", "close"]);
+
+        assert_eq!((row("openat").calls, row("openat").errors), (3, 2));
+        assert!((row("openat").total_time - 0.0003).abs() < 1e-9);
+        assert!((row("openat").average_time() - 0.0001).abs() < 1e-9);
+
+        // The unfinished/resumed pair is a single failed call
+        assert_eq!((row("read").calls, row("read").errors), (1, 1));
+        assert!((row("read").average_time() - 0.001).abs() < 1e-9);
+
+        assert_eq!((row("close").calls, row("close").errors), (1, 0));
+    }
+
+    #[test]
+    fn test_summary_table() {
+        let entries = StraceParser::new()
+            .parse_lines(
+                "1 10:00:00 read(3, \"a\", 1) = 1 <0.000300>\n\
+                 1 10:00:00 write(1, \"a\", 1) = 1 <0.000400>\n\
+                 1 10:00:00 read(3, \"\", 1) = -1 EAGAIN (Resource temporarily unavailable) <0.000300>\n\
+                 1 10:00:00 close(3) = 0 <0.000000>\n\
+                 1 10:00:00 --- SIGCHLD {si_signo=SIGCHLD} ---\n"
+                    .lines()
+                    .map(str::to_string),
+                false,
+            )
+            .unwrap();
+
+        assert_eq!(
+            summary_table(&entries),
+            "\
+% time     seconds  usecs/call     calls    errors syscall
+------ ----------- ----------- --------- --------- ----------------
+ 60.00    0.000600         300         2         1 read
+ 40.00    0.000400         400         1           write
+  0.00    0.000000           0         1           close
+------ ----------- ----------- --------- --------- ----------------
+100.00    0.001000         250         4         1 total
+"
+        );
+    }
+}
@@ -1,7 +1,82 @@
+use super::theme::Theme;
 use ratatui::style::Color;
+use serde::{Deserialize, Deserializer};
+use std::collections::HashMap;
+use std::str::FromStr;
 
-/// Returns the color for a syscall based on its category
-pub fn syscall_category_color(name: &str) -> Color {
+/// Kind of a syscall, which gives it its color
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SyscallCategory {
+    File,
+    Process,
+    Memory,
+    Network,
+    Filesystem,
+    Time,
+    Signal,
+    Security,
+    Polling,
+    Resource,
+    Other,
+}
+
+/// Colors overriding the built-in ones, from `colors.toml` in the config directory:
+///
+/// ```toml
+/// [categories]
+/// memory = "dark gray"
+///
+/// [syscalls]
+/// read = "red"
+/// ```
+///
+/// Colors are names, like in ratatui, `#rrggbb` or a palette index.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct SyscallColors {
+    #[serde(deserialize_with = "deserialize_colors")]
+    pub categories: HashMap<SyscallCategory, Color>,
+    #[serde(deserialize_with = "deserialize_colors")]
+    pub syscalls: HashMap<String, Color>,
+}
+
+impl SyscallColors {
+    pub fn parse(content: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(content)
+    }
+}
+
+fn deserialize_colors<'de, D, K>(deserializer: D) -> Result<HashMap<K, Color>, D::Error>
+where
+    D: Deserializer<'de>,
+    K: Deserialize<'de> + Eq + std::hash::Hash,
+{
+    HashMap::<K, String>::deserialize(deserializer)?
+        .into_iter()
+        .map(|(key, color)| {
+            Color::from_str(&color)
+                .map(|color| (key, color))
+                .map_err(|_| serde::de::Error::custom(format!("invalid color \"{}\"", color)))
+        })
+        .collect()
+}
+
+/// Returns the color for a syscall: its own override, else its category's, else the theme's
+/// color for the category
+pub fn syscall_category_color(name: &str, colors: &SyscallColors, theme: &Theme) -> Color {
+    if let Some(&color) = colors.syscalls.get(name) {
+        return color;
+    }
+    let category = syscall_category(name);
+    colors
+        .categories
+        .get(&category)
+        .copied()
+        .unwrap_or_else(|| theme.category_color(category))
+}
+
+pub fn syscall_category(name: &str) -> SyscallCategory {
     match name {
         // File I/O - Blue
         "read" | "write" | "pread" | "pwrite" | "pread64" | "pwrite64" | "readv" | "writev"
@@ -9,64 +84,304 @@ pub fn syscall_category_color(name: &str) -> Color {
         | "dup2" | "dup3" | "lseek" | "llseek" | "_llseek" | "fcntl" | "ioctl" | "fstat"
         | "stat" | "lstat" | "fstatat" | "newfstatat" | "statx" | "ftruncate" | "truncate"
         | "fsync" | "fdatasync" | "sync" | "syncfs" | "access" | "faccessat" | "faccessat2" => {
-            Color::Blue
+            SyscallCategory::File
         }
 
         // Process/Thread Control - Magenta
         "fork" | "vfork" | "clone" | "clone3" | "execve" | "execveat" | "exit" | "exit_group"
         | "wait4" | "waitid" | "waitpid" | "kill" | "tkill" | "tgkill" | "getpid" | "gettid"
         | "getppid" | "getpgid" | "getsid" | "setpgid" | "setsid" | "ptrace" | "prctl" => {
-            Color::Magenta
+            SyscallCategory::Process
         }
 
         // Memory Management - Cyan
         "mmap" | "mmap2" | "munmap" | "mremap" | "msync" | "mprotect" | "madvise" | "mlock"
         | "mlock2" | "munlock" | "mlockall" | "munlockall" | "brk" | "sbrk" | "memfd_create"
-        | "userfaultfd" | "remap_file_pages" => Color::Cyan,
+        | "userfaultfd" | "remap_file_pages" => SyscallCategory::Memory,
 
         // Network/IPC - Green
         "socket" | "bind" | "listen" | "accept" | "accept4" | "connect" | "send" | "sendto"
         | "sendmsg" | "sendmmsg" | "recv" | "recvfrom" | "recvmsg" | "recvmmsg" | "shutdown"
         | "getsockopt" | "setsockopt" | "pipe" | "pipe2" | "socketpair" | "getpeername"
-        | "getsockname" => Color::Green,
+        | "getsockname" => SyscallCategory::Network,
 
         // Filesystem Operations - Yellow
         "mkdir" | "mkdirat" | "rmdir" | "unlink" | "unlinkat" | "rename" | "renameat"
         | "renameat2" | "link" | "linkat" | "symlink" | "symlinkat" | "readlink" | "readlinkat"
         | "chmod" | "fchmod" | "fchmodat" | "chown" | "fchown" | "lchown" | "fchownat"
         | "chdir" | "fchdir" | "getcwd" | "mount" | "umount" | "umount2" | "chroot"
-        | "pivot_root" | "getdents" | "getdents64" | "statfs" | "fstatfs" => Color::Yellow,
+        | "pivot_root" | "getdents" | "getdents64" | "statfs" | "fstatfs" => {
+            SyscallCategory::Filesystem
+        }
 
         // Time/Timers - LightBlue
         "gettimeofday" | "settimeofday" | "clock_gettime" | "clock_settime" | "clock_getres"
         | "clock_nanosleep" | "time" | "stime" | "nanosleep" | "timer_create" | "timer_settime"
         | "timer_gettime" | "timer_delete" | "timer_getoverrun" | "alarm" | "setitimer"
-        | "getitimer" => Color::LightBlue,
+        | "getitimer" => SyscallCategory::Time,
 
         // Signal Handling - LightRed
         "signal" | "sigaction" | "sigreturn" | "rt_sigaction" | "rt_sigreturn" | "sigprocmask"
         | "rt_sigprocmask" | "sigpending" | "rt_sigpending" | "sigsuspend" | "rt_sigsuspend"
-        | "signalfd" | "signalfd4" => Color::LightRed,
+        | "signalfd" | "signalfd4" => SyscallCategory::Signal,
 
         // Security/Permissions - LightMagenta
         "setuid" | "setgid" | "setreuid" | "setregid" | "setresuid" | "setresgid" | "getuid"
         | "getgid" | "geteuid" | "getegid" | "capget" | "capset" | "setgroups" | "getgroups"
-        | "seccomp" | "keyctl" | "add_key" | "request_key" => Color::LightMagenta,
+        | "seccomp" | "keyctl" | "add_key" | "request_key" => SyscallCategory::Security,
 
         // Polling/Events - LightGreen
         "select" | "pselect6" | "poll" | "ppoll" | "epoll_create" | "epoll_create1"
         | "epoll_ctl" | "epoll_wait" | "epoll_pwait" | "inotify_init" | "inotify_init1"
         | "inotify_add_watch" | "inotify_rm_watch" | "eventfd" | "eventfd2" | "timerfd_create"
-        | "timerfd_settime" | "timerfd_gettime" => Color::LightGreen,
+        | "timerfd_settime" | "timerfd_gettime" => SyscallCategory::Polling,
 
         // Resource Limits - LightYellow
         "getrlimit" | "setrlimit" | "prlimit64" | "getrusage" | "getpriority" | "setpriority"
         | "nice" | "sched_setscheduler" | "sched_getscheduler" | "sched_setparam"
         | "sched_getparam" | "sched_setaffinity" | "sched_getaffinity" | "sched_yield" => {
-            Color::LightYellow
+            SyscallCategory::Resource
         }
 
-        // Default - White
-        _ => Color::White,
+        _ => SyscallCategory::Other,
+    }
+}
+
+/// Short description of what a syscall does, for the common ones, in the manner of the NAME
+/// line of its man page
+pub fn syscall_description(name: &str) -> Option<&'static str> {
+    let description = match name {
+        // File I/O
+        "read" => "read from a file descriptor",
+        "write" => "write to a file descriptor",
+        "pread" | "pread64" => "read from a file descriptor at a given offset",
+        "pwrite" | "pwrite64" => "write to a file descriptor at a given offset",
+        "readv" => "read data into multiple buffers",
+        "writev" => "write data from multiple buffers",
+        "preadv" => "read data into multiple buffers, at a given offset",
+        "pwritev" => "write data from multiple buffers, at a given offset",
+        "open" | "openat" | "openat2" => "open and possibly create a file",
+        "creat" => "create a file, or truncate an existing one, and open it for writing",
+        "close" => "close a file descriptor",
+        "dup" | "dup2" | "dup3" => "duplicate a file descriptor",
+        "lseek" | "llseek" | "_llseek" => "reposition the read/write file offset",
+        "fcntl" => "manipulate a file descriptor: flags, locks, duplication",
+        "ioctl" => "control a device, with a request specific to it",
+        "stat" | "lstat" | "fstat" | "fstatat" | "newfstatat" => "get file status",
+        "statx" => "get extended file status",
+        "truncate" | "ftruncate" => "truncate a file to a specified length",
+        "fsync" => "synchronize a file's in-core state with the storage device",
+        "fdatasync" => "synchronize a file's data, without the metadata not needed to read it",
+        "sync" => "commit the filesystem caches to disk",
+        "syncfs" => "commit the caches of the filesystem holding a file to disk",
+        "access" | "faccessat" | "faccessat2" => "check the user's permissions for a file",
+
+        // Process/Thread Control
+        "fork" => "create a child process",
+        "vfork" => "create a child process and block the parent until it execs or exits",
+        "clone" | "clone3" => "create a child process or thread",
+        "execve" | "execveat" => "execute a program, replacing the process image",
+        "exit" => "terminate the calling thread",
+        "exit_group" => "terminate all the threads of the process",
+        "wait4" | "waitpid" => "wait for a process to change state",
+        "waitid" => "wait for a process to change state, with finer control",
+        "kill" => "send a signal to a process",
+        "tkill" | "tgkill" => "send a signal to a thread",
+        "getpid" => "get the process ID",
+        "gettid" => "get the thread ID",
+        "getppid" => "get the parent process ID",
+        "getpgid" => "get the process group ID",
+        "setpgid" => "set the process group ID",
+        "getsid" => "get the session ID",
+        "setsid" => "create a session and set the process group ID",
+        "ptrace" => "trace another process",
+        "prctl" => "operations on a process or thread",
+
+        // Memory Management
+        "mmap" | "mmap2" => "map files or devices into memory",
+        "munmap" => "unmap files or devices from memory",
+        "mremap" => "remap a virtual memory address",
+        "msync" => "synchronize a file with a memory map",
+        "mprotect" => "set protection on a region of memory",
+        "madvise" => "give advice about the use of memory",
+        "mlock" | "mlock2" => "lock memory, keeping it from being paged out",
+        "munlock" => "unlock memory",
+        "mlockall" => "lock all the memory of the process",
+        "munlockall" => "unlock all the memory of the process",
+        "brk" | "sbrk" => "change the data segment size",
+        "memfd_create" => "create an anonymous file",
+        "userfaultfd" => "create a file descriptor for handling page faults in user space",
+        "remap_file_pages" => "create a nonlinear file mapping",
+
+        // Network/IPC
+        "socket" => "create an endpoint for communication",
+        "bind" => "bind a name to a socket",
+        "listen" => "listen for connections on a socket",
+        "accept" | "accept4" => "accept a connection on a socket",
+        "connect" => "initiate a connection on a socket",
+        "send" | "sendto" | "sendmsg" => "send a message on a socket",
+        "sendmmsg" => "send multiple messages on a socket",
+        "recv" | "recvfrom" | "recvmsg" => "receive a message from a socket",
+        "recvmmsg" => "receive multiple messages from a socket",
+        "shutdown" => "shut down part of a full-duplex connection",
+        "getsockopt" => "get options on a socket",
+        "setsockopt" => "set options on a socket",
+        "pipe" | "pipe2" => "create a pipe",
+        "socketpair" => "create a pair of connected sockets",
+        "getpeername" => "get the address of the peer connected to a socket",
+        "getsockname" => "get the address a socket is bound to",
+
+        // Filesystem Operations
+        "mkdir" | "mkdirat" => "create a directory",
+        "rmdir" => "delete a directory",
+        "unlink" | "unlinkat" => "delete a name and possibly the file it refers to",
+        "rename" | "renameat" | "renameat2" => "change the name or location of a file",
+        "link" | "linkat" => "make a new name for a file",
+        "symlink" | "symlinkat" => "make a new name for a file, as a symbolic link",
+        "readlink" | "readlinkat" => "read the value of a symbolic link",
+        "chmod" | "fchmod" | "fchmodat" => "change the permissions of a file",
+        "chown" | "fchown" | "lchown" | "fchownat" => "change the ownership of a file",
+        "chdir" | "fchdir" => "change the working directory",
+        "getcwd" => "get the current working directory",
+        "mount" => "mount a filesystem",
+        "umount" | "umount2" => "unmount a filesystem",
+        "chroot" => "change the root directory",
+        "pivot_root" => "change the root mount",
+        "getdents" | "getdents64" => "get directory entries",
+        "statfs" | "fstatfs" => "get filesystem statistics",
+
+        // Time/Timers
+        "gettimeofday" | "time" => "get the time",
+        "settimeofday" | "stime" => "set the time",
+        "clock_gettime" => "get the time of a clock",
+        "clock_settime" => "set the time of a clock",
+        "clock_getres" => "get the resolution of a clock",
+        "nanosleep" | "clock_nanosleep" => "high-resolution sleep",
+        "timer_create" => "create a POSIX per-process timer",
+        "timer_settime" => "arm or disarm a POSIX per-process timer",
+        "timer_gettime" => "get the time left on a POSIX per-process timer",
+        "timer_delete" => "delete a POSIX per-process timer",
+        "timer_getoverrun" => "get the overrun count of a POSIX per-process timer",
+        "alarm" => "set an alarm clock for delivery of a signal",
+        "setitimer" => "set the value of an interval timer",
+        "getitimer" => "get the value of an interval timer",
+
+        // Signal Handling
+        "signal" => "ANSI C signal handling",
+        "sigaction" | "rt_sigaction" => "examine and change a signal action",
+        "sigreturn" | "rt_sigreturn" => "return from a signal handler",
+        "sigprocmask" | "rt_sigprocmask" => "examine and change blocked signals",
+        "sigpending" | "rt_sigpending" => "examine pending signals",
+        "sigsuspend" | "rt_sigsuspend" => "wait for a signal",
+        "signalfd" | "signalfd4" => "create a file descriptor for accepting signals",
+
+        // Security/Permissions
+        "setuid" => "set the user identity",
+        "setgid" => "set the group identity",
+        "setreuid" => "set the real and effective user IDs",
+        "setregid" => "set the real and effective group IDs",
+        "setresuid" => "set the real, effective and saved user IDs",
+        "setresgid" => "set the real, effective and saved group IDs",
+        "getuid" => "get the real user ID",
+        "geteuid" => "get the effective user ID",
+        "getgid" => "get the real group ID",
+        "getegid" => "get the effective group ID",
+        "capget" => "get the capabilities of a thread",
+        "capset" => "set the capabilities of a thread",
+        "getgroups" => "get the supplementary group IDs",
+        "setgroups" => "set the supplementary group IDs",
+        "seccomp" => "operate on the secure computing state of the process",
+        "keyctl" => "manipulate the kernel's key management facility",
+        "add_key" => "add a key to the kernel's key management facility",
+        "request_key" => "request a key from the kernel's key management facility",
+
+        // Polling/Events
+        "select" | "pselect6" => "synchronous I/O multiplexing",
+        "poll" | "ppoll" => "wait for some event on a file descriptor",
+        "epoll_create" | "epoll_create1" => "open an epoll file descriptor",
+        "epoll_ctl" => "control interface for an epoll file descriptor",
+        "epoll_wait" | "epoll_pwait" => "wait for an I/O event on an epoll file descriptor",
+        "inotify_init" | "inotify_init1" => "initialize an inotify instance",
+        "inotify_add_watch" => "add a watch to an initialized inotify instance",
+        "inotify_rm_watch" => "remove an existing watch from an inotify instance",
+        "eventfd" | "eventfd2" => "create a file descriptor for event notification",
+        "timerfd_create" => "create a timer that notifies via a file descriptor",
+        "timerfd_settime" => "arm or disarm a timer that notifies via a file descriptor",
+        "timerfd_gettime" => "get the time left on a timer that notifies via a file descriptor",
+
+        // Resource Limits
+        "getrlimit" => "get resource limits",
+        "setrlimit" => "set resource limits",
+        "prlimit64" => "get and set the resource limits of a process",
+        "getrusage" => "get resource usage",
+        "getpriority" => "get the scheduling priority",
+        "setpriority" | "nice" => "set the scheduling priority",
+        "sched_setscheduler" => "set the scheduling policy and parameters",
+        "sched_getscheduler" => "get the scheduling policy",
+        "sched_setparam" => "set the scheduling parameters",
+        "sched_getparam" => "get the scheduling parameters",
+        "sched_setaffinity" => "set the CPU affinity mask of a thread",
+        "sched_getaffinity" => "get the CPU affinity mask of a thread",
+        "sched_yield" => "yield the processor",
+
+        _ => return None,
+    };
+    Some(description)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_color_overrides() {
+        let theme = Theme::dark();
+        let colors = SyscallColors::parse(
+            r##"
+            [categories]
+            file = "light cyan"
+            memory = "#808080"
+
+            [syscalls]
+            read = "Red"
+            "##,
+        )
+        .unwrap();
+
+        assert_eq!(syscall_category_color("read", &colors, &theme), Color::Red);
+        assert_eq!(
+            syscall_category_color("write", &colors, &theme),
+            Color::LightCyan
+        );
+        assert_eq!(
+            syscall_category_color("mmap", &colors, &theme),
+            Color::Rgb(128, 128, 128)
+        );
+        assert_eq!(
+            syscall_category_color("socket", &colors, &theme),
+            Color::Green
+        );
+
+        let defaults = SyscallColors::default();
+        assert_eq!(
+            syscall_category_color("read", &defaults, &theme),
+            Color::Blue
+        );
+
+        assert!(SyscallColors::parse("[syscalls]\nread = \"reddish\"").is_err());
+        assert!(SyscallColors::parse("[categories]\nnope = \"red\"").is_err());
+    }
+
+    #[test]
+    fn test_syscall_description() {
+        assert_eq!(
+            syscall_description("openat"),
+            Some("open and possibly create a file")
+        );
+        assert_eq!(
+            syscall_description("rt_sigprocmask"),
+            syscall_description("sigprocmask")
+        );
+        assert_eq!(syscall_description("not_a_syscall"), None);
     }
 }
@@ -0,0 +1,191 @@
+use super::syscall_colors::SyscallCategory;
+use ratatui::style::Color;
+use serde::Deserialize;
+
+/// Which palette to draw with, for a dark or a light terminal background
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ThemeName {
+    #[default]
+    Dark,
+    Light,
+}
+
+impl ThemeName {
+    /// Guess from `COLORFGBG`, which some terminals set to `fg;bg` with palette indices
+    pub fn detect() -> Option<Self> {
+        Self::from_colorfgbg(&std::env::var("COLORFGBG").ok()?)
+    }
+
+    fn from_colorfgbg(value: &str) -> Option<Self> {
+        let background: u8 = value.rsplit(';').next()?.parse().ok()?;
+        // White and the bright colors, except bright black
+        if background == 7 || (9..=15).contains(&background) {
+            Some(ThemeName::Light)
+        } else {
+            Some(ThemeName::Dark)
+        }
+    }
+}
+
+/// Colors of the UI, by the role they play
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    pub name: ThemeName,
+    pub text: Color,         // Syscall arguments and return values, prompts
+    pub muted: Color,        // Secondary details, like durations
+    pub dim: Color,          // Dividers, footer, argument values, backtrace frames
+    pub selection: Color,    // Background of the selected line
+    pub search_match: Color, // Background of the other search matches
+    pub accent: Color,       // Header bar, table headers, exits
+    pub highlight: Color,    // Keys, signals, unfinished calls, status messages
+    pub on_highlight: Color, // Text over a highlight background
+    pub error: Color,
+    pub success: Color,
+    pub special: Color, // Backtraces, stops, calls never resumed
+}
+
+impl Theme {
+    pub fn new(name: ThemeName) -> Self {
+        match name {
+            ThemeName::Dark => Self::dark(),
+            ThemeName::Light => Self::light(),
+        }
+    }
+
+    pub fn dark() -> Self {
+        Self {
+            name: ThemeName::Dark,
+            text: Color::White,
+            muted: Color::Gray,
+            dim: Color::DarkGray,
+            selection: Color::DarkGray,
+            search_match: Color::Rgb(60, 60, 0),
+            accent: Color::Cyan,
+            highlight: Color::Yellow,
+            on_highlight: Color::Black,
+            error: Color::Red,
+            success: Color::Green,
+            special: Color::Magenta,
+        }
+    }
+
+    /// Darker colors from the 256-color palette, as the basic ones are tuned for dark
+    /// backgrounds by most terminals
+    pub fn light() -> Self {
+        Self {
+            name: ThemeName::Light,
+            text: Color::Black,
+            muted: Color::Indexed(238),
+            dim: Color::Indexed(243),
+            selection: Color::Indexed(253),
+            search_match: Color::Indexed(230),
+            accent: Color::Indexed(25),
+            highlight: Color::Indexed(130),
+            on_highlight: Color::White,
+            error: Color::Indexed(160),
+            success: Color::Indexed(28),
+            special: Color::Indexed(127),
+        }
+    }
+
+    /// Color of the `index`th process of the trace, cycling through a palette
+    pub fn pid_color(&self, index: usize) -> Color {
+        let palette: &[Color] = match self.name {
+            ThemeName::Dark => &[
+                Color::Blue,
+                Color::Green,
+                Color::Yellow,
+                Color::Magenta,
+                Color::Cyan,
+                Color::LightBlue,
+                Color::LightGreen,
+                Color::LightMagenta,
+            ],
+            ThemeName::Light => &[
+                Color::Indexed(26),
+                Color::Indexed(28),
+                Color::Indexed(136),
+                Color::Indexed(127),
+                Color::Indexed(30),
+                Color::Indexed(32),
+                Color::Indexed(64),
+                Color::Indexed(90),
+            ],
+        };
+        palette[index % palette.len()]
+    }
+
+    /// Built-in color of a syscall category
+    pub fn category_color(&self, category: SyscallCategory) -> Color {
+        match self.name {
+            ThemeName::Dark => match category {
+                SyscallCategory::File => Color::Blue,
+                SyscallCategory::Process => Color::Magenta,
+                SyscallCategory::Memory => Color::Cyan,
+                SyscallCategory::Network => Color::Green,
+                SyscallCategory::Filesystem => Color::Yellow,
+                SyscallCategory::Time => Color::LightBlue,
+                SyscallCategory::Signal => Color::LightRed,
+                SyscallCategory::Security => Color::LightMagenta,
+                SyscallCategory::Polling => Color::LightGreen,
+                SyscallCategory::Resource => Color::LightYellow,
+                SyscallCategory::Other => Color::White,
+            },
+            ThemeName::Light => match category {
+                SyscallCategory::File => Color::Indexed(26),
+                SyscallCategory::Process => Color::Indexed(127),
+                SyscallCategory::Memory => Color::Indexed(30),
+                SyscallCategory::Network => Color::Indexed(28),
+                SyscallCategory::Filesystem => Color::Indexed(136),
+                SyscallCategory::Time => Color::Indexed(32),
+                SyscallCategory::Signal => Color::Indexed(160),
+                SyscallCategory::Security => Color::Indexed(90),
+                SyscallCategory::Polling => Color::Indexed(64),
+                SyscallCategory::Resource => Color::Indexed(94),
+                SyscallCategory::Other => Color::Black,
+            },
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_themes_differ() {
+        let dark = Theme::new(ThemeName::Dark);
+        let light = Theme::new(ThemeName::Light);
+
+        assert_ne!(dark.text, light.text);
+        assert_ne!(dark.dim, light.dim);
+        assert_ne!(dark.selection, light.selection);
+        assert_ne!(dark.highlight, light.highlight);
+        assert_ne!(
+            dark.category_color(SyscallCategory::Other),
+            light.category_color(SyscallCategory::Other)
+        );
+        assert_eq!(dark.category_color(SyscallCategory::File), Color::Blue);
+        assert_ne!(dark.pid_color(2), light.pid_color(2));
+        assert_eq!(dark.pid_color(0), dark.pid_color(8));
+    }
+
+    #[test]
+    fn test_detect_from_colorfgbg() {
+        assert_eq!(ThemeName::from_colorfgbg("0;15"), Some(ThemeName::Light));
+        assert_eq!(
+            ThemeName::from_colorfgbg("0;default;7"),
+            Some(ThemeName::Light)
+        );
+        assert_eq!(ThemeName::from_colorfgbg("15;0"), Some(ThemeName::Dark));
+        assert_eq!(ThemeName::from_colorfgbg("7;8"), Some(ThemeName::Dark));
+        assert_eq!(ThemeName::from_colorfgbg("default"), None);
+    }
+}
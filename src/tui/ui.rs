@@ -1,4 +1,8 @@
-use super::app::{App, split_arguments};
+use super::app::{
+    App, SearchScope, SortMode, argument_value, argument_width, format_bytes, format_duration,
+    format_more_arguments, format_return, format_syscall_group, wrap_chunks,
+};
+use super::theme::Theme;
 use ratatui::{
     Frame,
     layout::{Constraint, Direction, Flex, Layout, Rect},
@@ -23,17 +27,32 @@ pub fn draw(f: &mut Frame, app: &mut App) {
     draw_header(f, app, chunks[0]);
 
     // Draw divider
-    draw_divider(f, chunks[1]);
-
-    // Draw main list
-    draw_list(f, app, chunks[2]);
+    draw_divider(f, chunks[1], &app.theme);
+
+    // Draw main list, with the process tree beside it if open
+    if app.show_process_tree {
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Min(0), Constraint::Length(32)])
+            .split(chunks[2]);
+        draw_list(f, app, columns[0]);
+        draw_process_tree(f, app, columns[1]);
+    } else {
+        draw_list(f, app, chunks[2]);
+    }
 
     if app.search_state.active {
         // Draw search bar
         draw_search_bar(f, app, chunks[3]);
+    } else if let Some(input) = &app.duration_prompt {
+        draw_duration_prompt(f, app, input, chunks[3]);
+    } else if let Some(input) = &app.jump_prompt {
+        draw_jump_prompt(f, app, input, chunks[3]);
+    } else if let Some((_, input)) = &app.note_prompt {
+        draw_note_prompt(f, app, input, chunks[3]);
     } else {
         // Draw divider
-        draw_divider(f, chunks[3]);
+        draw_divider(f, chunks[3], &app.theme);
     }
 
     // Draw footer
@@ -41,16 +60,57 @@ pub fn draw(f: &mut Frame, app: &mut App) {
 
     // Draw help modal on top if active
     if app.show_help {
-        draw_help(f);
+        draw_help(f, &app.theme);
     }
 
     // Draw filter modal on top if active
     if app.show_filter_modal {
         draw_filter_modal(f, app);
     }
+
+    // Draw stats modal on top if active
+    if app.show_stats_modal {
+        draw_stats_modal(f, app);
+    }
+
+    // Draw duration histogram on top if active
+    if app.show_histogram {
+        draw_histogram(f, app);
+    }
+
+    // Draw PID picker on top if active
+    if app.show_pid_picker {
+        draw_pid_picker(f, app);
+    }
+
+    // Draw program output on top if active
+    if app.show_program_output {
+        draw_program_output(f, app);
+    }
+
+    // Draw errno filter on top if active
+    if app.show_errno_filter {
+        draw_errno_filter(f, app);
+    }
+
+    // Draw parse errors on top if active
+    if app.show_parse_errors {
+        draw_parse_errors(f, app);
+    }
+
+    // Draw the raw line of the current entry on top if active
+    if app.show_raw_line {
+        draw_raw_line(f, app);
+    }
+
+    // Draw the description of the current syscall on top if active
+    if app.show_syscall_help {
+        draw_syscall_help(f, app);
+    }
 }
 
 fn draw_header(f: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
     let file_name = app
         .file_path
         .as_ref()
@@ -58,56 +118,136 @@ fn draw_header(f: &mut Frame, app: &App, area: Rect) {
         .and_then(|n| n.to_str())
         .unwrap_or("strace");
 
-    let header_text = format!(
+    let file_name = if app.file_count > 1 {
+        format!("{} [{}/{}]", file_name, app.file_index + 1, app.file_count)
+    } else {
+        file_name.to_string()
+    };
+
+    let unfinished = if app.summary.never_resumed > 0 {
+        format!(
+            "{} ({} never resumed)",
+            app.summary.unfinished, app.summary.never_resumed
+        )
+    } else {
+        app.summary.unfinished.to_string()
+    };
+
+    let mut header_text = format!(
         "strace-tui: {} | Syscalls: {} | Failed: {} | Unfinished: {} | PIDs: {} | Signals: {}",
         file_name,
         app.summary.total_syscalls,
         app.summary.failed_syscalls,
-        app.summary.unfinished,
+        unfinished,
         app.summary.unique_pids.len(),
         app.summary.signals,
     );
 
+    if app.summary.bytes_read > 0 || app.summary.bytes_written > 0 {
+        header_text.push_str(&format!(
+            " | Read: {} | Written: {}",
+            format_bytes(app.summary.bytes_read),
+            format_bytes(app.summary.bytes_written)
+        ));
+    }
+
+    if let Some(max) = app.truncated_at {
+        header_text.push_str(&format!(" | Truncated at {} entries", max));
+    }
+
+    // Show the stripped prefix once, since backtrace paths are displayed relative to it
+    if app.strip_path_prefix && !app.common_path_prefix.is_empty() {
+        header_text.push_str(&format!(" | Paths: {}", app.common_path_prefix));
+    }
+
+    if app.hide_backtraces {
+        header_text.push_str(" | Backtraces hidden");
+    }
+
+    if let Some(min) = app.min_duration {
+        header_text.push_str(&format!(" | Duration ≥ {}", format_duration(min)));
+        if app.keep_untimed {
+            header_text.push_str(" (and untimed)");
+        }
+    }
+
+    if !app.errno_filter.is_empty() {
+        let mut codes: Vec<&str> = app.errno_filter.iter().map(String::as_str).collect();
+        codes.sort_unstable();
+        header_text.push_str(&format!(" | Errno: {}", codes.join(",")));
+    }
+
+    if app.sort_mode != SortMode::Trace {
+        header_text.push_str(&format!(" | Sorted by {}", app.sort_mode.label()));
+    }
+    if app.group_by_syscall {
+        header_text.push_str(" | Grouped by syscall");
+    }
+
+    // Children that never appear mean the trace likely misses them (no -f)
+    if app.show_follow_fork_hint {
+        header_text.push_str(&format!(
+            " | Hint: {} child processes not traced, capture with strace -f",
+            app.process_graph.untraced_children
+        ));
+    }
+
     let header = Paragraph::new(header_text).style(
         Style::default()
-            .fg(Color::Cyan)
+            .fg(theme.accent)
             .add_modifier(Modifier::BOLD),
     );
 
     f.render_widget(header, area);
 }
 
-fn draw_divider(f: &mut Frame, area: Rect) {
+fn draw_divider(f: &mut Frame, area: Rect, theme: &Theme) {
     let divider = Block::default()
         .borders(Borders::TOP)
-        .border_style(Style::default().fg(Color::DarkGray));
+        .border_style(Style::default().fg(theme.dim));
 
     f.render_widget(divider, area);
 }
 
 /// Split syscall name into spans, coloring "unfinished" and "resumed" keywords
-fn format_syscall_name_spans(
-    syscall_name: &str,
+fn format_syscall_name_spans<'a>(
+    syscall_name: &'a str,
     is_unfinished: bool,
     is_resumed: bool,
+    never_resumed: bool,
+    orphan_resumed: bool,
     syscall_color: Color,
-) -> Vec<Span<'_>> {
-    if is_unfinished {
+    theme: &Theme,
+) -> Vec<Span<'a>> {
+    if never_resumed {
+        vec![
+            Span::styled(syscall_name.to_string(), Style::default().fg(syscall_color)),
+            Span::styled(" <never resumed>", Style::default().fg(theme.special)),
+        ]
+    } else if is_unfinished {
         // Color "unfinished" in yellow, rest in syscall_color
         vec![
             Span::styled(syscall_name.to_string(), Style::default().fg(syscall_color)),
-            Span::styled(" <unfinished>", Style::default().fg(Color::Yellow)),
+            Span::styled(" <unfinished>", Style::default().fg(theme.highlight)),
         ]
     } else if is_resumed {
         // Reconstruct format: <... syscall_name resumed>
         // Color "resumed" in green
-        vec![
-            Span::styled("<... ", Style::default().fg(Color::DarkGray)),
+        let mut spans = vec![
+            Span::styled("<... ", Style::default().fg(theme.dim)),
             Span::styled(syscall_name.to_string(), Style::default().fg(syscall_color)),
-            Span::styled(" ", Style::default().fg(Color::DarkGray)),
-            Span::styled("resumed", Style::default().fg(Color::Green)),
-            Span::styled(">", Style::default().fg(Color::DarkGray)),
-        ]
+            Span::styled(" ", Style::default().fg(theme.dim)),
+            Span::styled("resumed", Style::default().fg(theme.success)),
+            Span::styled(">", Style::default().fg(theme.dim)),
+        ];
+        // Its unfinished half came before the trace started
+        if orphan_resumed {
+            spans.push(Span::styled(
+                " <start not traced>",
+                Style::default().fg(theme.special),
+            ));
+        }
+        spans
     } else {
         vec![Span::styled(
             syscall_name.to_string(),
@@ -122,7 +262,7 @@ fn draw_list(f: &mut Frame, app: &mut App, area: Rect) {
 
     // Calculate scroll offset to keep selected item visible
     let visible_height = area.height as usize; // No borders, use full height
-    app.update_visible_height(visible_height);
+    app.update_list_area(area);
 
     if app.selected_line >= app.scroll_offset + visible_height {
         app.scroll_offset = app.selected_line.saturating_sub(visible_height - 1);
@@ -130,17 +270,19 @@ fn draw_list(f: &mut Frame, app: &mut App, area: Rect) {
         app.scroll_offset = app.selected_line;
     }
 
+    let theme = &app.theme;
     let mut items = Vec::new();
 
     // Only render items in the visible window
     let start = app.scroll_offset;
     let end = (app.scroll_offset + visible_height).min(app.display_lines.len());
-    let width = area.width as usize;
+    let number_width = app.entry_number_width();
+    let width = app.list_content_width();
 
     for line_idx in start..end {
         let display_line = &app.display_lines[line_idx];
 
-        let line_content = match display_line {
+        let mut line_content = match display_line {
             DisplayLine::SyscallHeader {
                 entry_idx,
                 is_hidden,
@@ -157,40 +299,55 @@ fn draw_list(f: &mut Frame, app: &mut App, area: Rect) {
 
                 // Override color if hidden
                 let base_color_override = if *is_hidden && app.show_hidden {
-                    Some(Color::DarkGray)
+                    Some(theme.dim)
                 } else {
                     None
                 };
 
                 if is_signal || is_exit {
                     let syscall_info = if let Some(signal) = &entry.signal {
-                        format!("--- {} ---", signal.signal_name)
+                        if signal.stopped {
+                            format!("--- stopped by {} ---", signal.signal_name)
+                        } else {
+                            format!("--- {} ---", signal.signal_name)
+                        }
                     } else if let Some(exit) = &entry.exit_info {
-                        format!("+++ exit {} +++", exit.code)
+                        match &exit.signal {
+                            Some(signal) => format!("+++ killed by {} +++", signal),
+                            None => format!("+++ exit {} +++", exit.code),
+                        }
                     } else {
                         unreachable!()
                     };
 
                     // Get graph for this entry
-                    let graph_chars = app
-                        .process_graph
-                        .render_graph_for_entry(*entry_idx, &app.entries);
+                    let graph_chars = if app.show_graph && app.shows_trace_order() {
+                        app.process_graph.render_graph_for_entry(
+                            *entry_idx,
+                            &app.entries,
+                            &app.theme,
+                        )
+                    } else {
+                        Vec::new()
+                    };
                     let has_graph = !graph_chars.is_empty();
                     let graph_len = if has_graph { graph_chars.len() + 4 } else { 0 }; // +4 for "  "+"  "
 
-                    let pid_color = app.process_graph.get_color(entry.pid);
+                    let pid_color = app.process_graph.get_color(entry.pid, &app.theme);
                     let left_part = format!("{} {}", arrow, syscall_info);
                     let left_len = left_part.chars().count();
 
-                    let metadata_pid = format!("[{}]", entry.pid);
-                    let metadata_time = format!(" {}", entry.timestamp);
+                    let (metadata_pid, metadata_time) = header_metadata(app, *entry_idx);
                     let metadata_len = metadata_pid.chars().count() + metadata_time.chars().count();
 
+                    let is_stop = entry.signal.as_ref().is_some_and(|signal| signal.stopped);
                     let color = base_color_override.unwrap_or({
-                        if is_signal {
-                            Color::Yellow
+                        if is_stop {
+                            theme.special
+                        } else if is_signal {
+                            theme.highlight
                         } else {
-                            Color::Cyan
+                            theme.accent
                         }
                     });
 
@@ -243,30 +400,44 @@ fn draw_list(f: &mut Frame, app: &mut App, area: Rect) {
                 } else {
                     // Normal syscall - color the syscall name, rest is white or red
                     let args_preview = &entry.arguments;
-                    let ret = entry.return_value.as_deref().unwrap_or("?");
+                    let mut ret = entry.return_value.as_deref().unwrap_or("?").to_string();
+                    if let Some(path) = &entry.return_fd_path {
+                        ret.push_str(&format!("<{}>", path));
+                    }
 
                     // Get graph for this entry
-                    let graph_chars = app
-                        .process_graph
-                        .render_graph_for_entry(*entry_idx, &app.entries);
+                    let graph_chars = if app.show_graph && app.shows_trace_order() {
+                        app.process_graph.render_graph_for_entry(
+                            *entry_idx,
+                            &app.entries,
+                            &app.theme,
+                        )
+                    } else {
+                        Vec::new()
+                    };
                     let has_graph = !graph_chars.is_empty();
                     let graph_len = if has_graph { graph_chars.len() + 4 } else { 0 }; // +4 for "  "+"  "
 
                     // Build the parts
                     let arrow_str = format!("{} ", arrow);
                     let syscall_name = &entry.syscall_name;
-                    let args_and_ret = format!("({}) = {}", args_preview, ret);
-                    let pid_color = app.process_graph.get_color(entry.pid);
-                    let metadata_pid = format!("[{}]", entry.pid);
-                    let metadata_time = format!(" {}", entry.timestamp);
+                    // A call that never returned has no return value to show, not even "?"
+                    let args_and_ret = if entry.never_resumed {
+                        format!("({})", args_preview)
+                    } else {
+                        format!("({}) = {}", args_preview, ret)
+                    };
+                    let pid_color = app.process_graph.get_color(entry.pid, &app.theme);
+                    let (metadata_pid, metadata_time) = header_metadata(app, *entry_idx);
 
                     // Determine colors
-                    let syscall_color =
-                        base_color_override.unwrap_or_else(|| syscall_category_color(syscall_name));
+                    let syscall_color = base_color_override.unwrap_or_else(|| {
+                        syscall_category_color(syscall_name, &app.config.colors, theme)
+                    });
                     let rest_color = base_color_override.unwrap_or(if has_error {
-                        Color::Red
+                        theme.error
                     } else {
-                        Color::White
+                        theme.text
                     });
 
                     // Get syscall name spans (handles unfinished/resumed coloring)
@@ -274,7 +445,10 @@ fn draw_list(f: &mut Frame, app: &mut App, area: Rect) {
                         syscall_name,
                         entry.is_unfinished,
                         entry.is_resumed,
+                        entry.never_resumed,
+                        entry.orphan_resumed,
                         syscall_color,
+                        theme,
                     );
 
                     // Calculate lengths (sum up all syscall spans)
@@ -390,36 +564,125 @@ fn draw_list(f: &mut Frame, app: &mut App, area: Rect) {
                 let entry = &app.entries[*entry_idx];
                 let args_expanded = app.expanded_arguments.contains(entry_idx);
                 let args_arrow = if args_expanded { "▼" } else { "▶" };
-                let args = split_arguments(&entry.arguments);
+                let args = entry.args();
                 let prefix_str = App::tree_prefix_to_string_header(tree_prefix);
                 let content = format!("{} Arguments ({})", args_arrow, args.len());
                 Line::from(vec![
                     Span::styled(prefix_str, Style::default()),
-                    Span::styled(content, Style::default().fg(Color::Gray)),
+                    Span::styled(content, Style::default().fg(theme.muted)),
                 ])
             }
 
             DisplayLine::ArgumentLine {
                 entry_idx,
                 arg_idx,
+                wrap_row,
                 tree_prefix,
                 ..
             } => {
                 let entry = &app.entries[*entry_idx];
-                let args = split_arguments(&entry.arguments);
+                let args = entry.args();
                 if let Some(arg) = args.get(*arg_idx) {
                     let prefix_str = App::tree_prefix_to_string(tree_prefix);
-                    let max_len = width.saturating_sub(prefix_str.len() + 1);
-                    let content = truncate(arg, max_len);
+                    let content = if app.wrap_arguments {
+                        wrap_chunks(arg, argument_width(tree_prefix, width))
+                            .get(*wrap_row)
+                            .map_or(String::new(), |chunk| chunk.to_string())
+                    } else {
+                        let max_len = width.saturating_sub(prefix_str.len() + 1);
+                        truncate(arg, max_len)
+                    };
                     Line::from(vec![
                         Span::styled(prefix_str, Style::default()),
-                        Span::styled(content, Style::default().fg(Color::DarkGray)),
+                        Span::styled(content, Style::default().fg(theme.dim)),
                     ])
                 } else {
                     continue;
                 }
             }
 
+            DisplayLine::RawArguments {
+                entry_idx,
+                wrap_row,
+                tree_prefix,
+                ..
+            } => {
+                let arguments = &app.entries[*entry_idx].arguments;
+                let content = wrap_chunks(arguments, argument_width(tree_prefix, width))
+                    .get(*wrap_row)
+                    .map_or(String::new(), |chunk| chunk.to_string());
+                Line::from(vec![
+                    Span::styled(App::tree_prefix_to_string(tree_prefix), Style::default()),
+                    Span::styled(content, Style::default().fg(theme.dim)),
+                ])
+            }
+
+            DisplayLine::ArgumentField {
+                entry_idx,
+                arg_idx,
+                field,
+                tree_prefix,
+                ..
+            } => {
+                let entry = &app.entries[*entry_idx];
+                let args = entry.args();
+                let Some(text) = args.get(*arg_idx).and_then(|arg| arg.get(field.clone())) else {
+                    continue;
+                };
+                let prefix_str = App::tree_prefix_to_string(tree_prefix);
+                let value = argument_value(text);
+                let key = &text[..text.len() - value.len()];
+                let max_len = width.saturating_sub(prefix_str.len() + key.len() + 1);
+                Line::from(vec![
+                    Span::styled(prefix_str, Style::default()),
+                    Span::styled(key.to_string(), Style::default().fg(theme.highlight)),
+                    Span::styled(truncate(value, max_len), Style::default().fg(theme.dim)),
+                ])
+            }
+
+            DisplayLine::Note {
+                entry_idx,
+                tree_prefix,
+                ..
+            } => {
+                let prefix_str = App::tree_prefix_to_string(tree_prefix);
+                let note = app.notes.get(entry_idx).map_or("", |note| note);
+                let max_len = width.saturating_sub(prefix_str.len() + 6);
+                Line::from(vec![
+                    Span::styled(prefix_str, Style::default()),
+                    Span::styled("Note: ", Style::default().fg(theme.highlight)),
+                    Span::styled(truncate(note, max_len), Style::default().fg(theme.text)),
+                ])
+            }
+
+            DisplayLine::MoreArguments {
+                count, tree_prefix, ..
+            } => {
+                let prefix_str = App::tree_prefix_to_string(tree_prefix);
+                Line::from(vec![
+                    Span::styled(prefix_str, Style::default()),
+                    Span::styled(
+                        format_more_arguments(*count),
+                        Style::default().fg(theme.muted),
+                    ),
+                ])
+            }
+
+            DisplayLine::OutputArguments {
+                entry_idx,
+                tree_prefix,
+                ..
+            } => {
+                let prefix_str = App::tree_prefix_to_string(tree_prefix);
+                let output = app.entries[*entry_idx].output_args.as_deref().unwrap_or("");
+                let max_len = width.saturating_sub(prefix_str.len() + 8);
+                Line::from(vec![
+                    Span::styled(prefix_str, Style::default()),
+                    Span::styled("Output: ", Style::default().fg(theme.highlight)),
+                    Span::styled(truncate(output, max_len), Style::default().fg(theme.text)),
+                ])
+            }
+
             DisplayLine::ReturnValue {
                 entry_idx,
                 tree_prefix,
@@ -427,18 +690,17 @@ fn draw_list(f: &mut Frame, app: &mut App, area: Rect) {
             } => {
                 let entry = &app.entries[*entry_idx];
                 let prefix_str = App::tree_prefix_to_string(tree_prefix);
-                let content = if entry.errno.is_some() {
-                    format!(
-                        "Return: {} (error)",
-                        entry.return_value.as_deref().unwrap_or("?")
-                    )
-                } else {
-                    format!("Return: {}", entry.return_value.as_deref().unwrap_or("?"))
-                };
+                let mut content = format_return(entry);
+                if entry.errno.is_some() {
+                    content.push_str(" (error)");
+                }
+                // A poll or select that timed out returned nothing, which is worth telling apart
                 let ret_color = if entry.errno.is_some() {
-                    Color::Red
+                    theme.error
+                } else if entry.return_annotation.as_deref() == Some("Timeout") {
+                    theme.highlight
                 } else {
-                    Color::Green
+                    theme.success
                 };
                 Line::from(vec![
                     Span::styled(prefix_str, Style::default()),
@@ -457,7 +719,7 @@ fn draw_list(f: &mut Frame, app: &mut App, area: Rect) {
                     let content = format!("Error: {} ({})", errno.code, errno.message);
                     Line::from(vec![
                         Span::styled(prefix_str, Style::default()),
-                        Span::styled(content, Style::default().fg(Color::Red)),
+                        Span::styled(content, Style::default().fg(theme.error)),
                     ])
                 } else {
                     continue;
@@ -472,10 +734,11 @@ fn draw_list(f: &mut Frame, app: &mut App, area: Rect) {
                 let entry = &app.entries[*entry_idx];
                 if let Some(dur) = entry.duration {
                     let prefix_str = App::tree_prefix_to_string(tree_prefix);
-                    let content = format!("Duration: {:.6}s", dur);
+                    // Right-aligned so the units line up when scanning expanded entries
+                    let content = format!("Duration: {:>7}", format_duration(dur));
                     Line::from(vec![
                         Span::styled(prefix_str, Style::default()),
-                        Span::styled(content, Style::default().fg(Color::Gray)),
+                        Span::styled(content, Style::default().fg(theme.muted)),
                     ])
                 } else {
                     continue;
@@ -491,20 +754,53 @@ fn draw_list(f: &mut Frame, app: &mut App, area: Rect) {
                 if let Some(ref signal) = entry.signal {
                     let prefix_str = App::tree_prefix_to_string(tree_prefix);
                     let max_len = width.saturating_sub(prefix_str.len() + 9); // "Signal: "
-                    let content = format!(
-                        "Signal: {} - {}",
-                        signal.signal_name,
-                        truncate(&signal.details, max_len)
-                    );
+                    let (content, color) = if signal.stopped {
+                        (format!("Stopped by {}", signal.signal_name), theme.special)
+                    } else if !signal.fields.is_empty() {
+                        // The details are broken out in the lines below
+                        (format!("Signal: {}", signal.signal_name), theme.highlight)
+                    } else {
+                        (
+                            format!(
+                                "Signal: {} - {}",
+                                signal.signal_name,
+                                truncate(&signal.details, max_len)
+                            ),
+                            theme.highlight,
+                        )
+                    };
                     Line::from(vec![
                         Span::styled(prefix_str, Style::default()),
-                        Span::styled(content, Style::default().fg(Color::Yellow)),
+                        Span::styled(content, Style::default().fg(color)),
                     ])
                 } else {
                     continue;
                 }
             }
 
+            DisplayLine::SignalField {
+                entry_idx,
+                field_idx,
+                tree_prefix,
+                ..
+            } => {
+                let entry = &app.entries[*entry_idx];
+                let Some((key, value)) = entry
+                    .signal
+                    .as_ref()
+                    .and_then(|signal| signal.sorted_fields().get(*field_idx).copied())
+                else {
+                    continue;
+                };
+                let prefix_str = App::tree_prefix_to_string(tree_prefix);
+                let max_len = width.saturating_sub(prefix_str.len() + key.len() + 2);
+                Line::from(vec![
+                    Span::styled(prefix_str, Style::default()),
+                    Span::styled(format!("{}=", key), Style::default().fg(theme.highlight)),
+                    Span::styled(truncate(value, max_len), Style::default().fg(theme.dim)),
+                ])
+            }
+
             DisplayLine::Exit {
                 entry_idx,
                 tree_prefix,
@@ -513,14 +809,14 @@ fn draw_list(f: &mut Frame, app: &mut App, area: Rect) {
                 let entry = &app.entries[*entry_idx];
                 if let Some(ref exit) = entry.exit_info {
                     let prefix_str = App::tree_prefix_to_string(tree_prefix);
-                    let content = if exit.killed {
-                        format!("Killed with signal {}", exit.code)
-                    } else {
-                        format!("Exited with code {}", exit.code)
+                    let content = match &exit.signal {
+                        Some(signal) => format!("Killed by {}", signal),
+                        None if exit.killed => "Killed by a signal".to_string(),
+                        None => format!("Exited with code {}", exit.code),
                     };
                     Line::from(vec![
                         Span::styled(prefix_str, Style::default()),
-                        Span::styled(content, Style::default().fg(Color::Cyan)),
+                        Span::styled(content, Style::default().fg(theme.accent)),
                     ])
                 } else {
                     continue;
@@ -545,7 +841,30 @@ fn draw_list(f: &mut Frame, app: &mut App, area: Rect) {
 
                 Line::from(vec![
                     Span::styled(prefix_str, Style::default()),
-                    Span::styled(content, Style::default().fg(Color::DarkGray)),
+                    Span::styled(content, Style::default().fg(theme.dim)),
+                ])
+            }
+
+            DisplayLine::FdReference {
+                entry_idx,
+                ref_idx,
+                tree_prefix,
+                ..
+            } => {
+                let fd_ref = &app.fd_links.get(*entry_idx)[*ref_idx];
+                let creator = &app.entries[fd_ref.creator_idx];
+                let prefix_str = App::tree_prefix_to_string(tree_prefix);
+
+                let content = format!(
+                    "{} created by entry #{} ({})",
+                    fd_ref.inode,
+                    fd_ref.creator_idx + 1,
+                    creator.syscall_name
+                );
+
+                Line::from(vec![
+                    Span::styled(prefix_str, Style::default()),
+                    Span::styled(content, Style::default().fg(theme.dim)),
                 ])
             }
 
@@ -578,7 +897,7 @@ fn draw_list(f: &mut Frame, app: &mut App, area: Rect) {
 
                 Line::from(vec![
                     Span::styled(prefix_str, Style::default()),
-                    Span::styled(content, Style::default().fg(Color::Magenta)),
+                    Span::styled(content, Style::default().fg(theme.special)),
                 ])
             }
 
@@ -605,13 +924,13 @@ fn draw_list(f: &mut Frame, app: &mut App, area: Rect) {
                 let max_binary_len = width.saturating_sub(prefix_str.len() + 10);
                 let content = format!(
                     "{}{} [{}]",
-                    truncate(&frame.binary, max_binary_len),
+                    truncate(app.display_path(&frame.binary), max_binary_len),
                     func_info,
                     frame.address
                 );
                 Line::from(vec![
                     Span::styled(prefix_str, Style::default()),
-                    Span::styled(content, Style::default().fg(Color::DarkGray)),
+                    Span::styled(content, Style::default().fg(theme.dim)),
                 ])
             }
 
@@ -630,14 +949,15 @@ fn draw_list(f: &mut Frame, app: &mut App, area: Rect) {
                     let prefix_str = App::tree_prefix_to_string(tree_prefix);
 
                     // Use intelligent truncation
-                    let content = format_resolved_frame(resolved, prefix_str.len(), width);
+                    let file = app.display_path(&resolved.file);
+                    let content = format_resolved_frame(resolved, file, prefix_str.len(), width);
 
                     let style = if resolved.is_inlined {
                         Style::default()
-                            .fg(Color::Cyan)
+                            .fg(theme.accent)
                             .add_modifier(Modifier::ITALIC)
                     } else {
-                        Style::default().fg(Color::Green)
+                        Style::default().fg(theme.success)
                     };
 
                     Line::from(vec![
@@ -646,9 +966,44 @@ fn draw_list(f: &mut Frame, app: &mut App, area: Rect) {
                     ])
                 } else {
                     // Shouldn't happen, but fallback
-                    Line::from(Span::styled("  <invalid>", Style::default().fg(Color::Red)))
+                    Line::from(Span::styled(
+                        "  <invalid>",
+                        Style::default().fg(theme.error),
+                    ))
                 }
             }
+            DisplayLine::SyscallGroup {
+                entry_idx, count, ..
+            } => {
+                let name = &app.entries[*entry_idx].syscall_name;
+                let expanded = app.expanded_groups.contains(name);
+                let text = format_syscall_group(name, *count);
+                let color =
+                    super::syscall_colors::syscall_category_color(name, &app.config.colors, theme);
+                Line::from(vec![
+                    Span::styled(
+                        if expanded { "▼ " } else { "▶ " },
+                        Style::default().fg(theme.dim),
+                    ),
+                    Span::styled(text[..name.len()].to_string(), Style::default().fg(color)),
+                    Span::styled(
+                        text[name.len()..].to_string(),
+                        Style::default().fg(theme.muted),
+                    ),
+                ])
+            }
+
+            DisplayLine::CollapsedProcess { pid, count, .. } => Line::from(vec![
+                Span::styled("▶ ", Style::default().fg(theme.dim)),
+                Span::styled(
+                    format!("[{}]", pid),
+                    Style::default().fg(app.process_graph.get_color(*pid, &app.theme)),
+                ),
+                Span::styled(
+                    format!(" {} syscalls collapsed", count),
+                    Style::default().fg(theme.muted),
+                ),
+            ]),
         };
 
         // Check if this line is a search match
@@ -662,6 +1017,21 @@ fn draw_list(f: &mut Frame, app: &mut App, area: Rect) {
             DisplayLine::ArgumentLine {
                 is_search_match, ..
             } => *is_search_match,
+            DisplayLine::ArgumentField {
+                is_search_match, ..
+            } => *is_search_match,
+            DisplayLine::MoreArguments {
+                is_search_match, ..
+            } => *is_search_match,
+            DisplayLine::RawArguments {
+                is_search_match, ..
+            } => *is_search_match,
+            DisplayLine::Note {
+                is_search_match, ..
+            } => *is_search_match,
+            DisplayLine::OutputArguments {
+                is_search_match, ..
+            } => *is_search_match,
             DisplayLine::ReturnValue {
                 is_search_match, ..
             } => *is_search_match,
@@ -674,12 +1044,18 @@ fn draw_list(f: &mut Frame, app: &mut App, area: Rect) {
             DisplayLine::Signal {
                 is_search_match, ..
             } => *is_search_match,
+            DisplayLine::SignalField {
+                is_search_match, ..
+            } => *is_search_match,
             DisplayLine::Exit {
                 is_search_match, ..
             } => *is_search_match,
             DisplayLine::EntryReference {
                 is_search_match, ..
             } => *is_search_match,
+            DisplayLine::FdReference {
+                is_search_match, ..
+            } => *is_search_match,
             DisplayLine::BacktraceHeader {
                 is_search_match, ..
             } => *is_search_match,
@@ -689,12 +1065,45 @@ fn draw_list(f: &mut Frame, app: &mut App, area: Rect) {
             DisplayLine::BacktraceResolved {
                 is_search_match, ..
             } => *is_search_match,
+            DisplayLine::SyscallGroup {
+                is_search_match, ..
+            } => *is_search_match,
+            DisplayLine::CollapsedProcess {
+                is_search_match, ..
+            } => *is_search_match,
         };
 
+        // Number the headers, and indent the lines under them to match
+        if number_width > 0 {
+            let number = match display_line {
+                DisplayLine::SyscallHeader { entry_idx, .. } => {
+                    format!("{:>1$} ", entry_idx + 1, number_width - 1)
+                }
+                _ => " ".repeat(number_width),
+            };
+            line_content
+                .spans
+                .insert(0, Span::styled(number, Style::default().fg(theme.dim)));
+        }
+        if !app.bookmarks.is_empty() {
+            let marker = match display_line {
+                DisplayLine::SyscallHeader { entry_idx, .. }
+                    if app.bookmarks.contains(entry_idx) =>
+                {
+                    "● "
+                }
+                _ => "  ",
+            };
+            line_content.spans.insert(
+                0,
+                Span::styled(marker, Style::default().fg(theme.highlight)),
+            );
+        }
+
         // Apply search highlight style
         let item = if is_search_match {
             // Darker yellow for other matches
-            ListItem::new(line_content).style(Style::default().bg(Color::Rgb(60, 60, 0)))
+            ListItem::new(line_content).style(Style::default().bg(theme.search_match))
         } else {
             ListItem::new(line_content)
         };
@@ -704,7 +1113,7 @@ fn draw_list(f: &mut Frame, app: &mut App, area: Rect) {
 
     let list = List::new(items).highlight_style(
         Style::default()
-            .bg(Color::DarkGray)
+            .bg(theme.selection)
             .add_modifier(Modifier::BOLD),
     );
 
@@ -718,8 +1127,31 @@ fn draw_list(f: &mut Frame, app: &mut App, area: Rect) {
 }
 
 fn draw_footer(f: &mut Frame, app: &App, area: Rect) {
-    let mut footer_text = String::from(
-        "?: Help | q: Quit | [Ctrl+] ↑↓/jk: Nav | ←→: Fold | Enter: Toggle | e/c: All | h: Hide | H: Filter | .: Ghost",
+    let theme = &app.theme;
+    if let Some(resolving) = &app.resolving {
+        let (done, total) = resolving.progress.get();
+        const BAR_WIDTH: usize = 20;
+        let filled = (done * BAR_WIDTH).checked_div(total).unwrap_or(0);
+        let footer = Paragraph::new(format!(
+            "Resolving backtraces [{}{}] {}/{}",
+            "█".repeat(filled),
+            "░".repeat(BAR_WIDTH - filled),
+            done,
+            total
+        ))
+        .style(Style::default().fg(theme.highlight));
+        f.render_widget(footer, area);
+        return;
+    }
+    if let Some(message) = &app.status_message {
+        let footer = Paragraph::new(message.as_str()).style(Style::default().fg(theme.highlight));
+        f.render_widget(footer, area);
+        return;
+    }
+
+    let mut footer_text = format!(
+        "?: Help | q: Quit | [Ctrl+] ↑↓/jk: Nav | ←→: Fold | {} | e/c: All | H: Filter | .: Ghost",
+        footer_context_hints(app)
     );
 
     // Add filter status
@@ -731,43 +1163,138 @@ fn draw_footer(f: &mut Frame, app: &App, area: Rect) {
         }
     }
 
-    let footer = Paragraph::new(footer_text).style(Style::default().fg(Color::DarkGray));
+    let footer = Paragraph::new(footer_text).style(Style::default().fg(theme.dim));
     f.render_widget(footer, area);
 }
 
+/// The `[pid]` tag and time at the right of a syscall header, each empty while hidden
+fn header_metadata(app: &App, entry_idx: usize) -> (String, String) {
+    let pid = if app.show_pid_tags {
+        format!("[{}]", app.entries[entry_idx].pid)
+    } else {
+        String::new()
+    };
+    let time = if app.show_timestamps {
+        format!(" {}", header_time(app, entry_idx))
+    } else {
+        String::new()
+    };
+    (pid, time)
+}
+
+/// Timestamp shown on an entry's header, or the time since the previous syscall of its PID
+fn header_time(app: &App, entry_idx: usize) -> String {
+    if app.show_deltas {
+        app.deltas[entry_idx]
+            .map(|delta| format!("+{}", format_duration(delta)))
+            .unwrap_or_default()
+    } else {
+        app.entries[entry_idx].timestamp.clone()
+    }
+}
+
+/// Actions that apply to the selected line, so context-dependent keys are discoverable
+fn footer_context_hints(app: &App) -> &'static str {
+    use super::app::DisplayLine;
+
+    match app.display_lines.get(app.selected_line) {
+        Some(DisplayLine::SyscallHeader { .. }) => "Enter: Expand | h: Hide syscall | y: Copy",
+        Some(DisplayLine::ArgumentsHeader { .. }) => "Enter: Show arguments",
+        Some(DisplayLine::MoreArguments { .. }) => "Enter: Show all arguments",
+        Some(DisplayLine::RawArguments { .. }) => "V: Split arguments",
+        Some(DisplayLine::Note { .. }) => "Enter/a: Edit note",
+        Some(DisplayLine::ArgumentLine { .. } | DisplayLine::ArgumentField { .. }) => {
+            "y/Y: Copy arg/value"
+        }
+        Some(DisplayLine::FdReference { .. }) => "Enter: Jump to creator",
+        Some(DisplayLine::EntryReference { .. }) => "Enter: Jump to linked entry",
+        Some(DisplayLine::BacktraceHeader { .. }) => "Enter: Resolve backtrace | F: Hide all",
+        Some(DisplayLine::BacktraceFrame { .. }) => "f: Strip paths",
+        Some(DisplayLine::BacktraceResolved { .. }) => "Enter: Open editor | w: Open link",
+        Some(DisplayLine::SyscallGroup { .. }) => "Enter: Toggle group | Z: Ungroup",
+        Some(DisplayLine::CollapsedProcess { .. }) => "Enter/z: Expand process",
+        _ => "Enter: Toggle | h: Hide",
+    }
+}
+
 fn draw_search_bar(f: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
     let match_info = if app.search_state.matches.is_empty() {
         if app.search_state.query.is_empty() {
             String::new()
         } else {
-            "No matches".to_string()
+            // Tell how far the query got before it stopped matching
+            match &app.search_state.last_hit {
+                Some((hit, count)) if app.search_state.query.starts_with(hit.as_str()) => {
+                    format!("No matches, {} for \"{}\"", count, hit)
+                }
+                _ => "No matches".to_string(),
+            }
         }
     } else {
         format!(
-            "Match {}/{}",
+            "Match {}/{} in {} entries",
             app.search_state.current_match_idx + 1,
-            app.search_state.matches.len()
+            app.search_state.matches.len(),
+            app.search_state.matched_entries
         )
     };
 
+    let scope = match app.search_state.scope {
+        SearchScope::All => String::new(),
+        scope => format!(" in {}", scope.label()),
+    };
     let text = if match_info.is_empty() {
         format!(
-            "Search: {}█  Enter:accept | Esc: cancel | Ctrl-n/N: next/prev",
-            app.search_state.query
+            "Search{}: {}█  Enter:accept | Esc: cancel | Ctrl-n/N: next/prev | Tab: scope",
+            scope, app.search_state.query
         )
     } else {
         format!(
-            "Search: {}█  [{}]  Enter:accept | Esc: cancel | Ctrl-n/N: next/prev",
-            app.search_state.query, match_info
+            "Search{}: {}█  [{}]  Enter:accept | Esc: cancel | Ctrl-n/N: next/prev | Tab: scope",
+            scope, app.search_state.query, match_info
         )
     };
 
-    let paragraph = Paragraph::new(text).style(Style::default().fg(Color::White));
+    let paragraph = Paragraph::new(text).style(Style::default().fg(theme.text));
+
+    f.render_widget(paragraph, area);
+}
+
+fn draw_duration_prompt(f: &mut Frame, app: &App, input: &str, area: Rect) {
+    let theme = &app.theme;
+    let untimed = if app.keep_untimed { "shown" } else { "hidden" };
+    let text = format!(
+        "Min duration: {}█  (e.g. 1ms, 250us) Enter: apply | Tab: untimed entries {} | Esc: cancel",
+        input, untimed
+    );
+
+    let paragraph = Paragraph::new(text).style(Style::default().fg(theme.text));
+    f.render_widget(paragraph, area);
+}
+
+fn draw_jump_prompt(f: &mut Frame, app: &App, input: &str, area: Rect) {
+    let text = format!(
+        "Go to entry: {}█  (1-{}, or 0x hex) Enter: jump | Esc: cancel",
+        input,
+        app.entries.len()
+    );
+
+    let paragraph = Paragraph::new(text).style(Style::default().fg(app.theme.text));
+    f.render_widget(paragraph, area);
+}
+
+fn draw_note_prompt(f: &mut Frame, app: &App, input: &str, area: Rect) {
+    let text = format!(
+        "Note: {}█  Enter: save (empty removes it) | Esc: cancel",
+        input
+    );
 
+    let paragraph = Paragraph::new(text).style(Style::default().fg(app.theme.text));
     f.render_widget(paragraph, area);
 }
 
-fn draw_help(f: &mut Frame) {
+fn draw_help(f: &mut Frame, theme: &Theme) {
     let left_help_text = vec![
         Line::from(Span::styled(
             "Navigation:",
@@ -777,12 +1304,20 @@ fn draw_help(f: &mut Frame) {
         Line::from("  ↓/j         Move down one line"),
         Line::from("  Ctrl+↑/k    Previous with same PID"),
         Line::from("  Ctrl+↓/j    Next with same PID"),
+        Line::from("  ]/[         Next/previous failed syscall"),
+        Line::from("  s/S         Next/previous signal or exit"),
+        Line::from("  b/B         Next/previous bookmark"),
         Line::from("  PageUp      Scroll up one page"),
         Line::from("  PageDown    Scroll down one page"),
         Line::from("  Ctrl+U      Scroll up half page"),
         Line::from("  Ctrl+D      Scroll down half page"),
-        Line::from("  Home/g      Jump to first item"),
+        Line::from("  Home/gg     Jump to first item"),
         Line::from("  End/G       Jump to last item"),
+        Line::from("  N G / N gg  Jump to entry N"),
+        Line::from("  N j / N k   Move N lines"),
+        Line::from("  :           Jump to entry number"),
+        Line::from("  p           Process list / jump to PID"),
+        Line::from("  t           Process tree panel"),
         Line::from(""),
         Line::from(Span::styled(
             "Actions:",
@@ -794,11 +1329,35 @@ fn draw_help(f: &mut Frame) {
         Line::from("  →           Expand item"),
         Line::from("  e           Expand all syscalls"),
         Line::from("  c           Collapse all items"),
+        Line::from("  f           Strip common path prefix"),
+        Line::from("  F           Hide/show all backtraces"),
+        Line::from("  r           Resolve the entry's backtrace"),
+        Line::from("  R           Resolve all backtraces"),
+        Line::from("  |           Toggle process graph"),
+        Line::from("  o           Sort by duration (desc/asc/off)"),
+        Line::from("  Z           Group entries by syscall"),
+        Line::from("  w           Open source link in browser"),
+        Line::from("  W           Wrap long arguments"),
+        Line::from("  V           Show arguments unsplit"),
+        Line::from("  i           Time since previous syscall of the PID"),
+        Line::from("  T           Hide/show timestamps"),
+        Line::from("  I           Hide/show PID tags"),
+        Line::from("  #           Show entry numbers"),
+        Line::from("  m           Bookmark entry"),
+        Line::from("  a           Add or edit a note on the entry"),
+        Line::from("  z           Collapse/expand the entry's process"),
+        Line::from("  y           Copy entry, or the argument under the cursor"),
+        Line::from("  Y           Copy argument value"),
+        Line::from("  v           Show the entry's raw input line"),
+        Line::from("  K           Describe the entry's syscall"),
+        Line::from("  !           Show lines that failed to parse"),
         Line::from(""),
         Line::from(Span::styled(
             "Other:",
             Style::default().add_modifier(Modifier::UNDERLINED),
         )),
+        Line::from("  }/{         Next/previous file"),
+        Line::from("  O           Show program output"),
         Line::from("  q/Q         Quit"),
         Line::from("  ?           Toggle this help"),
         Line::from("  Ctrl+C      Force quit"),
@@ -812,6 +1371,11 @@ fn draw_help(f: &mut Frame) {
         Line::from("  h           Hide/show current syscall"),
         Line::from("  H           Open filter modal"),
         Line::from("  .           Toggle show hidden"),
+        Line::from("  +/-         Raise/lower min duration"),
+        Line::from("  >           Type a min duration"),
+        Line::from("  E           Filter by errno"),
+        Line::from("  %           Syscall time summary"),
+        Line::from("  D           Syscall duration histogram"),
         Line::from(""),
         Line::from(Span::styled(
             "Filter Modal:",
@@ -828,13 +1392,14 @@ fn draw_help(f: &mut Frame) {
         Line::from("  /           Start search"),
         Line::from("  n           Next match"),
         Line::from("  N           Previous match"),
+        Line::from("  Tab         Cycle search scope"),
         Line::from("  Enter       Accept search"),
         Line::from("  Esc         Cancel search"),
         Line::from(""),
         Line::from(""),
         Line::from(Span::styled(
             "Press ? or Esc to close help",
-            Style::default().fg(Color::Yellow),
+            Style::default().fg(theme.highlight),
         )),
     ];
 
@@ -865,6 +1430,7 @@ fn draw_help(f: &mut Frame) {
 }
 
 fn draw_filter_modal(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
     let modal_state = &app.filter_modal_state;
     let area = centered_rect(70, 70, f.area());
 
@@ -910,11 +1476,11 @@ fn draw_filter_modal(f: &mut Frame, app: &App) {
             let text = format!("{} {} ({} calls)", checkbox, name, count);
 
             let style = if is_current_match {
-                Style::default().bg(Color::Yellow).fg(Color::Black)
+                Style::default().bg(theme.highlight).fg(theme.on_highlight)
             } else if is_match {
-                Style::default().bg(Color::DarkGray).fg(Color::Yellow)
+                Style::default().bg(theme.selection).fg(theme.highlight)
             } else if is_hidden {
-                Style::default().fg(Color::DarkGray)
+                Style::default().fg(theme.dim)
             } else {
                 Style::default()
             };
@@ -933,7 +1499,7 @@ fn draw_filter_modal(f: &mut Frame, app: &App) {
         .block(Block::default().borders(Borders::ALL).title(title))
         .highlight_style(
             Style::default()
-                .bg(Color::DarkGray)
+                .bg(theme.selection)
                 .add_modifier(Modifier::BOLD),
         );
 
@@ -952,7 +1518,428 @@ fn draw_filter_modal(f: &mut Frame, app: &App) {
     }
 }
 
+fn draw_errno_filter(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let modal_state = &app.errno_filter_state;
+    let area = centered_rect(50, 50, f.area());
+
+    // Account for borders
+    let visible_height = area.height.saturating_sub(2) as usize;
+    let start = modal_state.scroll_offset;
+    let end = (start + visible_height).min(modal_state.codes.len());
+
+    let mut lines = Vec::new();
+    for (idx, (code, count)) in modal_state.codes[start..end].iter().enumerate() {
+        let selected = app.errno_filter.contains(code);
+        let checkbox = if selected { "[✓]" } else { "[ ]" };
+        let text = format!("{} {} ({} calls)", checkbox, code, count);
+
+        let style = if start + idx == modal_state.selected_index {
+            Style::default()
+                .bg(theme.selection)
+                .add_modifier(Modifier::BOLD)
+        } else if selected {
+            Style::default().fg(theme.error)
+        } else {
+            Style::default()
+        };
+        lines.push(Line::from(Span::styled(text, style)));
+    }
+
+    let paragraph = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("Filter by Errno (Space: Toggle | a: Clear | q/Esc: Close)"),
+    );
+
+    f.render_widget(ratatui::widgets::Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+fn draw_stats_modal(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let modal_state = &app.stats_modal_state;
+    let area = centered_rect(70, 70, f.area());
+
+    // Account for borders and the column header
+    let visible_height = area.height.saturating_sub(3) as usize;
+    let start = modal_state.scroll_offset;
+    let end = (start + visible_height).min(modal_state.rows.len());
+
+    let header_style = Style::default()
+        .fg(theme.accent)
+        .add_modifier(Modifier::BOLD);
+    let mut lines = vec![Line::from(Span::styled(
+        format!(
+            "{:>7} {:>10} {:>10} {:>8} {:>7}  {}",
+            "% time", "total", "avg", "calls", "errors", "syscall"
+        ),
+        header_style,
+    ))];
+
+    for (idx, row) in modal_state.rows[start..end].iter().enumerate() {
+        let errors = if row.errors > 0 {
+            row.errors.to_string()
+        } else {
+            String::new()
+        };
+        let text = format!(
+            "{:>7.2} {:>10} {:>10} {:>8} {:>7}  {}",
+            row.percent_time,
+            format_duration(row.total_time),
+            format_duration(row.average_time()),
+            row.calls,
+            errors,
+            row.name
+        );
+
+        let style = if start + idx == modal_state.selected_index {
+            Style::default()
+                .bg(theme.selection)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default()
+        };
+        lines.push(Line::from(Span::styled(text, style)));
+    }
+
+    let paragraph = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("Syscall Time Summary (q/Esc: Close)"),
+    );
+
+    f.render_widget(ratatui::widgets::Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+fn draw_histogram(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let modal_state = &app.histogram_state;
+    let area = centered_rect(70, 50, f.area());
+
+    // Account for borders and the column header
+    let visible_height = area.height.saturating_sub(3) as usize;
+    let start = modal_state.scroll_offset;
+    let end = (start + visible_height).min(modal_state.buckets.len());
+
+    // Bars take what the borders and the label and count columns leave, with a margin, the
+    // fullest bucket the whole of it
+    let bar_width = (area.width as usize).saturating_sub(2 + 19 + 1);
+    let max_count = modal_state
+        .buckets
+        .iter()
+        .map(|bucket| bucket.count)
+        .max()
+        .unwrap_or(0);
+
+    let header_style = Style::default()
+        .fg(theme.accent)
+        .add_modifier(Modifier::BOLD);
+    let mut lines = vec![Line::from(Span::styled(
+        format!("{:>8} {:>8}", "duration", "calls"),
+        header_style,
+    ))];
+
+    for (idx, bucket) in modal_state.buckets[start..end].iter().enumerate() {
+        let label = if bucket.upper.is_finite() {
+            format!("< {}", format_duration(bucket.upper))
+        } else {
+            let previous = modal_state.buckets[modal_state.buckets.len() - 2].upper;
+            format!(">= {}", format_duration(previous))
+        };
+        // Round up, so that no call goes without a mark
+        let bar_len = if max_count > 0 {
+            (bucket.count * bar_width).div_ceil(max_count)
+        } else {
+            0
+        };
+
+        let mut spans = vec![
+            Span::raw(format!("{:>8} {:>8}  ", label, bucket.count)),
+            Span::styled("█".repeat(bar_len), Style::default().fg(theme.accent)),
+        ];
+
+        if start + idx == modal_state.selected_index {
+            let selected = Style::default()
+                .bg(theme.selection)
+                .add_modifier(Modifier::BOLD);
+            spans = spans
+                .into_iter()
+                .map(|span| span.patch_style(selected))
+                .collect();
+        }
+        lines.push(Line::from(spans));
+    }
+
+    let title = match &modal_state.syscall {
+        Some(name) if modal_state.filtered => {
+            format!("Durations of {} (Tab: All syscalls | q/Esc: Close)", name)
+        }
+        Some(name) => format!("Syscall Durations (Tab: Only {} | q/Esc: Close)", name),
+        None => "Syscall Durations (q/Esc: Close)".to_string(),
+    };
+    let paragraph =
+        Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(title));
+
+    f.render_widget(ratatui::widgets::Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+fn draw_pid_picker(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let modal_state = &app.pid_picker_state;
+    let area = centered_rect(70, 50, f.area());
+
+    // Account for borders and the column header
+    let visible_height = area.height.saturating_sub(3) as usize;
+    let start = modal_state.scroll_offset;
+    let end = (start + visible_height).min(modal_state.rows.len());
+
+    let header_style = Style::default()
+        .fg(theme.accent)
+        .add_modifier(Modifier::BOLD);
+    let mut lines = vec![Line::from(Span::styled(
+        format!(
+            "{:>8} {:>7} {:>7} {:>8} {:>8}  {}",
+            "pid", "calls", "failed", "time", "lifetime", "command"
+        ),
+        header_style,
+    ))];
+
+    for (idx, row) in modal_state.rows[start..end].iter().enumerate() {
+        let mut spans = vec![
+            Span::styled(
+                format!("{:>8}", row.pid),
+                Style::default().fg(app.process_graph.get_color(row.pid, &app.theme)),
+            ),
+            Span::raw(format!(
+                " {:>7} {:>7} {:>8} {:>8}  {}",
+                row.calls,
+                row.failed,
+                format_duration(row.total_time),
+                row.lifetime(&app.entries)
+                    .map(format_duration)
+                    .unwrap_or_default(),
+                row.command.as_deref().unwrap_or("")
+            )),
+        ];
+
+        if start + idx == modal_state.selected_index {
+            let selected = Style::default()
+                .bg(theme.selection)
+                .add_modifier(Modifier::BOLD);
+            spans = spans
+                .into_iter()
+                .map(|span| span.patch_style(selected))
+                .collect();
+        }
+        lines.push(Line::from(spans));
+    }
+
+    let paragraph = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("Processes (Enter: Jump to first entry | q/Esc: Close)"),
+    );
+
+    f.render_widget(ratatui::widgets::Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+fn draw_process_tree(f: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
+    let panel_state = &app.process_tree_state;
+
+    // Account for borders
+    let visible_height = area.height.saturating_sub(2) as usize;
+    let start = panel_state.scroll_offset;
+    let end = (start + visible_height).min(panel_state.rows.len());
+    let width = area.width.saturating_sub(2) as usize;
+
+    let mut lines = Vec::new();
+    for (idx, row) in panel_state.rows[start..end].iter().enumerate() {
+        let indent = "  ".repeat(row.depth);
+        let pid = row.pid.to_string();
+        let command = app
+            .pid_picker_state
+            .rows
+            .iter()
+            .find(|summary| summary.pid == row.pid)
+            .and_then(|summary| summary.command.as_deref())
+            .map(|command| {
+                let name = command.rsplit('/').next().unwrap_or(command);
+                truncate(name, width.saturating_sub(indent.len() + pid.len() + 1))
+            })
+            .unwrap_or_default();
+
+        let mut spans = vec![
+            Span::raw(indent),
+            Span::styled(
+                pid,
+                Style::default().fg(app.process_graph.get_color(row.pid, &app.theme)),
+            ),
+            Span::styled(format!(" {}", command), Style::default().fg(theme.dim)),
+        ];
+
+        if start + idx == panel_state.selected_index {
+            let selected = Style::default()
+                .bg(theme.selection)
+                .add_modifier(Modifier::BOLD);
+            spans = spans
+                .into_iter()
+                .map(|span| span.patch_style(selected))
+                .collect();
+        }
+        lines.push(Line::from(spans));
+    }
+
+    let paragraph = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("Processes (Enter: Jump | t: Close)"),
+    );
+    f.render_widget(paragraph, area);
+}
+
+fn draw_program_output(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let modal_state = &app.program_output_state;
+    let area = centered_rect(80, 70, f.area());
+
+    // Account for borders
+    let visible_height = area.height.saturating_sub(2) as usize;
+    let start = modal_state.scroll_offset;
+    let end = (start + visible_height).min(app.program_output.len());
+
+    let mut lines = Vec::new();
+    for (idx, (line_number, text)) in app.program_output[start..end].iter().enumerate() {
+        let mut spans = vec![
+            Span::styled(
+                format!("{:>6} ", line_number),
+                Style::default().fg(theme.dim),
+            ),
+            Span::raw(text.as_str()),
+        ];
+
+        if start + idx == modal_state.selected_index {
+            let selected = Style::default()
+                .bg(theme.selection)
+                .add_modifier(Modifier::BOLD);
+            spans = spans
+                .into_iter()
+                .map(|span| span.patch_style(selected))
+                .collect();
+        }
+        lines.push(Line::from(spans));
+    }
+
+    let paragraph =
+        Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(format!(
+            "Program Output, {} lines (q/Esc: Close)",
+            app.program_output.len()
+        )));
+
+    f.render_widget(ratatui::widgets::Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+fn draw_parse_errors(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let modal_state = &app.parse_errors_state;
+    let area = centered_rect(80, 70, f.area());
+
+    // Account for borders
+    let visible_height = area.height.saturating_sub(2) as usize;
+    let start = modal_state.scroll_offset;
+    let end = (start + visible_height).min(app.parse_errors.len());
+
+    let mut lines = Vec::new();
+    for (idx, error) in app.parse_errors[start..end].iter().enumerate() {
+        let mut spans = vec![
+            Span::styled(
+                format!("{:>6} ", error.line_number),
+                Style::default().fg(theme.dim),
+            ),
+            Span::styled(error.message.as_str(), Style::default().fg(theme.error)),
+        ];
+        if let Some(raw_line) = &error.raw_line {
+            spans.push(Span::raw(format!("  {}", raw_line)));
+        }
+
+        if start + idx == modal_state.selected_index {
+            let selected = Style::default()
+                .bg(theme.selection)
+                .add_modifier(Modifier::BOLD);
+            spans = spans
+                .into_iter()
+                .map(|span| span.patch_style(selected))
+                .collect();
+        }
+        lines.push(Line::from(spans));
+    }
+
+    let paragraph =
+        Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(format!(
+            "Parse Errors, {} lines (Enter: Jump | q/Esc: Close)",
+            app.parse_errors.len()
+        )));
+
+    f.render_widget(ratatui::widgets::Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+fn draw_raw_line(f: &mut Frame, app: &App) {
+    let Some(line) = app.display_lines.get(app.selected_line) else {
+        return;
+    };
+    let entry_idx = line.entry_idx();
+    let raw_line = &app.entries[entry_idx].raw_line;
+    let area = centered_rect(80, 30, f.area());
+
+    let paragraph = Paragraph::new(raw_line.as_str())
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("Raw Line of #{} (v/Esc: Close)", entry_idx + 1)),
+        )
+        .wrap(Wrap { trim: false });
+
+    f.render_widget(ratatui::widgets::Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+fn draw_syscall_help(f: &mut Frame, app: &App) {
+    use super::syscall_colors::syscall_description;
+
+    let Some(line) = app.display_lines.get(app.selected_line) else {
+        return;
+    };
+    let entry = &app.entries[line.entry_idx()];
+    // Signals and exits are named after the syscalls they aren't
+    let description = if entry.signal.is_some() {
+        "a signal delivered to the process, not a syscall"
+    } else if entry.exit_info.is_some() {
+        "the end of the process, not a syscall"
+    } else {
+        syscall_description(&entry.syscall_name).unwrap_or("no description available")
+    };
+    let area = centered_rect(60, 20, f.area());
+
+    let paragraph = Paragraph::new(format!("{} - {}", entry.syscall_name, description))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("{} (K/Esc: Close)", entry.syscall_name)),
+        )
+        .wrap(Wrap { trim: false });
+
+    f.render_widget(ratatui::widgets::Clear, area);
+    f.render_widget(paragraph, area);
+}
+
 fn draw_modal_search_bar(f: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
     let query = &app.modal_search_state.query;
     let match_info = if app.modal_search_state.matches.is_empty() {
         if query.is_empty() {
@@ -974,7 +1961,7 @@ fn draw_modal_search_bar(f: &mut Frame, app: &App, area: Rect) {
     );
 
     let search_bar =
-        Paragraph::new(search_text).style(Style::default().bg(Color::DarkGray).fg(Color::White));
+        Paragraph::new(search_text).style(Style::default().bg(theme.selection).fg(theme.text));
 
     f.render_widget(search_bar, area);
 }
@@ -1059,9 +2046,11 @@ fn truncate_path_with_line(path: &str, line: u32, column: Option<u32>, max_len:
     )
 }
 
-/// Format a resolved frame with intelligent truncation
+/// Format a resolved frame with intelligent truncation. `file` is the source path to display,
+/// which may have its common prefix stripped.
 fn format_resolved_frame(
     resolved: &crate::parser::ResolvedFrame,
+    file: &str,
     prefix_len: usize,
     width: usize,
 ) -> String {
@@ -1082,9 +2071,9 @@ fn format_resolved_frame(
 
     // Build full location string
     let location = if let Some(col) = resolved.column {
-        format!("{}:{}:{}", resolved.file, resolved.line, col)
+        format!("{}:{}:{}", file, resolved.line, col)
     } else {
-        format!("{}:{}", resolved.file, resolved.line)
+        format!("{}:{}", file, resolved.line)
     };
 
     let function_len = resolved.function.len();
@@ -1126,12 +2115,7 @@ fn format_resolved_frame(
 
     // Truncate location intelligently
     let location_display = if location_len > location_budget {
-        truncate_path_with_line(
-            &resolved.file,
-            resolved.line,
-            resolved.column,
-            location_budget,
-        )
+        truncate_path_with_line(file, resolved.line, resolved.column, location_budget)
     } else {
         location.clone()
     };
@@ -1175,3 +2159,50 @@ fn centered_rect_absolute(width: u16, height: u16, r: Rect) -> Rect {
         height: height.min(r.height),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{StraceParser, SummaryStats};
+    use crate::tui::Config;
+    use crossterm::event::{KeyCode, KeyEvent};
+    use ratatui::{Terminal, backend::TestBackend};
+
+    fn render(app: &mut App) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(80, 10)).unwrap();
+        terminal.draw(|f| draw(f, app)).unwrap();
+        let buffer = terminal.backend().buffer();
+        buffer
+            .content()
+            .chunks(buffer.area.width as usize)
+            .map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn test_hide_header_metadata() {
+        let entries = StraceParser::new()
+            .parse_lines(
+                std::iter::once("1234 10:20:30 getpid() = 1234".to_string()),
+                false,
+            )
+            .unwrap();
+        let summary = SummaryStats::from_entries(&entries);
+        let mut app = App::new(entries, summary, None, Config::default());
+
+        let screen = render(&mut app);
+        assert!(screen.contains("[1234] 10:20:30"));
+
+        app.handle_event(KeyEvent::from(KeyCode::Char('T')));
+        let screen = render(&mut app);
+        assert!(screen.contains("getpid() = 1234"));
+        assert!(screen.contains("[1234]"));
+        assert!(!screen.contains("10:20:30"));
+
+        app.handle_event(KeyEvent::from(KeyCode::Char('I')));
+        let screen = render(&mut app);
+        assert!(screen.contains("getpid() = 1234"));
+        assert!(!screen.contains("[1234]"));
+    }
+}
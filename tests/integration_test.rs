@@ -1,5 +1,5 @@
 use std::io::Write;
-use strace_tui::{Addr2LineResolver, StraceParser};
+use strace_tui::{Addr2LineResolver, BacktraceFrame, StraceParser, SummaryStats};
 use tempfile::NamedTempFile;
 
 #[test]
@@ -70,6 +70,8 @@ fn test_unfinished_resumed() {
 #[test]
 fn test_addr2line_resolver() {
     let mut resolver = Addr2LineResolver::new();
+    // /bin/ls is stripped, so the bias can't be learned from `main`
+    resolver.set_load_bias("/bin/ls", 0);
 
     // Create a dummy frame
     let mut frame = strace_tui::BacktraceFrame {
@@ -251,6 +253,666 @@ fn test_cli_parse_subcommand() {
     assert!(parsed["summary"].is_object());
 }
 
+#[test]
+fn test_cli_process_tree() {
+    use std::process::Command;
+
+    // 100 forks 200, which runs ls
+    let sample = r#"100 10:20:30 clone(child_stack=NULL, flags=SIGCHLD) = 200
+200 10:20:30 execve("/bin/ls", ["ls"], 0x7ffd /* 3 vars */) = 0
+200 10:20:31 exit_group(0) = ?
+100 10:20:31 wait4(-1, NULL, 0, NULL) = 200
+"#;
+
+    let mut temp_file = NamedTempFile::new().unwrap();
+    temp_file.write_all(sample.as_bytes()).unwrap();
+    let temp_path = temp_file.path().to_str().unwrap();
+
+    Command::new("cargo")
+        .args(["build", "--quiet"])
+        .status()
+        .expect("Failed to build");
+
+    let output = Command::new("./target/debug/strace-tui")
+        .args(["parse", temp_path, "--json", "--process-tree"])
+        .output()
+        .expect("Failed to run parse command");
+    assert!(output.status.success(), "parse command should succeed");
+
+    let parsed: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let tree = parsed["process_tree"].as_array().unwrap();
+    assert_eq!(tree.len(), 2);
+
+    assert_eq!(tree[0]["pid"], 100);
+    assert_eq!(tree[0]["parent_pid"], serde_json::Value::Null);
+    assert_eq!(tree[0]["children"], serde_json::json!([200]));
+    assert_eq!(tree[0]["first_entry_idx"], 0);
+    assert_eq!(tree[0]["last_entry_idx"], 3);
+
+    assert_eq!(tree[1]["pid"], 200);
+    assert_eq!(tree[1]["parent_pid"], 100);
+    assert_eq!(tree[1]["children"], serde_json::json!([]));
+    assert_eq!(tree[1]["program"], "/bin/ls");
+
+    // Only there when asked for
+    let output = Command::new("./target/debug/strace-tui")
+        .args(["parse", temp_path, "--json"])
+        .output()
+        .expect("Failed to run parse command");
+    let parsed: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(parsed.get("process_tree").is_none());
+}
+
+#[test]
+fn test_cli_parse_stdin() {
+    use std::process::{Command, Stdio};
+
+    let sample = "12345 10:20:30 write(1, \"test\\n\", 5) = 5\n12345 10:20:31 close(1) = 0\n";
+
+    Command::new("cargo")
+        .args(["build", "--quiet"])
+        .status()
+        .expect("Failed to build");
+
+    let mut child = Command::new("./target/debug/strace-tui")
+        .args(["parse", "-", "--json"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Failed to run parse command");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(sample.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    assert!(output.status.success(), "parse command should succeed");
+
+    let parsed: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("Output should be valid JSON");
+    let entries = parsed["entries"].as_array().unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0]["syscall_name"], "write");
+    assert_eq!(entries[1]["syscall_name"], "close");
+}
+
+#[test]
+fn test_cli_parse_chrome_format() {
+    use std::process::Command;
+
+    let sample = "100 10:20:30.000000 openat(AT_FDCWD, \"x\", O_RDONLY) = 3 <0.000100>\n\
+                  101 10:20:30.000050 read(3,  <unfinished ...>\n\
+                  100 10:20:30.000200 close(3) = 0\n\
+                  101 10:20:30.001000 <... read resumed>\"\", 1) = 0 <0.000950>\n\
+                  100 10:20:30.002000 +++ exited with 0 +++\n";
+    let mut temp_file = NamedTempFile::new().unwrap();
+    temp_file.write_all(sample.as_bytes()).unwrap();
+
+    Command::new("cargo")
+        .args(["build", "--quiet"])
+        .status()
+        .expect("Failed to build");
+
+    let output = Command::new("./target/debug/strace-tui")
+        .args(["parse", "--format", "chrome"])
+        .arg(temp_file.path())
+        .output()
+        .expect("Failed to run parse command");
+    assert!(output.status.success(), "parse command should succeed");
+
+    let parsed: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("Output should be valid JSON");
+    let events = parsed["traceEvents"].as_array().unwrap();
+
+    // The unfinished read and its resumed half make a single event
+    let phases: Vec<(&str, &str)> = events
+        .iter()
+        .map(|event| {
+            (
+                event["name"].as_str().unwrap(),
+                event["ph"].as_str().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        phases,
+        [
+            ("openat", "X"),
+            ("read", "X"),
+            ("close", "i"),
+            ("exit", "i")
+        ]
+    );
+
+    assert_eq!(events[0]["ts"], 0.0);
+    assert_eq!(events[1]["pid"], 101);
+    assert_eq!(events[1]["ts"], 50.0);
+    assert!((events[1]["dur"].as_f64().unwrap() - 950.0).abs() < 1e-6);
+    assert_eq!(events[1]["args"]["return_value"], "0");
+}
+
+#[test]
+fn test_parse_errors_keep_raw_line() {
+    let sample = "12345 10:20:30 close(1) = 0\n >\n12345 10:20:31 read(3, \"x\", 1) = 1\n";
+
+    let mut parser = StraceParser::new();
+    let entries = parser
+        .parse_lines(sample.lines().map(str::to_string), true)
+        .unwrap();
+
+    assert_eq!(entries.len(), 2);
+    assert_eq!(parser.errors.len(), 1);
+
+    let (line, err) = &parser.errors[0];
+    assert_eq!(*line, 2);
+    assert_eq!(err.kind(), "invalid_backtrace");
+    assert_eq!(parser.error_lines[line], " >");
+}
+
+#[test]
+fn test_backtrace_after_parse_error_is_dropped() {
+    let sample = "\
+12345 10:20:30 close(1) = 0
+ > /usr/lib/libc.so.6(close+0x14) [0x10e5d4]
+   read(3, \"x\", 1) = 1
+ > /usr/lib/libc.so.6(read+0x14) [0x10e6d4]
+ > /usr/bin/app(main+0x2a) [0x1129]
+12345 10:20:32 getpid() = 12345
+ > /usr/lib/libc.so.6(getpid+0x5) [0x10e7d4]
+";
+
+    // A call indented as if it were part of the line before isn't one the parser accepts
+    let mut parser = StraceParser::new();
+    let entries = parser
+        .parse_lines(sample.lines().map(str::to_string), false)
+        .unwrap();
+
+    assert_eq!(parser.errors.len(), 1);
+    assert_eq!(parser.errors[0].0, 3);
+    assert_eq!(entries.len(), 2);
+
+    // The frames of the bad line don't go to the call before it
+    assert_eq!(entries[0].backtrace.len(), 1);
+    assert_eq!(entries[0].backtrace[0].function.as_deref(), Some("close"));
+    assert_eq!(entries[1].backtrace.len(), 1);
+    assert_eq!(entries[1].backtrace[0].function.as_deref(), Some("getpid"));
+}
+
+#[test]
+fn test_program_output_is_not_an_error() {
+    let sample = "Starting build...\n12345 10:20:30 close(1) = 0\nhello, world\n";
+
+    let mut parser = StraceParser::new();
+    let entries = parser
+        .parse_lines(sample.lines().map(str::to_string), false)
+        .unwrap();
+
+    assert_eq!(entries.len(), 1);
+    assert!(parser.errors.is_empty());
+    assert_eq!(
+        parser.program_output,
+        vec![
+            (1, "Starting build...".to_string()),
+            (3, "hello, world".to_string())
+        ]
+    );
+}
+
+#[test]
+fn test_parse_return_continuation_line() {
+    let sample =
+        "12345 10:20:30 write(1, \"hi\\n\", 3)\n = 3 <0.000010>\n12345 10:20:31 close(1) = 0\n";
+
+    let mut parser = StraceParser::new();
+    let entries = parser
+        .parse_lines(sample.lines().map(str::to_string), false)
+        .unwrap();
+
+    assert!(parser.errors.is_empty());
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].syscall_name, "write");
+    assert_eq!(entries[0].return_value, Some("3".to_string()));
+    assert_eq!(entries[0].duration, Some(0.00001));
+    assert_eq!(entries[1].return_value, Some("0".to_string()));
+}
+
+#[test]
+fn test_max_entries_stops_early() {
+    let sample = "1 10:00:00 open(\"/a\", O_RDONLY) = 3\n\
+                  1 10:00:00 read(3, \"x\", 1) = 1\n\
+                  1 10:00:00 close(3) = 0\n";
+
+    let mut parser = StraceParser::new();
+    parser.max_entries = Some(2);
+    let entries = parser
+        .parse_lines(sample.lines().map(str::to_string), false)
+        .unwrap();
+
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[1].syscall_name, "read");
+    assert!(parser.truncated);
+
+    // A cap the trace fits in doesn't truncate
+    let mut parser = StraceParser::new();
+    parser.max_entries = Some(3);
+    let entries = parser
+        .parse_lines(sample.lines().map(str::to_string), false)
+        .unwrap();
+
+    assert_eq!(entries.len(), 3);
+    assert!(!parser.truncated);
+}
+
+#[test]
+fn test_summary_io_bytes() {
+    let sample = "1 10:00:00 read(3, \"abcd\", 4) = 4\n\
+                  1 10:00:00 read(3, \"\", 4) = 0\n\
+                  1 10:00:00 read(4, 0x7ffd, 4) = -1 EAGAIN (Resource temporarily unavailable)\n\
+                  1 10:00:00 pread64(3, \"xy\", 2, 8) = 2\n\
+                  2 10:00:00 write(1, \"hello\\n\", 6 <unfinished ...>\n\
+                  1 10:00:00 sendto(5, \"ping\", 4, 0, NULL, 0) = 4\n\
+                  2 10:00:00 <... write resumed>) = 6\n\
+                  1 10:00:00 close(3) = 0\n";
+
+    let mut parser = StraceParser::new();
+    let entries = parser
+        .parse_lines(sample.lines().map(str::to_string), false)
+        .unwrap();
+    let summary = SummaryStats::from_entries(&entries);
+
+    assert_eq!(summary.bytes_read, 6);
+    assert_eq!(summary.bytes_written, 10);
+    assert_eq!(summary.io_bytes.get("read"), Some(&4));
+    assert_eq!(summary.io_bytes.get("pread64"), Some(&2));
+    assert_eq!(summary.io_bytes.get("write"), Some(&6));
+    assert_eq!(summary.io_bytes.get("sendto"), Some(&4));
+    assert_eq!(summary.io_bytes.get("close"), None);
+}
+
+#[test]
+fn test_backtrace_after_interleaved_lines() {
+    let sample = "1 10:00:00 read(3, <unfinished ...>\n \
+                  > /usr/lib/libc.so.6(read+0x14) [0x10e53e]\n\
+                  2 10:00:00 write(1, \"x\", 1) = 1\n \
+                  > /usr/lib/libc.so.6(write+0x14) [0x10e600]\n\
+                  2 10:00:00 --- SIGCHLD {si_signo=SIGCHLD, si_code=CLD_EXITED} ---\n \
+                  > /usr/bin/app(main+0x20) [0x1120]\n\
+                  3 10:00:00 +++ exited with 0 +++\n \
+                  > /usr/bin/app(exit+0x20) [0x1180]\n\
+                  1 10:00:00 <... read resumed>\"\", 1) = 0\n \
+                  > /usr/lib/libc.so.6(__libc_read+0x14) [0x10e540]\n";
+    let frames = |entry: &strace_tui::SyscallEntry| -> Vec<String> {
+        entry
+            .backtrace
+            .iter()
+            .map(|frame| frame.function.clone().unwrap_or_default())
+            .collect()
+    };
+
+    // Signals and exits never have a backtrace, so the frames after a signal go to the latest
+    // syscall of the same PID, and are dropped when it has none or exited
+    let entries = StraceParser::new()
+        .parse_lines(sample.lines().map(str::to_string), false)
+        .unwrap();
+    assert_eq!(entries.len(), 5);
+    assert_eq!(frames(&entries[0]), ["read"]);
+    assert_eq!(frames(&entries[1]), ["write", "main"]);
+    assert!(entries[2].backtrace.is_empty());
+    assert!(entries[3].backtrace.is_empty());
+    assert_eq!(frames(&entries[4]), ["__libc_read"]);
+
+    // Streamed, the frames land on the same entries
+    let streamed: Vec<_> = StraceParser::new()
+        .parse_streaming(sample.lines().map(str::to_string))
+        .collect::<Result<_, _>>()
+        .unwrap();
+    let all_frames = |entries: &[strace_tui::SyscallEntry]| -> Vec<Vec<String>> {
+        entries.iter().map(frames).collect()
+    };
+    assert_eq!(all_frames(&streamed), all_frames(&entries));
+
+    // Merged, the read has the stack of its return
+    let entries = StraceParser::new()
+        .parse_lines(sample.lines().map(str::to_string), true)
+        .unwrap();
+    assert_eq!(entries.len(), 4);
+    assert_eq!(frames(&entries[0]), ["__libc_read"]);
+    assert_eq!(frames(&entries[1]), ["write", "main"]);
+}
+
+#[test]
+fn test_dangling_unfinished_calls() {
+    let sample = "1 10:00:00 read(0, <unfinished ...>\n\
+                  2 10:00:00 write(1, \"x\", 1 <unfinished ...>\n\
+                  2 10:00:00 <... write resumed>) = 1\n\
+                  1 10:00:01 +++ killed by SIGKILL +++\n";
+
+    let mut parser = StraceParser::new();
+    let entries = parser
+        .parse_lines(sample.lines().map(str::to_string), false)
+        .unwrap();
+
+    assert_eq!(parser.dangling_unfinished, 1);
+    assert!(entries[0].never_resumed);
+    assert!(!entries[1].never_resumed);
+}
+
+#[test]
+fn test_interleaved_unfinished_calls() {
+    // Each PID's call is unfinished while the others start, resume and complete theirs
+    let sample = "1 10:00:00 read(3, <unfinished ...>\n\
+                  2 10:00:00 write(4, \"a\", 1 <unfinished ...>\n\
+                  3 10:00:00 poll([{fd=5, events=POLLIN}], 1, -1 <unfinished ...>\n\
+                  2 10:00:00 <... write resumed>) = 1\n\
+                  1 10:00:00 getpid() = 1\n\
+                  3 10:00:00 <... poll resumed>) = 1 ([{fd=5, revents=POLLIN}])\n\
+                  2 10:00:00 fsync(4 <unfinished ...>\n\
+                  1 10:00:00 <... read resumed>\"xy\", 2) = 2\n\
+                  3 10:00:00 close(5) = 0\n\
+                  2 10:00:00 <... fsync resumed>) = 0\n";
+    let lines = || sample.lines().map(str::to_string);
+
+    let entries = StraceParser::new().parse_lines(lines(), false).unwrap();
+    let links: Vec<_> = entries
+        .iter()
+        .map(|entry| {
+            (
+                entry.pid,
+                entry.resumed_entry_idx,
+                entry.unfinished_entry_idx,
+            )
+        })
+        .collect();
+    assert_eq!(
+        links,
+        [
+            (1, Some(7), None),
+            (2, Some(3), None),
+            (3, Some(5), None),
+            (2, None, Some(1)),
+            (1, None, None),
+            (3, None, Some(2)),
+            (2, Some(9), None),
+            (1, None, Some(0)),
+            (3, None, None),
+            (2, None, Some(6)),
+        ]
+    );
+
+    let mut parser = StraceParser::new();
+    let entries = parser.parse_lines(lines(), true).unwrap();
+    assert!(parser.errors.is_empty());
+    let calls: Vec<_> = entries
+        .iter()
+        .map(|entry| {
+            (
+                entry.pid,
+                entry.syscall_name.as_str(),
+                entry.return_value.as_deref(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        calls,
+        [
+            (1, "read", Some("2")),
+            (2, "write", Some("1")),
+            (3, "poll", Some("1")),
+            (1, "getpid", Some("1")),
+            (2, "fsync", Some("0")),
+            (3, "close", Some("0")),
+        ]
+    );
+    assert!(entries.iter().all(|entry| !entry.is_unfinished));
+}
+
+#[test]
+fn test_orphan_resumed_call() {
+    // The trace was started while the read was blocked
+    let sample = "1 10:00:00 <... read resumed>\"xy\", 2) = 2\n";
+
+    for merge_resumed in [false, true] {
+        let mut parser = StraceParser::new();
+        let entries = parser
+            .parse_lines(sample.lines().map(str::to_string), merge_resumed)
+            .unwrap();
+        assert!(parser.errors.is_empty());
+        assert_eq!(entries.len(), 1);
+        assert!(entries[0].is_resumed);
+        assert!(entries[0].orphan_resumed);
+        assert_eq!(entries[0].return_value.as_deref(), Some("2"));
+    }
+}
+
+#[test]
+fn test_raw_line() {
+    let sample = "\
+1234  10:00:00.000001 openat(AT_FDCWD, \"/etc/passwd\", O_RDONLY) = 3
+1234  10:00:00.000002 read(3, <unfinished ...>
+1234  10:00:00.000003 <... read resumed>\"root\", 4) = 4
+";
+    let lines: Vec<&str> = sample.lines().collect();
+
+    let mut parser = StraceParser::new();
+    let entries = parser
+        .parse_lines(sample.lines().map(str::to_string), false)
+        .unwrap();
+    assert_eq!(entries.len(), 3);
+    for (entry, line) in entries.iter().zip(&lines) {
+        assert_eq!(entry.raw_line, *line);
+    }
+
+    // A merged call keeps both of its lines
+    let mut parser = StraceParser::new();
+    let entries = parser
+        .parse_lines(sample.lines().map(str::to_string), true)
+        .unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].raw_line, lines[0]);
+    assert_eq!(entries[1].raw_line, format!("{}\n{}", lines[1], lines[2]));
+}
+
+#[test]
+fn test_strip_log_prefix() {
+    let sample = "\
+[2024-01-01T00:00:00] 1234  10:00:00 openat(AT_FDCWD, \"/etc/passwd\", O_RDONLY) = 3
+[2024-01-01T00:00:00]  > /usr/lib/libc.so.6(open64+0x5b) [0x10e5d4]
+[2024-01-01T00:00:01] 1234  10:00:01 write(1, \"hi\", 2
+[2024-01-01T00:00:01] = 2
+[2024-01-01T00:00:02] server started
+[2024-01-01T00:00:03] retries = 5
+2024-01-01 00:00:04,120 [main]: 1234  10:00:04 close(3) = 0
+";
+
+    let mut parser = StraceParser::new();
+    parser.strip_log_prefix = true;
+    let entries = parser
+        .parse_lines(sample.lines().map(str::to_string), false)
+        .unwrap();
+    assert!(parser.errors.is_empty());
+    assert_eq!(entries.len(), 3);
+
+    assert_eq!(entries[0].pid, 1234);
+    assert_eq!(entries[0].syscall_name, "openat");
+    assert_eq!(entries[0].return_value.as_deref(), Some("3"));
+    assert_eq!(entries[0].backtrace.len(), 1);
+    assert_eq!(entries[0].backtrace[0].function.as_deref(), Some("open64"));
+    assert_eq!(
+        entries[0].raw_line,
+        "1234  10:00:00 openat(AT_FDCWD, \"/etc/passwd\", O_RDONLY) = 3"
+    );
+
+    assert_eq!(entries[1].syscall_name, "write");
+    assert_eq!(entries[1].return_value.as_deref(), Some("2"));
+    assert_eq!(entries[2].raw_line, "1234  10:00:04 close(3) = 0");
+
+    // Other log lines are kept whole, even with a ` = ` in their text
+    let output: Vec<_> = parser
+        .program_output
+        .iter()
+        .map(|(_, line)| line.as_str())
+        .collect();
+    assert_eq!(
+        output,
+        [
+            "[2024-01-01T00:00:02] server started",
+            "[2024-01-01T00:00:03] retries = 5"
+        ]
+    );
+
+    // Without the option, the prefixed lines aren't strace output
+    let mut parser = StraceParser::new();
+    let entries = parser
+        .parse_lines(sample.lines().map(str::to_string), false)
+        .unwrap();
+    assert!(entries.is_empty());
+}
+
+#[test]
+fn test_unfinished_call_found_after_entries_dropped() {
+    let mut parser = StraceParser::new();
+    let mut entries = Vec::new();
+    let lines = |lines: &[&str]| {
+        lines
+            .iter()
+            .map(|line| line.to_string())
+            .collect::<Vec<_>>()
+    };
+    parser.parse_more(
+        lines(&[
+            "2 10:00:00 write(1, \"x\", 1) = 1",
+            "1 10:00:00 read(3, <unfinished ...>",
+        ]),
+        true,
+        &mut entries,
+    );
+
+    // The reader dropped the older entries, which moves the pending read
+    entries.remove(0);
+    parser.parse_more(
+        lines(&["1 10:00:01 <... read resumed>\"\", 1) = 0"]),
+        true,
+        &mut entries,
+    );
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].syscall_name, "read");
+    assert_eq!(entries[0].return_value.as_deref(), Some("0"));
+    assert!(!entries[0].is_unfinished);
+}
+
+#[test]
+fn test_streaming_large_trace() {
+    // 3 entries per round: an unfinished read, another process's write, and the resumed read
+    const ROUNDS: usize = 50_000;
+    let lines = (0..ROUNDS).flat_map(|i| {
+        [
+            format!("100 10:00:00.{:06} read(3, <unfinished ...>", i % 1_000_000),
+            format!("200 10:00:00.{:06} write(1, \"x\", 1) = 1", i % 1_000_000),
+            " > /usr/lib/libc.so.6(write+0x14) [0x10e5d4]".to_string(),
+            format!(
+                "100 10:00:00.{:06} <... read resumed>\"y\", 1) = 1",
+                i % 1_000_000
+            ),
+        ]
+    });
+
+    let mut parser = StraceParser::new();
+    let mut count = 0;
+    for (idx, entry) in parser.parse_streaming(lines).enumerate() {
+        let entry = entry.unwrap();
+        match idx % 3 {
+            0 => assert!(entry.is_unfinished),
+            1 => assert_eq!(entry.backtrace.len(), 1),
+            _ => {
+                assert!(entry.is_resumed);
+                assert_eq!(entry.unfinished_entry_idx, Some(idx - 2));
+            }
+        }
+        count += 1;
+    }
+
+    assert_eq!(count, ROUNDS * 3);
+    assert!(parser.errors.is_empty());
+}
+
+#[test]
+fn test_streaming_matches_parse_lines() {
+    let sample = "1 10:00:00 open(\"/a\", O_RDONLY) = 3\n\
+                  hello from the program\n\
+                  1 10:00:00 write(1, \"hi\\n\", 3)\n = 3\n\
+                  1 10:00:00 <... read resumed>\"x\", 1) = 1\n\
+                  2 10:00:00 poll([{fd=3, events=POLLIN}], 1, -1 <unfinished ...>\n\
+                  1 10:00:00 write(1, \"x\", 1) = 1\n\
+                  1 10:00:00 --- SIGCHLD {si_signo=SIGCHLD, si_code=CLD_EXITED} ---\n \
+                  > /usr/lib/libc.so.6(write+0x14) [0x10e5d4]\n\
+                  2 10:00:01 <... poll resumed>) = 1 ([{fd=3, revents=POLLIN}])\n\
+                  3 10:00:01 read(0, <unfinished ...>\n\
+                  1 10:00:01 +++ exited with 0 +++\n";
+
+    let expected = StraceParser::new()
+        .parse_lines(sample.lines().map(str::to_string), false)
+        .unwrap();
+
+    let mut parser = StraceParser::new();
+    let streamed: Vec<_> = parser
+        .parse_streaming(sample.lines().map(str::to_string))
+        .collect::<Result<_, _>>()
+        .unwrap();
+
+    // Everything matches, but the signal fields, which are in hash order in the debug output
+    let json = |entries: &[strace_tui::SyscallEntry]| serde_json::to_value(entries).unwrap();
+    assert_eq!(json(&streamed), json(&expected));
+    for (a, b) in streamed.iter().zip(&expected) {
+        assert_eq!((&a.raw_line, a.line_number), (&b.raw_line, b.line_number));
+    }
+    assert_eq!(parser.program_output.len(), 1);
+
+    // The frame after the signal goes to the write before it
+    assert_eq!(streamed[4].syscall_name, "write");
+    assert_eq!(streamed[4].backtrace.len(), 1);
+    assert_eq!(streamed[3].resumed_entry_idx, Some(6));
+    assert!(streamed[7].never_resumed);
+}
+
+/// Write a script that stands in for strace, so the `trace` tests don't need it installed. It
+/// runs the command, then writes a made-up trace of it where strace would: to the `-o` file, or
+/// else to stderr. `-e trace=write` leaves only the write.
+fn fake_strace(dir: &std::path::Path) -> String {
+    use std::os::unix::fs::PermissionsExt;
+
+    let path = dir.join("fake-strace");
+    std::fs::write(
+        &path,
+        r#"#!/bin/sh
+out=/dev/stderr
+filter=
+while [ "$#" -gt 0 ]; do
+    case "$1" in
+        -o) out="$2"; shift 2 ;;
+        -e) filter="$2"; shift 2 ;;
+        -s) shift 2 ;;
+        -*) shift ;;
+        *) break ;;
+    esac
+done
+"$@"
+{
+    if [ "$filter" != trace=write ]; then
+        echo "$$ 10:00:00 execve(\"$1\", [\"$1\"], 0x7ffe /* 1 vars */) = 0"
+    fi
+    echo "$$ 10:00:00 write(1, \"test\\n\", 5) = 5"
+    echo " > /usr/lib/libc.so.6(write+0x14) [0x10e5d4]"
+    echo "$$ 10:00:00 +++ exited with 0 +++"
+} > "$out"
+"#,
+    )
+    .unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    path.to_str().unwrap().to_string()
+}
+
 #[test]
 fn test_cli_trace_subcommand() {
     use std::process::Command;
@@ -288,3 +950,236 @@ fn test_cli_trace_subcommand() {
     let syscall_count = parsed["summary"]["total_syscalls"].as_u64().unwrap();
     assert!(syscall_count > 0, "Should trace at least one syscall");
 }
+
+#[test]
+fn test_cli_trace_strace_args() {
+    use std::process::Command;
+
+    Command::new("cargo")
+        .args(["build", "--quiet"])
+        .status()
+        .expect("Failed to build");
+
+    let temp_output = NamedTempFile::new().unwrap();
+    let output_path = temp_output.path().to_str().unwrap();
+    let dir = tempfile::tempdir().unwrap();
+
+    let output = Command::new("./target/debug/strace-tui")
+        .args(["trace", "--json", "--output", output_path])
+        .args(["--strace-bin", &fake_strace(dir.path())])
+        .args([
+            "--strace-arg=-e",
+            "--strace-arg=trace=write",
+            "echo",
+            "test",
+        ])
+        .output()
+        .expect("Failed to run trace command");
+    assert!(output.status.success(), "trace command should succeed");
+
+    let json_str = std::fs::read_to_string(output_path).expect("Failed to read output file");
+    let parsed: serde_json::Value =
+        serde_json::from_str(&json_str).expect("Output should be valid JSON");
+
+    // Only writes were traced, besides the exit of the process
+    let syscalls: Vec<_> = parsed["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|entry| entry["signal"].is_null() && entry["exit_info"].is_null())
+        .collect();
+    assert!(!syscalls.is_empty());
+    assert!(
+        syscalls
+            .iter()
+            .all(|entry| entry["syscall_name"] == "write")
+    );
+}
+
+#[test]
+fn test_cli_trace_refuses_strace_output_arg() {
+    use std::process::Command;
+
+    Command::new("cargo")
+        .args(["build", "--quiet"])
+        .status()
+        .expect("Failed to build");
+
+    let output = Command::new("./target/debug/strace-tui")
+        .args([
+            "trace",
+            "--json",
+            "--strace-arg=-o",
+            "--strace-arg=x",
+            "true",
+        ])
+        .output()
+        .expect("Failed to run trace command");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("-o is not allowed"));
+}
+
+#[test]
+fn test_cli_trace_strace_bin() {
+    use std::os::unix::fs::PermissionsExt;
+    use std::process::Command;
+
+    Command::new("cargo")
+        .args(["build", "--quiet"])
+        .status()
+        .expect("Failed to build");
+
+    // Stands in for strace, writing a single syscall where it is told to
+    let dir = tempfile::tempdir().unwrap();
+    let wrapper = dir.path().join("fake-strace");
+    std::fs::write(
+        &wrapper,
+        "#!/bin/sh\n\
+         while [ \"$#\" -gt 0 ]; do\n\
+             if [ \"$1\" = -o ]; then out=\"$2\"; fi\n\
+             shift\n\
+         done\n\
+         echo '1 10:00:00 getpid() = 1' > \"$out\"\n",
+    )
+    .unwrap();
+    std::fs::set_permissions(&wrapper, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let wrapper = wrapper.to_str().unwrap();
+
+    let check = |output: std::process::Output| {
+        assert!(output.status.success(), "trace command should succeed");
+        let parsed: serde_json::Value =
+            serde_json::from_slice(&output.stdout).expect("Output should be valid JSON");
+        assert_eq!(parsed["entries"][0]["syscall_name"], "getpid");
+    };
+
+    check(
+        Command::new("./target/debug/strace-tui")
+            .args(["trace", "--json", "--strace-bin", wrapper, "true"])
+            .output()
+            .expect("Failed to run trace command"),
+    );
+    check(
+        Command::new("./target/debug/strace-tui")
+            .args(["trace", "--json", "true"])
+            .env("STRACE_TUI_STRACE", wrapper)
+            .output()
+            .expect("Failed to run trace command"),
+    );
+}
+
+#[test]
+fn test_cli_trace_stream() {
+    use std::process::Command;
+
+    Command::new("cargo")
+        .args(["build", "--quiet"])
+        .status()
+        .expect("Failed to build");
+
+    let temp_output = NamedTempFile::new().unwrap();
+    let output_path = temp_output.path().to_str().unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+
+    let output = Command::new("./target/debug/strace-tui")
+        .args(["trace", "--stream", "--json", "--output", output_path])
+        .args(["--strace-bin", &fake_strace(dir.path()), "echo", "test"])
+        .output()
+        .expect("Failed to run trace command");
+    assert!(output.status.success(), "trace command should succeed");
+
+    let json_str = std::fs::read_to_string(output_path).expect("Failed to read output file");
+    let parsed: serde_json::Value =
+        serde_json::from_str(&json_str).expect("Output should be valid JSON");
+
+    // The syscalls came through the pipe, and so did the command's output
+    let entries = parsed["entries"].as_array().unwrap();
+    assert!(
+        entries
+            .iter()
+            .any(|entry| entry["syscall_name"] == "execve")
+    );
+    let write = entries
+        .iter()
+        .find(|entry| entry["syscall_name"] == "write")
+        .expect("echo should write");
+    assert!(
+        !write["backtrace"].as_array().unwrap().is_empty(),
+        "backtrace lines should be attached to their syscall"
+    );
+    assert!(
+        parsed["program_output"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|line| line["text"] == "test")
+    );
+}
+
+#[test]
+fn test_resolve_pie_with_load_bias() {
+    use object::{Object, ObjectKind, ObjectSymbol};
+    use std::process::Command;
+
+    Command::new("cargo")
+        .args(["build", "--quiet", "--example", "syscall_test"])
+        .status()
+        .expect("Failed to build");
+    let binary = "./target/debug/examples/syscall_test";
+
+    let data = std::fs::read(binary).unwrap();
+    let file = object::File::parse(&*data).unwrap();
+    assert_eq!(file.kind(), ObjectKind::Dynamic, "example should be PIE");
+    let symbol = file
+        .symbols()
+        .find(|symbol| {
+            symbol
+                .name()
+                .is_ok_and(|name| name.contains("syscall_test") && name.contains("test_file_io"))
+        })
+        .unwrap();
+    let (name, symbol_address) = (symbol.name().unwrap(), symbol.address());
+
+    let frame = |function: Option<&str>, address: u64| BacktraceFrame {
+        binary: binary.to_string(),
+        function: function.map(str::to_string),
+        offset: function.map(|_| "0x10".to_string()),
+        address: format!("{:#x}", address),
+        resolved: None,
+    };
+    let resolve = |resolver: &mut Addr2LineResolver, mut frame: BacktraceFrame| {
+        resolver.resolve_frame(&mut frame).unwrap();
+        frame.resolved.map(|resolved| format!("{:?}", resolved))
+    };
+
+    // What the link-time addresses resolve to
+    let mut plain = Addr2LineResolver::new();
+    let expected = resolve(&mut plain, frame(None, symbol_address + 0x10));
+    let expected_next = resolve(&mut plain, frame(None, symbol_address + 0x20));
+    assert!(expected.as_ref().unwrap().contains("syscall_test.rs"));
+
+    // As seen at runtime, with the binary loaded somewhere else
+    let bias = 0x5555_5555_0000;
+
+    // The bias is learned from a frame with function+offset, and applied to one without
+    let mut resolver = Addr2LineResolver::new();
+    assert_eq!(
+        resolve(
+            &mut resolver,
+            frame(Some(name), bias + symbol_address + 0x10)
+        ),
+        expected
+    );
+    assert_eq!(
+        resolve(&mut resolver, frame(None, bias + symbol_address + 0x20)),
+        expected_next
+    );
+
+    // Or given up front
+    let mut resolver = Addr2LineResolver::new();
+    resolver.set_load_bias(binary, bias);
+    assert_eq!(
+        resolve(&mut resolver, frame(None, bias + symbol_address + 0x20)),
+        expected_next
+    );
+}